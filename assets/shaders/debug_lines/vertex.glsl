@@ -0,0 +1,12 @@
+#version 330 core
+layout(location = 0) in vec3 aPos;
+layout(location = 1) in vec3 aColor;
+
+uniform mat4 viewProjection;
+
+out vec3 Color;
+
+void main() {
+    gl_Position = viewProjection * vec4(aPos, 1.0);
+    Color = aColor;
+}
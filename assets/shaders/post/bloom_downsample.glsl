@@ -0,0 +1,68 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoords;
+
+uniform sampler2D sourceTexture;
+uniform vec2 sourceTexelSize;
+uniform bool firstLevel = false;   // Reading the scene, apply the threshold
+uniform float threshold = 1.0;
+
+float luminance(vec3 color)
+{
+    return dot(color, vec3(0.2126, 0.7152, 0.0722));
+}
+
+// Weights bright samples down so single hot pixels like the sun disc don't flicker
+vec3 karisAverage(vec3 a, vec3 b, vec3 c, vec3 d)
+{
+    vec4 sum = vec4(0.0);
+    sum += vec4(a, 1.0) / (1.0 + luminance(a));
+    sum += vec4(b, 1.0) / (1.0 + luminance(b));
+    sum += vec4(c, 1.0) / (1.0 + luminance(c));
+    sum += vec4(d, 1.0) / (1.0 + luminance(d));
+    return sum.rgb / sum.a;
+}
+
+void main()
+{
+    vec2 t = sourceTexelSize;
+
+    // 13 tap filter, each 2x2 box is weighted so the result stays energy preserving
+    vec3 a = texture(sourceTexture, TexCoords + t * vec2(-2.0, 2.0)).rgb;
+    vec3 b = texture(sourceTexture, TexCoords + t * vec2(0.0, 2.0)).rgb;
+    vec3 c = texture(sourceTexture, TexCoords + t * vec2(2.0, 2.0)).rgb;
+    vec3 d = texture(sourceTexture, TexCoords + t * vec2(-2.0, 0.0)).rgb;
+    vec3 e = texture(sourceTexture, TexCoords).rgb;
+    vec3 f = texture(sourceTexture, TexCoords + t * vec2(2.0, 0.0)).rgb;
+    vec3 g = texture(sourceTexture, TexCoords + t * vec2(-2.0, -2.0)).rgb;
+    vec3 h = texture(sourceTexture, TexCoords + t * vec2(0.0, -2.0)).rgb;
+    vec3 i = texture(sourceTexture, TexCoords + t * vec2(2.0, -2.0)).rgb;
+    vec3 j = texture(sourceTexture, TexCoords + t * vec2(-1.0, 1.0)).rgb;
+    vec3 k = texture(sourceTexture, TexCoords + t * vec2(1.0, 1.0)).rgb;
+    vec3 l = texture(sourceTexture, TexCoords + t * vec2(-1.0, -1.0)).rgb;
+    vec3 m = texture(sourceTexture, TexCoords + t * vec2(1.0, -1.0)).rgb;
+
+    vec3 color;
+    if (firstLevel)
+    {
+        color = karisAverage(j, k, l, m) * 0.5
+            + karisAverage(a, b, d, e) * 0.125
+            + karisAverage(b, c, e, f) * 0.125
+            + karisAverage(d, e, g, h) * 0.125
+            + karisAverage(e, f, h, i) * 0.125;
+
+        // Soft threshold, only the part above it blooms
+        float brightness = luminance(color);
+        color *= max(brightness - threshold, 0.0) / max(brightness, 0.0001);
+    }
+    else
+    {
+        color = e * 0.125
+            + (a + c + g + i) * 0.03125
+            + (b + d + f + h) * 0.0625
+            + (j + k + l + m) * 0.125;
+    }
+
+    FragColor = vec4(max(color, vec3(0.0)), 1.0);
+}
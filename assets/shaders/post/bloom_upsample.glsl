@@ -0,0 +1,25 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoords;
+
+uniform sampler2D sourceTexture;
+uniform vec2 sourceTexelSize;
+
+void main()
+{
+    vec2 t = sourceTexelSize;
+
+    // 3x3 tent filter, added on top of the larger level by blending
+    vec3 color = texture(sourceTexture, TexCoords).rgb * 4.0;
+    color += (texture(sourceTexture, TexCoords + t * vec2(0.0, 1.0)).rgb
+        + texture(sourceTexture, TexCoords + t * vec2(0.0, -1.0)).rgb
+        + texture(sourceTexture, TexCoords + t * vec2(1.0, 0.0)).rgb
+        + texture(sourceTexture, TexCoords + t * vec2(-1.0, 0.0)).rgb) * 2.0;
+    color += texture(sourceTexture, TexCoords + t * vec2(-1.0, 1.0)).rgb
+        + texture(sourceTexture, TexCoords + t * vec2(1.0, 1.0)).rgb
+        + texture(sourceTexture, TexCoords + t * vec2(-1.0, -1.0)).rgb
+        + texture(sourceTexture, TexCoords + t * vec2(1.0, -1.0)).rgb;
+
+    FragColor = vec4(color / 16.0, 1.0);
+}
@@ -0,0 +1,62 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoords;
+
+uniform sampler2D sceneColor;
+uniform sampler2D ambientOcclusion;
+uniform sampler2D bloomTexture;
+
+uniform bool ssaoEnabled = false;
+uniform float ssaoIntensity = 1.0;
+uniform bool bloomEnabled = false;
+uniform float bloomIntensity = 0.04;
+uniform bool tonemapping = true;
+uniform float exposure = 1.0;
+
+// The fitted curve darkens mid tones, this keeps exposure 1 close to the untonemapped image
+const float ACES_PRE_EXPOSURE = 1.6;
+
+// Stephen Hill's fit of the ACES reference rendering and output transforms
+const mat3 ACES_INPUT = mat3(
+    0.59719, 0.07600, 0.02840,
+    0.35458, 0.90834, 0.13383,
+    0.04823, 0.01566, 0.83777);
+const mat3 ACES_OUTPUT = mat3(
+    1.60475, -0.10208, -0.00327,
+    -0.53108, 1.10813, -0.07276,
+    -0.07367, -0.00605, 1.07602);
+
+vec3 rrtAndOdtFit(vec3 v)
+{
+    vec3 a = v * (v + 0.0245786) - 0.000090537;
+    vec3 b = v * (0.983729 * v + 0.4329510) + 0.238081;
+    return a / b;
+}
+
+vec3 acesFitted(vec3 color)
+{
+    color = ACES_INPUT * (color * ACES_PRE_EXPOSURE);
+    color = rrtAndOdtFit(color);
+    return clamp(ACES_OUTPUT * color, 0.0, 1.0);
+}
+
+void main()
+{
+    vec3 color = texture(sceneColor, TexCoords).rgb;
+
+    // Applied to all light, not just ambient, there is no depth prepass to do better
+    if (ssaoEnabled)
+        color *= pow(texture(ambientOcclusion, TexCoords).r, ssaoIntensity);
+    if (bloomEnabled)
+        color = mix(color, texture(bloomTexture, TexCoords).rgb, bloomIntensity);
+
+    color *= exposure;
+    color = tonemapping ? acesFitted(color) : clamp(color, 0.0, 1.0);
+
+    // Lighting is done in linear space, convert to sRGB for display
+    color = pow(color, vec3(1.0 / 2.2));
+
+    // FXAA reads luma from alpha
+    FragColor = vec4(color, dot(color, vec3(0.299, 0.587, 0.114)));
+}
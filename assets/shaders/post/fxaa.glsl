@@ -0,0 +1,46 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 TexCoords;
+
+uniform sampler2D displayTexture;   // Tonemapped colour with luma in alpha
+uniform vec2 texelSize;
+
+const float FXAA_SPAN_MAX = 8.0;
+const float FXAA_REDUCE_MUL = 1.0 / 8.0;
+const float FXAA_REDUCE_MIN = 1.0 / 128.0;
+
+// Lottes' FXAA, the small console version: blur along the edge direction found from luma
+void main()
+{
+    float lumaNW = texture(displayTexture, TexCoords + vec2(-1.0, -1.0) * texelSize).a;
+    float lumaNE = texture(displayTexture, TexCoords + vec2(1.0, -1.0) * texelSize).a;
+    float lumaSW = texture(displayTexture, TexCoords + vec2(-1.0, 1.0) * texelSize).a;
+    float lumaSE = texture(displayTexture, TexCoords + vec2(1.0, 1.0) * texelSize).a;
+    vec4 center = texture(displayTexture, TexCoords);
+    float lumaM = center.a;
+
+    float lumaMin = min(lumaM, min(min(lumaNW, lumaNE), min(lumaSW, lumaSE)));
+    float lumaMax = max(lumaM, max(max(lumaNW, lumaNE), max(lumaSW, lumaSE)));
+
+    vec2 direction = vec2(
+        -((lumaNW + lumaNE) - (lumaSW + lumaSE)),
+        (lumaNW + lumaSW) - (lumaNE + lumaSE));
+
+    float directionReduce = max((lumaNW + lumaNE + lumaSW + lumaSE) * 0.25 * FXAA_REDUCE_MUL, FXAA_REDUCE_MIN);
+    float inverseSmallest = 1.0 / (min(abs(direction.x), abs(direction.y)) + directionReduce);
+    direction = clamp(direction * inverseSmallest, vec2(-FXAA_SPAN_MAX), vec2(FXAA_SPAN_MAX)) * texelSize;
+
+    vec3 resultA = 0.5 * (
+        texture(displayTexture, TexCoords + direction * (1.0 / 3.0 - 0.5)).rgb +
+        texture(displayTexture, TexCoords + direction * (2.0 / 3.0 - 0.5)).rgb);
+    vec3 resultB = resultA * 0.5 + 0.25 * (
+        texture(displayTexture, TexCoords + direction * -0.5).rgb +
+        texture(displayTexture, TexCoords + direction * 0.5).rgb);
+
+    // The wide blur overshot the local range, it crossed another edge
+    float lumaB = dot(resultB, vec3(0.299, 0.587, 0.114));
+    vec3 color = (lumaB < lumaMin || lumaB > lumaMax) ? resultA : resultB;
+
+    FragColor = vec4(color, 1.0);
+}
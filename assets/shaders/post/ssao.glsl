@@ -0,0 +1,62 @@
+#version 330 core
+out float FragColor;
+
+in vec2 TexCoords;
+
+const int KERNEL_SIZE = 16;   // SSAO_KERNEL_SIZE in post_processing.rs
+
+uniform sampler2D sceneDepth;
+uniform mat4 projection;
+uniform mat4 inverseProjection;
+uniform vec3 ssaoKernel[KERNEL_SIZE];  // Hemisphere offsets around +Z
+uniform float radius = 1.5;            // World units searched for occluders
+
+vec3 viewPosition(vec2 uv)
+{
+    float depth = texture(sceneDepth, uv).r;
+    vec4 position = inverseProjection * vec4(vec3(uv, depth) * 2.0 - 1.0, 1.0);
+    return position.xyz / position.w;
+}
+
+// Per-pixel rotation of the kernel, turns banding into noise the blur removes
+float interleavedGradientNoise(vec2 pixel)
+{
+    return fract(52.9829189 * fract(dot(pixel, vec2(0.06711056, 0.00583715))));
+}
+
+void main()
+{
+    // Nothing to occlude in the sky
+    if (texture(sceneDepth, TexCoords).r >= 1.0)
+    {
+        FragColor = 1.0;
+        return;
+    }
+
+    vec3 position = viewPosition(TexCoords);
+    vec3 normal = normalize(cross(dFdx(position), dFdy(position)));
+
+    float angle = interleavedGradientNoise(gl_FragCoord.xy) * 6.2831853;
+    vec3 randomVector = vec3(cos(angle), sin(angle), 0.0);
+    vec3 tangent = normalize(randomVector - normal * dot(randomVector, normal));
+    vec3 bitangent = cross(normal, tangent);
+    mat3 TBN = mat3(tangent, bitangent, normal);
+
+    // Bias grows with distance, depth precision drops further away
+    float bias = 0.02 - position.z * 0.001;
+    float occlusion = 0.0;
+    for (int i = 0; i < KERNEL_SIZE; ++i)
+    {
+        vec3 samplePosition = position + TBN * ssaoKernel[i] * radius;
+
+        vec4 offset = projection * vec4(samplePosition, 1.0);
+        vec2 sampleUv = offset.xy / offset.w * 0.5 + 0.5;
+        float sceneDepthAtSample = viewPosition(sampleUv).z;
+
+        // Ignore occluders far in front of the point, they belong to other geometry
+        float rangeCheck = smoothstep(0.0, 1.0, radius / abs(position.z - sceneDepthAtSample));
+        occlusion += (sceneDepthAtSample >= samplePosition.z + bias ? 1.0 : 0.0) * rangeCheck;
+    }
+
+    FragColor = 1.0 - occlusion / float(KERNEL_SIZE);
+}
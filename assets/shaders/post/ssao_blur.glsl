@@ -0,0 +1,21 @@
+#version 330 core
+out float FragColor;
+
+in vec2 TexCoords;
+
+uniform sampler2D ambientOcclusion;
+uniform vec2 texelSize;
+
+void main()
+{
+    // 4x4 box matches the tiling of the noise pattern closely enough to hide it
+    float result = 0.0;
+    for (int x = -2; x < 2; ++x)
+    {
+        for (int y = -2; y < 2; ++y)
+        {
+            result += texture(ambientOcclusion, TexCoords + (vec2(x, y) + 0.5) * texelSize).r;
+        }
+    }
+    FragColor = result / 16.0;
+}
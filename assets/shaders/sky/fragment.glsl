@@ -0,0 +1,119 @@
+#version 330 core
+out vec4 FragColor;
+
+in vec2 NdcPos;
+
+uniform mat4 inverseViewProjection;
+uniform vec3 viewPos;
+uniform vec3 sunPosition;   // Unit vector towards the sun
+uniform vec3 moonPosition;  // Unit vector towards the moon
+
+// Set when drawing straight to the output, see PostSettings::apply_output
+uniform bool encodeOutput = false;
+uniform bool tonemapping = true;
+uniform float exposure = 1.0;
+
+// Keep these in sync with src/renderer/time_of_day.rs
+const float PI = 3.14159265359;
+const vec3 RAYLEIGH = vec3(5.8e-3, 13.5e-3, 33.1e-3);
+const float MIE = 4.0e-3;
+const float MIE_EXTINCTION = MIE / 0.9;
+const float RAYLEIGH_SCALE_HEIGHT = 8.0;
+const float MIE_SCALE_HEIGHT = 1.2;
+const float MIE_ANISOTROPY = 0.76;
+const float SUN_ILLUMINANCE = 3.2;
+const float SUN_SKY_INTENSITY = 12.0;
+const float MOON_SKY_INTENSITY = 0.25;
+const vec3 MOON_COLOR = vec3(0.6, 0.7, 1.0);
+const vec3 NIGHT_SKY = vec3(0.002, 0.003, 0.008);
+
+// Cosine of the angular radius of the sun and moon discs
+const float SUN_DISC = 0.99999;
+const float MOON_DISC = 0.99999;
+
+// Kasten-Young relative air mass, finite at the horizon
+float airMass(float elevationSin)
+{
+    float mu = clamp(elevationSin, 0.0, 1.0);
+    float elevationDegrees = degrees(asin(mu));
+    return 1.0 / (mu + 0.50572 * pow(elevationDegrees + 6.07995, -1.6364));
+}
+
+vec3 transmittance(float elevationSin)
+{
+    vec3 opticalDepth = RAYLEIGH * RAYLEIGH_SCALE_HEIGHT + vec3(MIE_EXTINCTION * MIE_SCALE_HEIGHT);
+    return exp(-opticalDepth * airMass(elevationSin));
+}
+
+vec3 singleScattering(vec3 view, vec3 lightPosition, float intensity)
+{
+    if (intensity <= 0.0)
+        return vec3(0.0);
+
+    float cosTheta = dot(view, lightPosition);
+    float rayleighPhase = 3.0 / (16.0 * PI) * (1.0 + cosTheta * cosTheta);
+    float g = MIE_ANISOTROPY;
+    float miePhase = (1.0 - g * g) / (4.0 * PI * pow(1.0 + g * g - 2.0 * g * cosTheta, 1.5));
+
+    float viewAirMass = airMass(view.y);
+    vec3 rayleighDepth = RAYLEIGH * RAYLEIGH_SCALE_HEIGHT * viewAirMass;
+    float mieDepth = MIE * MIE_SCALE_HEIGHT * viewAirMass;
+    vec3 extinction = rayleighDepth + vec3(mieDepth / 0.9);
+    vec3 scattering = rayleighDepth * rayleighPhase + vec3(mieDepth * miePhase);
+
+    return intensity * transmittance(lightPosition.y) * scattering / extinction * (1.0 - exp(-extinction));
+}
+
+float twilight(float elevationSin)
+{
+    return clamp((elevationSin + 0.12) / 0.14, 0.0, 1.0);
+}
+
+// Stephen Hill's ACES fit, the same curve as post/composite.glsl
+const float ACES_PRE_EXPOSURE = 1.6;
+const mat3 ACES_INPUT = mat3(
+    0.59719, 0.07600, 0.02840,
+    0.35458, 0.90834, 0.13383,
+    0.04823, 0.01566, 0.83777);
+const mat3 ACES_OUTPUT = mat3(
+    1.60475, -0.10208, -0.00327,
+    -0.53108, 1.10813, -0.07276,
+    -0.07367, -0.00605, 1.07602);
+
+// Tonemaps and converts to sRGB when the post pass isn't there to do it
+vec3 encodeOutputColor(vec3 color)
+{
+    color *= exposure;
+    if (tonemapping)
+    {
+        color = ACES_INPUT * (color * ACES_PRE_EXPOSURE);
+        vec3 a = color * (color + 0.0245786) - 0.000090537;
+        vec3 b = color * (0.983729 * color + 0.4329510) + 0.238081;
+        color = ACES_OUTPUT * (a / b);
+    }
+    return pow(clamp(color, 0.0, 1.0), vec3(1.0 / 2.2));
+}
+
+void main()
+{
+    vec4 farPoint = inverseViewProjection * vec4(NdcPos, 1.0, 1.0);
+    vec3 view = normalize(farPoint.xyz / farPoint.w - viewPos);
+
+    vec3 color = NIGHT_SKY
+        + singleScattering(view, sunPosition, SUN_SKY_INTENSITY * twilight(sunPosition.y))
+        + singleScattering(view, moonPosition, MOON_SKY_INTENSITY * twilight(moonPosition.y)) * MOON_COLOR;
+
+    // Sun and moon discs, dimmed by the air in front of them
+    if (view.y > 0.0)
+    {
+        if (dot(view, sunPosition) > SUN_DISC)
+            color += SUN_ILLUMINANCE * 20.0 * transmittance(sunPosition.y);
+        if (dot(view, moonPosition) > MOON_DISC)
+            color += MOON_COLOR * 0.5 * transmittance(moonPosition.y);
+    }
+
+    // Linear HDR, the post pass tonemaps and converts to sRGB
+    if (encodeOutput)
+        color = encodeOutputColor(color);
+    FragColor = vec4(color, 1.0);
+}
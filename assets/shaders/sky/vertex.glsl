@@ -0,0 +1,10 @@
+#version 330 core
+layout(location = 0) in vec2 aPos;
+
+out vec2 NdcPos;
+
+void main() {
+    NdcPos = aPos;
+    // Put the quad on the far plane so terrain always wins the depth test
+    gl_Position = vec4(aPos, 1.0, 1.0);
+}
@@ -4,50 +4,119 @@
     in VS_OUT {
         vec3 FragPos;
         vec3 Normal;
-        vec4 FragPosLightSpace;
+        float ViewDepth;
+        flat int Material;
     } fs_in;
 
-    uniform sampler2D uGrassTex;
-    uniform sampler2D uGrassNormal;
-    uniform sampler2D uRockTex;
-    uniform sampler2D uRockNormal;
+    const int MAX_CASCADES = 4;
+    const int MAX_MATERIALS = 64;
 
-    uniform sampler2D shadowMap;
+    // One layer per material id, see terrain_materials.json
+    uniform sampler2DArray materialAlbedo;
+    uniform sampler2DArray materialNormal;
+    uniform sampler2DArray materialRoughness;
+    uniform float materialTiling[MAX_MATERIALS];     // World units per texture repeat
+    uniform float materialSharpness[MAX_MATERIALS];  // Triplanar blend exponent
+    uniform int materialSteep[MAX_MATERIALS];        // Material drawn on steep faces
+
+    // GLSL 330 can't index sampler arrays dynamically, so each cascade has its own sampler
+    uniform sampler2D shadowMap0;
+    uniform sampler2D shadowMap1;
+    uniform sampler2D shadowMap2;
+    uniform sampler2D shadowMap3;
+    uniform mat4 lightSpaceMatrices[MAX_CASCADES];
+    uniform float cascadeSplits[MAX_CASCADES];   // View-space far distance of each cascade
+    uniform float cascadeDepthBias[MAX_CASCADES];  // About one texel of world depth, in shadow map depth units
+    uniform int cascadeCount = 1;
+    uniform int pcfRadius = 3;  // Half width of the PCF kernel in texels
 
-    uniform vec3 lightPos;
     uniform vec3 viewPos;
-    uniform vec3 lightDir;
+    uniform vec3 lightDir;    // Direction the sunlight travels
+    uniform vec3 lightColor = vec3(3.0);                 // Linear irradiance of the sun or moon
+    uniform vec3 skyAmbient = vec3(0.30, 0.38, 0.55);   // Ambient light from above
+    uniform vec3 groundAmbient = vec3(0.12, 0.10, 0.08); // Ambient light bounced from below
+    uniform vec3 sunPosition = vec3(0.0, 1.0, 0.0);      // Unit vector towards the sun
+
+    // Height fog, see src/renderer/fog.rs
+    uniform float fogDensity = 0.0;        // Extinction per unit at the base height
+    uniform float fogHeightFalloff = 0.015;
+    uniform float fogBaseHeight = 0.0;
+    uniform float fogEdgeStart = 1e9;      // Distance where fading out streamed chunks begins
+    uniform float fogEdgeEnd = 1e9;        // View distance, fully fogged
+    uniform vec3 fogColor = vec3(0.5);     // Sky at the horizon
+    uniform vec3 fogSunColor = vec3(0.5);  // Sky at the horizon below the sun
+    uniform vec3 fogExtinction = vec3(0.7, 0.85, 1.0);
     uniform float normalStrength = 1.0;
+    uniform float chunkSize = 64.0;      // Chunk size in world units
+
+    // Set when drawing straight to the output, see PostSettings::apply_output
+    uniform bool encodeOutput = false;
+    uniform bool tonemapping = true;
+    uniform float exposure = 1.0;
+
+    float sampleShadowMap(int cascade, vec2 uv)
+    {
+        if (cascade == 0) return texture(shadowMap0, uv).r;
+        if (cascade == 1) return texture(shadowMap1, uv).r;
+        if (cascade == 2) return texture(shadowMap2, uv).r;
+        return texture(shadowMap3, uv).r;
+    }
+
+    vec2 shadowTexelSize(int cascade)
+    {
+        if (cascade == 0) return 1.0 / textureSize(shadowMap0, 0);
+        if (cascade == 1) return 1.0 / textureSize(shadowMap1, 0);
+        if (cascade == 2) return 1.0 / textureSize(shadowMap2, 0);
+        return 1.0 / textureSize(shadowMap3, 0);
+    }
+
+    // First cascade whose split contains the fragment, or -1 beyond the last one
+    int selectCascade(float viewDepth)
+    {
+        for (int i = 0; i < MAX_CASCADES; ++i)
+        {
+            if (i >= cascadeCount)
+                break;
+            if (viewDepth <= cascadeSplits[i])
+                return i;
+        }
+        return -1;
+    }
 
-    float ShadowCalculation(vec4 fragPosLightSpace)
+    float ShadowCalculation(vec3 fragPos, vec3 normal)
     {
+        int cascade = selectCascade(fs_in.ViewDepth);
+        if (cascade < 0)
+            return 0.0;
+
+        vec4 fragPosLightSpace = lightSpaceMatrices[cascade] * vec4(fragPos, 1.0);
         // perform perspective divide
         vec3 projCoords = fragPosLightSpace.xyz / fragPosLightSpace.w;
         // transform to [0,1] range
         projCoords = projCoords * 0.5 + 0.5;
 
-        // get closest depth value from light's perspective (using [0,1] range fragPosLight as coords)
-        float closestDepth = texture(shadowMap, projCoords.xy).r; 
+        // keep the shadow at 0.0 when outside the far_plane region of the light's frustum.
+        if(projCoords.z > 1.0)
+            return 0.0;
+
         // get depth of current fragment from light's perspective
         float currentDepth = projCoords.z;
-        // calculate bias (based on depth map resolution and slope)
-        vec3 normal = normalize(fs_in.Normal);
-        vec3 lightDir = normalize(lightPos - fs_in.FragPos);
-        float lightDistance = length(lightPos - fs_in.FragPos);
-        float bias = max(0.005 * (1.0 - dot(normal, lightDir)) / lightDistance, 0.005);
+        // calculate bias from the slope and the cascade's texel footprint
+        float slope = 1.0 - abs(dot(normal, lightDir));
+        float bias = cascadeDepthBias[cascade] * (1.0 + 2.0 * slope);
 
         // PCF
         float shadow = 0.0;
-        int kernelSize = 15; // Increased kernel size for more samples
-        vec2 texelSize = 1.0 / textureSize(shadowMap, 0);
+        int halfKernelSize = pcfRadius;
+        vec2 texelSize = shadowTexelSize(cascade);
+        int kernelSize = 2 * halfKernelSize + 1;
         int sampleCount = kernelSize * kernelSize;
-        int halfKernelSize = kernelSize / 5;
 
         for(int x = -halfKernelSize; x <= halfKernelSize; ++x)
         {
             for(int y = -halfKernelSize; y <= halfKernelSize; ++y)
             {
-                float pcfDepth = texture(shadowMap, projCoords.xy + vec2(x, y) * texelSize).r;
+                float pcfDepth = sampleShadowMap(cascade, projCoords.xy + vec2(x, y) * texelSize);
                 // Smooth falloff using smoothstep
                 float depthDifference = currentDepth - bias - pcfDepth;
                 float shadowFactor = depthDifference > 0.0 ? 1.0 : 0.0;
@@ -55,71 +124,185 @@
             }    
         }
         shadow /= float(sampleCount);
-        
-        // keep the shadow at 0.0 when outside the far_plane region of the light's frustum.
-        if(projCoords.z > 1.0)
-            shadow = 0.0;
             
         return shadow;
     }
 
-    // Perturb normal with normal map
-    vec3 perturbNormal(vec3 normal, vec3 normalSample) {
-        vec3 normalTex = normalize(normalSample * 2.0 - 1.0);
-        normalTex = mix(vec3(0.0, 0.0, 1.0), normalTex, normalStrength);
-        return normalize(normal + normalTex);
+    const float PI = 3.14159265359;
+
+    struct MaterialSample {
+        vec3 albedo;      // Linear, the albedo array is sRGB so the sampler converts it
+        vec3 normal;      // World-space normal with the normal map applied
+        float roughness;
+    };
+
+    // Unpacks a tangent-space normal map sample
+    vec3 unpackNormal(vec3 normalSample)
+    {
+        vec3 normalTex = normalSample * 2.0 - 1.0;
+        normalTex.xy *= normalStrength;
+        return normalTex;
+    }
+
+    // Tri-planar sampling of one material layer with chunk-aligned UVs. Normal maps are
+    // combined with a whiteout blend per projection, so `surfaceNormal` must point out of
+    // the terrain.
+    MaterialSample sampleMaterial(int material, vec3 surfaceNormal)
+    {
+        float tiling = materialTiling[material];
+        float layer = float(material);
+
+        vec3 blending = pow(abs(surfaceNormal), vec3(materialSharpness[material]));
+        blending /= max(blending.x + blending.y + blending.z, 0.00001);
+
+        vec3 uvX = vec3(mod(fs_in.FragPos.zy, chunkSize) / tiling, layer);
+        vec3 uvY = vec3(mod(fs_in.FragPos.xz, chunkSize) / tiling, layer);
+        vec3 uvZ = vec3(mod(fs_in.FragPos.xy, chunkSize) / tiling, layer);
+
+        MaterialSample result;
+        result.albedo = texture(materialAlbedo, uvX).rgb * blending.x
+                      + texture(materialAlbedo, uvY).rgb * blending.y
+                      + texture(materialAlbedo, uvZ).rgb * blending.z;
+        result.roughness = texture(materialRoughness, uvX).r * blending.x
+                         + texture(materialRoughness, uvY).r * blending.y
+                         + texture(materialRoughness, uvZ).r * blending.z;
+
+        vec3 normalX = unpackNormal(texture(materialNormal, uvX).xyz);
+        vec3 normalY = unpackNormal(texture(materialNormal, uvY).xyz);
+        vec3 normalZ = unpackNormal(texture(materialNormal, uvZ).xyz);
+        normalX = vec3(normalX.xy + surfaceNormal.zy, abs(normalX.z) * surfaceNormal.x);
+        normalY = vec3(normalY.xy + surfaceNormal.xz, abs(normalY.z) * surfaceNormal.y);
+        normalZ = vec3(normalZ.xy + surfaceNormal.xy, abs(normalZ.z) * surfaceNormal.z);
+        result.normal = normalize(normalX.zyx * blending.x + normalY.xzy * blending.y + normalZ.xyz * blending.z);
+        return result;
+    }
+
+    // GGX / Trowbridge-Reitz normal distribution
+    float distributionGGX(float NdotH, float roughness)
+    {
+        float a = roughness * roughness;
+        float a2 = a * a;
+        float denom = NdotH * NdotH * (a2 - 1.0) + 1.0;
+        return a2 / (PI * denom * denom);
+    }
+
+    // Smith geometry term with the Schlick-GGX approximation for direct light
+    float geometrySmith(float NdotV, float NdotL, float roughness)
+    {
+        float r = roughness + 1.0;
+        float k = (r * r) / 8.0;
+        float ggxV = NdotV / (NdotV * (1.0 - k) + k);
+        float ggxL = NdotL / (NdotL * (1.0 - k) + k);
+        return ggxV * ggxL;
+    }
+
+    vec3 fresnelSchlick(float cosTheta, vec3 F0)
+    {
+        return F0 + (1.0 - F0) * pow(clamp(1.0 - cosTheta, 0.0, 1.0), 5.0);
+    }
+
+    // Height-based exponential fog with aerial perspective. The fog density falls off
+    // exponentially with height, integrated analytically along the view ray.
+    vec3 applyFog(vec3 color, vec3 fragPos)
+    {
+        vec3 offset = fragPos - viewPos;
+        float distance = length(offset);
+        vec3 view = offset / max(distance, 0.0001);
+
+        float rise = offset.y * fogHeightFalloff;
+        float startDensity = fogDensity * exp(-fogHeightFalloff * (viewPos.y - fogBaseHeight));
+        float opticalDepth = abs(rise) < 0.0001
+            ? startDensity * distance
+            : startDensity * distance * (1.0 - exp(-rise)) / rise;
+
+        // Distant terrain loses blue first, and the edge fade hides chunks streaming in
+        vec3 transmittance = exp(-fogExtinction * opticalDepth);
+        transmittance *= 1.0 - smoothstep(fogEdgeStart, fogEdgeEnd, distance);
+
+        // Brighter, warmer inscatter when looking towards the sun
+        float sunAmount = pow(max(dot(view, sunPosition), 0.0), 8.0);
+        vec3 inscatter = mix(fogColor, fogSunColor, sunAmount);
+
+        return color * transmittance + inscatter * (1.0 - transmittance);
+    }
+
+    // Stephen Hill's ACES fit, the same curve as post/composite.glsl
+    const float ACES_PRE_EXPOSURE = 1.6;
+    const mat3 ACES_INPUT = mat3(
+        0.59719, 0.07600, 0.02840,
+        0.35458, 0.90834, 0.13383,
+        0.04823, 0.01566, 0.83777);
+    const mat3 ACES_OUTPUT = mat3(
+        1.60475, -0.10208, -0.00327,
+        -0.53108, 1.10813, -0.07276,
+        -0.07367, -0.00605, 1.07602);
+
+    // Tonemaps and converts to sRGB when the post pass isn't there to do it
+    vec3 encodeOutputColor(vec3 color)
+    {
+        color *= exposure;
+        if (tonemapping)
+        {
+            color = ACES_INPUT * (color * ACES_PRE_EXPOSURE);
+            vec3 a = color * (color + 0.0245786) - 0.000090537;
+            vec3 b = color * (0.983729 * color + 0.4329510) + 0.238081;
+            color = ACES_OUTPUT * (a / b);
+        }
+        return pow(clamp(color, 0.0, 1.0), vec3(1.0 / 2.2));
     }
 
     void main()
     {
-        // --- Chunk-Aligned Tiling Parameters ---
-        const float CHUNK_SIZE = 64.0;       // Chunk size in world units
-        const float ROCK_TILE_SCALE = 8.0;   // Rock texture repeats every 8 units (64/8 = 8 tiles per chunk)
-        const float GRASS_TILE_SCALE = 4.0;  // Grass texture repeats every 4 units (64/4 = 16 tiles per chunk)
-
-        // --- Normalize Interpolated Normal ---
-        vec3 normal = normalize(fs_in.Normal);
-        
+        // Mesh normals point into the solid, flip them to get the surface normal
+        vec3 N = -normalize(fs_in.Normal);
+        vec3 V = normalize(viewPos - fs_in.FragPos);
+        vec3 L = -normalize(lightDir);
+
         // --- Blend Factors (Top vs. Sides) ---
-        float upFactor = -normal.y; // 1=up, -1=down
-        float topBlend = smoothstep(0.7, 0.9, upFactor);
-        
-        // --- Tri-Planar Blending (Favor Y-Axis) ---
-        vec3 blending = abs(normal);
-        blending.y *= 4.0;          // Boost top projection influence
-        blending = normalize(max(blending, 0.00001));
-
-        // --- Rock Texture Sampling (Chunk-Aligned UVs) ---
-        vec2 RockUVX = mod(fs_in.FragPos.zy, CHUNK_SIZE) / ROCK_TILE_SCALE;
-        vec2 RockUVY = mod(fs_in.FragPos.xz, CHUNK_SIZE) / ROCK_TILE_SCALE; // Dominant top projection
-        vec2 RockUVZ = mod(fs_in.FragPos.xy, CHUNK_SIZE) / ROCK_TILE_SCALE;
-        
-        vec4 RockX = texture(uRockTex, RockUVX);
-        vec4 RockY = texture(uRockTex, RockUVY);
-        vec4 RockZ = texture(uRockTex, RockUVZ);
-        vec4 RockColor = RockX * blending.x + RockY * blending.y + RockZ * blending.z;
-        
-        // --- Rock Normal Mapping ---
-        vec3 RockNormalX = texture(uRockNormal, RockUVX).xyz;
-        vec3 RockNormalY = texture(uRockNormal, RockUVY).xyz;
-        vec3 RockNormalZ = texture(uRockNormal, RockUVZ).xyz;
-        vec3 RockNormalTex = RockNormalX * blending.x + RockNormalY * blending.y + RockNormalZ * blending.z;
-
-        // --- Grass Texture Sampling (Chunk-Aligned UVs) ---
-        vec2 grassUV = mod(fs_in.FragPos.xz, CHUNK_SIZE) / GRASS_TILE_SCALE;
-        vec4 grassColor = texture(uGrassTex, grassUV);
-        vec3 grassNormalTex = texture(uGrassNormal, grassUV).xyz;
-
-        // --- Final Blending (Rock vs. Grass) ---
-        vec4 finalColor = mix(RockColor, grassColor, topBlend);
-        vec3 finalNormalTex = mix(RockNormalTex, grassNormalTex, topBlend);
-        vec3 finalNormal = perturbNormal(normal, finalNormalTex);
-
-        // --- Lighting Calculation ---
-        float diffuse = max(dot(finalNormal, lightDir), 0.0);
-        float ambient = 0.2;
-        float shadow = ShadowCalculation(fs_in.FragPosLightSpace);                    
-        float lighting = ambient + (1.0 - shadow*4.0) * (1.0 - ambient) * diffuse;  
-        
-        FragColor = finalColor * lighting;
+        float topBlend = smoothstep(0.7, 0.9, N.y);
+
+        // --- Material Sampling (voxel material on top, its steep material on the sides) ---
+        int material = clamp(fs_in.Material, 0, MAX_MATERIALS - 1);
+        int steepMaterial = materialSteep[material];
+
+        MaterialSample surface = sampleMaterial(material, N);
+        if (steepMaterial != material && topBlend < 1.0)
+        {
+            MaterialSample steep = sampleMaterial(steepMaterial, N);
+            surface.albedo = mix(steep.albedo, surface.albedo, topBlend);
+            surface.normal = normalize(mix(steep.normal, surface.normal, topBlend));
+            surface.roughness = mix(steep.roughness, surface.roughness, topBlend);
+        }
+
+        vec3 normal = surface.normal;
+        float roughness = clamp(surface.roughness, 0.05, 1.0);
+        vec3 albedo = surface.albedo;
+
+        // --- Direct Sun Light (Cook-Torrance, dielectric terrain) ---
+        vec3 H = normalize(V + L);
+        float NdotL = max(dot(normal, L), 0.0);
+        float NdotV = max(dot(normal, V), 0.0001);
+        float NdotH = max(dot(normal, H), 0.0);
+
+        vec3 F0 = vec3(0.04);
+        vec3 F = fresnelSchlick(max(dot(H, V), 0.0), F0);
+        float D = distributionGGX(NdotH, roughness);
+        float G = geometrySmith(NdotV, NdotL, roughness);
+        vec3 specular = (D * G * F) / max(4.0 * NdotV * NdotL, 0.0001);
+        vec3 diffuse = (vec3(1.0) - F) * albedo / PI;
+
+        // The shadow test uses the geometric normal, normal maps would make the bias noisy
+        float shadow = ShadowCalculation(fs_in.FragPos, -N);
+        vec3 direct = (diffuse + specular) * lightColor * NdotL * (1.0 - shadow);
+
+        // --- Hemispherical Ambient ---
+        vec3 ambientLight = mix(groundAmbient, skyAmbient, normal.y * 0.5 + 0.5);
+        vec3 ambientSpecular = fresnelSchlick(NdotV, F0) * (1.0 - roughness) * ambientLight;
+        vec3 ambient = ambientLight * albedo + ambientSpecular;
+
+        // Linear HDR, the post pass tonemaps and converts to sRGB
+        vec3 color = applyFog(ambient + direct, fs_in.FragPos);
+        if (encodeOutput)
+            color = encodeOutputColor(color);
+        FragColor = vec4(color, 1.0);
     }
\ No newline at end of file
@@ -1,22 +1,25 @@
 #version 330 core
 layout (location = 0) in vec3 aPos;
 layout (location = 1) in vec3 aNormal;
+layout (location = 2) in float aMaterial;
 
 out VS_OUT {
     vec3 FragPos;
     vec3 Normal;
-    vec4 FragPosLightSpace;
+    float ViewDepth;
+    flat int Material;
 } vs_out;
 
 uniform mat4 projection;
 uniform mat4 view;
 uniform mat4 model;
-uniform mat4 lightSpaceMatrix;
 
 void main()
 {
     vs_out.FragPos = vec3(model * vec4(aPos, 1.0));
     vs_out.Normal = transpose(inverse(mat3(model))) * aNormal;
-    vs_out.FragPosLightSpace = lightSpaceMatrix * vec4(vs_out.FragPos, 1.0);
-    gl_Position = projection * view * model * vec4(aPos, 1.0);
-}
\ No newline at end of file
+    vec4 viewPos = view * vec4(vs_out.FragPos, 1.0);
+    vs_out.ViewDepth = -viewPos.z;
+    vs_out.Material = int(aMaterial + 0.5);
+    gl_Position = projection * viewPos;
+}
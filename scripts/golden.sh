@@ -0,0 +1,32 @@
+#!/usr/bin/env sh
+# Renders the golden scene offscreen and compares it with tests/golden/default.png.
+# `scripts/golden.sh --bless` replaces the golden image with a fresh render instead, run it
+# after a change that's meant to alter the picture and commit the new image.
+#
+# Runs under xvfb-run with Mesa's software rasterizer when there is no display, like CI.
+set -eu
+
+cd "$(dirname "$0")/.."
+golden=tests/golden/default.png
+output=target/golden/default.png
+mkdir -p "$(dirname "$output")" "$(dirname "$golden")"
+
+cargo build --release
+
+run() {
+    if [ -z "${DISPLAY:-}" ] && command -v xvfb-run >/dev/null; then
+        LIBGL_ALWAYS_SOFTWARE=1 xvfb-run -a -s "-screen 0 1280x720x24" "$@"
+    else
+        "$@"
+    fi
+}
+
+if [ "${1:-}" = "--bless" ]; then
+    run ./target/release/fallendust --offscreen "$golden"
+    echo "Updated $golden"
+elif [ ! -f "$golden" ]; then
+    echo "$golden is missing, create it with scripts/golden.sh --bless" >&2
+    exit 1
+else
+    run ./target/release/fallendust --offscreen "$output" --golden "$golden"
+fi
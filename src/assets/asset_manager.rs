@@ -0,0 +1,261 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use ferrousgl::Shader;
+use image::RgbaImage;
+
+use crate::assets::handle::Handle;
+use crate::assets::placeholder::{self, PLACEHOLDER_SIZE};
+use crate::language::Language;
+use crate::utils::file_watcher::FileWatcher;
+
+/// Why an asset couldn't be loaded. Turned into a user-facing message with the
+/// `assets.errors` strings from `language.json`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssetError {
+    NotFound { asset: String, expected_path: PathBuf },
+    Corrupted { asset: String, error: String },
+}
+
+impl AssetError {
+    pub fn message(&self, language: &Language) -> String {
+        match self {
+            AssetError::NotFound { asset, expected_path } => language.format(
+                "assets.errors.not_found",
+                &[("asset", asset), ("expected_path", &expected_path.display().to_string())],
+            ),
+            AssetError::Corrupted { asset, error } => {
+                language.format("assets.errors.corrupted", &[("asset", asset), ("error", error)])
+            }
+        }
+    }
+}
+
+/// What an image is used for, which decides the placeholder it gets when loading fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageKind {
+    /// Albedo and other colour textures, replaced by a checkerboard
+    Color,
+    /// Tangent-space normal maps, replaced by a flat normal
+    Normal,
+    /// Roughness and other single-value maps, replaced by mid grey
+    Data,
+}
+
+impl ImageKind {
+    pub fn placeholder(&self) -> RgbaImage {
+        match self {
+            ImageKind::Color => placeholder::checkerboard(PLACEHOLDER_SIZE, PLACEHOLDER_SIZE / 8),
+            ImageKind::Normal => placeholder::flat_normal(PLACEHOLDER_SIZE),
+            ImageKind::Data => placeholder::solid(PLACEHOLDER_SIZE, 128),
+        }
+    }
+}
+
+struct ImageEntry {
+    path: PathBuf,
+    kind: ImageKind,
+    image: RgbaImage,
+    is_placeholder: bool,
+}
+
+struct ShaderEntry {
+    vertex_path: PathBuf,
+    fragment_path: PathBuf,
+    shader: Shader,
+}
+
+/// Owns images and shaders loaded from disk and hands out typed handles to them.
+/// Problems are reported once through the language strings; images that fail to load
+/// are replaced by a generated placeholder so the engine keeps running.
+///
+/// Every loaded file is watched, `reload_changed` picks up edits without a restart.
+pub struct AssetManager {
+    language: Language,
+    images: Vec<ImageEntry>,
+    shaders: Vec<ShaderEntry>,
+    errors: Vec<AssetError>,
+    watcher: FileWatcher,
+}
+
+impl AssetManager {
+    pub fn new(language: Language) -> Self {
+        AssetManager {
+            language,
+            images: Vec::new(),
+            shaders: Vec::new(),
+            errors: Vec::new(),
+            watcher: FileWatcher::new(Duration::from_millis(250)),
+        }
+    }
+
+    pub fn language(&self) -> &Language {
+        &self.language
+    }
+
+    /// Every problem reported so far, oldest first.
+    pub fn errors(&self) -> &[AssetError] {
+        &self.errors
+    }
+
+    pub fn report(&mut self, error: AssetError) {
+        println!("{}", error.message(&self.language));
+        self.errors.push(error);
+    }
+
+    /// Name used for an asset in messages, its file name.
+    pub fn asset_name(path: &Path) -> String {
+        path.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string())
+    }
+
+    /// Loads an image, or a placeholder for `kind` if it is missing or can't be decoded.
+    /// Loading the same file twice returns the same handle.
+    pub fn load_image<P: AsRef<Path>>(&mut self, path: P, kind: ImageKind) -> Handle<RgbaImage> {
+        let path = path.as_ref();
+        if let Some(index) = self.images.iter().position(|entry| entry.path == path && entry.kind == kind) {
+            return Handle::new(index);
+        }
+
+        let (image, is_placeholder) = match Self::decode_image(path) {
+            Ok(image) => (image, false),
+            Err(error) => {
+                self.report(error);
+                (kind.placeholder(), true)
+            }
+        };
+
+        self.watcher.watch_file(path);
+        self.images.push(ImageEntry {
+            path: path.to_path_buf(),
+            kind,
+            image,
+            is_placeholder,
+        });
+        Handle::new(self.images.len() - 1)
+    }
+
+    pub fn decode_image(path: &Path) -> Result<RgbaImage, AssetError> {
+        if !path.is_file() {
+            return Err(AssetError::NotFound {
+                asset: Self::asset_name(path),
+                expected_path: path.to_path_buf(),
+            });
+        }
+
+        image::open(path)
+            .map(|image| image.to_rgba8())
+            .map_err(|e| AssetError::Corrupted {
+                asset: Self::asset_name(path),
+                error: e.to_string(),
+            })
+    }
+
+    pub fn image(&self, handle: Handle<RgbaImage>) -> &RgbaImage {
+        &self.images[handle.index()].image
+    }
+
+    /// True if the image couldn't be loaded and a placeholder is used instead.
+    pub fn is_placeholder(&self, handle: Handle<RgbaImage>) -> bool {
+        self.images[handle.index()].is_placeholder
+    }
+
+    /// Compiles a shader program. There is no sensible placeholder for a shader, so
+    /// failures are reported and returned as the formatted message.
+    pub fn load_shader<P: AsRef<Path>>(&mut self, vertex_path: P, fragment_path: P) -> Result<Handle<Shader>, String> {
+        let (vertex_path, fragment_path) = (vertex_path.as_ref(), fragment_path.as_ref());
+        if let Some(index) = self
+            .shaders
+            .iter()
+            .position(|entry| entry.vertex_path == vertex_path && entry.fragment_path == fragment_path)
+        {
+            return Ok(Handle::new(index));
+        }
+
+        self.watcher.watch_file(vertex_path);
+        self.watcher.watch_file(fragment_path);
+
+        let shader = match Self::compile_shader(vertex_path, fragment_path) {
+            Ok(shader) => shader,
+            Err(error) => {
+                let message = error.message(&self.language);
+                self.report(error);
+                return Err(message);
+            }
+        };
+
+        self.shaders.push(ShaderEntry {
+            vertex_path: vertex_path.to_path_buf(),
+            fragment_path: fragment_path.to_path_buf(),
+            shader,
+        });
+        Ok(Handle::new(self.shaders.len() - 1))
+    }
+
+    pub fn compile_shader(vertex_path: &Path, fragment_path: &Path) -> Result<Shader, AssetError> {
+        for path in [vertex_path, fragment_path] {
+            if !path.is_file() {
+                return Err(AssetError::NotFound {
+                    asset: Self::asset_name(path),
+                    expected_path: path.to_path_buf(),
+                });
+            }
+        }
+
+        Shader::new_from_file(vertex_path, fragment_path).map_err(|e| AssetError::Corrupted {
+            asset: format!("{} + {}", Self::asset_name(vertex_path), Self::asset_name(fragment_path)),
+            error: format!("{:?}", e),
+        })
+    }
+
+    pub fn shader(&self, handle: Handle<Shader>) -> &Shader {
+        &self.shaders[handle.index()].shader
+    }
+
+    /// Watches a file that isn't loaded through the manager, such as a JSON data file, so
+    /// it shows up in `reload_changed`.
+    pub fn watch_file<P: AsRef<Path>>(&mut self, path: P) {
+        self.watcher.watch_file(path);
+    }
+
+    /// Reloads images and shaders whose files changed since the last poll and returns
+    /// every changed watched file. Assets that fail to reload are reported and keep their
+    /// previous contents, so a half-saved shader never breaks a running frame.
+    pub fn reload_changed(&mut self) -> Vec<PathBuf> {
+        let changed = self.watcher.poll();
+
+        for path in &changed {
+            for index in 0..self.images.len() {
+                if self.images[index].path != *path {
+                    continue;
+                }
+                match Self::decode_image(path) {
+                    Ok(image) => {
+                        println!("Reloaded image {:?}", path);
+                        let entry = &mut self.images[index];
+                        entry.image = image;
+                        entry.is_placeholder = false;
+                    }
+                    Err(error) => self.report(error),
+                }
+            }
+
+            for index in 0..self.shaders.len() {
+                let entry = &self.shaders[index];
+                if entry.vertex_path != *path && entry.fragment_path != *path {
+                    continue;
+                }
+                match Self::compile_shader(&entry.vertex_path, &entry.fragment_path) {
+                    Ok(shader) => {
+                        println!("Reloaded shader {:?}", path);
+                        self.shaders[index].shader = shader;
+                    }
+                    Err(error) => self.report(error),
+                }
+            }
+        }
+
+        changed
+    }
+}
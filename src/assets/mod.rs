@@ -0,0 +1,3 @@
+pub mod asset_manager;
+pub mod handle;
+pub mod placeholder;
@@ -0,0 +1,26 @@
+use image::{Rgba, RgbaImage};
+
+/// Edge length of generated placeholder textures.
+pub const PLACEHOLDER_SIZE: u32 = 256;
+
+/// Magenta and black checkerboard that makes missing colour textures obvious.
+pub fn checkerboard(size: u32, cell_size: u32) -> RgbaImage {
+    let cell_size = cell_size.max(1);
+    RgbaImage::from_fn(size, size, |x, y| {
+        if (x / cell_size + y / cell_size).is_multiple_of(2) {
+            Rgba([255, 0, 255, 255])
+        } else {
+            Rgba([0, 0, 0, 255])
+        }
+    })
+}
+
+/// Tangent-space normal map pointing straight out of the surface.
+pub fn flat_normal(size: u32) -> RgbaImage {
+    RgbaImage::from_pixel(size, size, Rgba([128, 128, 255, 255]))
+}
+
+/// Single-value map, used for missing roughness and other data textures.
+pub fn solid(size: u32, value: u8) -> RgbaImage {
+    RgbaImage::from_pixel(size, size, Rgba([value, value, value, 255]))
+}
@@ -0,0 +1,84 @@
+//! Timings of a camera path played back in offscreen mode, for comparing performance
+//! between commits. See `headless` for how playback is started.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use crate::terrain::terrain_manager::TerrainTimings;
+use crate::utils::timing_stats::TimingSummary;
+
+/// Work done for one played back tick, times in milliseconds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct FrameTiming {
+    /// Whole frame: terrain work, rendering and waiting for the GPU
+    pub frame: f64,
+    pub generation: f64,
+    pub meshing: f64,
+    pub chunks_generated: usize,
+    pub chunks_meshed: usize,
+}
+
+impl FrameTiming {
+    pub fn new(frame_seconds: f64, terrain: &TerrainTimings) -> Self {
+        FrameTiming {
+            frame: frame_seconds * 1000.0,
+            generation: terrain.generation.as_secs_f64() * 1000.0,
+            meshing: terrain.meshing.as_secs_f64() * 1000.0,
+            chunks_generated: terrain.chunks_generated,
+            chunks_meshed: terrain.chunks_meshed,
+        }
+    }
+}
+
+/// Per-frame timings of a playback with their summaries.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkReport {
+    pub frame: TimingSummary,
+    pub generation: TimingSummary,
+    pub meshing: TimingSummary,
+    pub chunks_generated: usize,
+    pub chunks_meshed: usize,
+    pub frames: Vec<FrameTiming>,
+}
+
+impl BenchmarkReport {
+    pub fn new(frames: Vec<FrameTiming>) -> Self {
+        let summary = |value: fn(&FrameTiming) -> f64| {
+            TimingSummary::from_samples(&frames.iter().map(value).collect::<Vec<_>>())
+        };
+
+        BenchmarkReport {
+            frame: summary(|frame| frame.frame),
+            generation: summary(|frame| frame.generation),
+            meshing: summary(|frame| frame.meshing),
+            chunks_generated: frames.iter().map(|frame| frame.chunks_generated).sum(),
+            chunks_meshed: frames.iter().map(|frame| frame.chunks_meshed).sum(),
+            frames,
+        }
+    }
+
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
+        let file = File::create(path)?;
+        let writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for BenchmarkReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} frames, {} chunks generated, {} meshed", self.frames.len(), self.chunks_generated, self.chunks_meshed)?;
+        writeln!(f, "{:<12} {:>9} {:>9} {:>9} {:>9}", "ms", "avg", "p95", "p99", "max")?;
+        for (name, summary) in [("frame", &self.frame), ("generation", &self.generation), ("meshing", &self.meshing)] {
+            writeln!(
+                f,
+                "{:<12} {:>9.3} {:>9.3} {:>9.3} {:>9.3}",
+                name, summary.average, summary.p95, summary.p99, summary.max
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -1,7 +1,70 @@
-use ferrousgl::{GlWindow, WindowKey};
-use glam::{Mat4, Vec3};
+use ferrousgl::GlWindow;
+use glam::{Mat4, Vec2, Vec3};
+use crate::input::action::Action;
+use crate::input::bindings::InputBindings;
+use crate::input::input_source::InputSource;
+use crate::physics::density_field::DensityField;
+use crate::physics::walker::{WalkSettings, Walker};
+use crate::utils::frustum::Frustum;
 use crate::utils::ray::Ray;
 
+/// Height of the top-down camera above its centre, well above any terrain around it
+const TOP_DOWN_ALTITUDE: f32 = 200.0;
+
+/// How the camera is driven. Each mode keeps its own state while another one is active.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CameraMode {
+    /// Mouse look with flying or walking movement
+    FirstPerson,
+    /// Circles a pivot, the mouse rotates, movement keys pan and zoom
+    Orbit,
+    /// Orthographic view straight down, movement keys pan and zoom
+    TopDown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    Perspective,
+    /// `extent` is half the view height in world units
+    Orthographic { extent: f32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrbitState {
+    pub pivot: Vec3,
+    pub distance: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TopDownState {
+    pub center: Vec3,
+    /// Half the view height in world units
+    pub extent: f32,
+}
+
+/// Blend from the view before a mode switch to the new mode's view.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraTransition {
+    pub from_position: Vec3,
+    pub from_target: Vec3,
+    pub from_up: Vec3,
+    pub from_projection: Projection,
+    /// 0 at the switch, 1 when done
+    pub progress: f32,
+    pub previous_progress: f32,
+}
+
+/// Movement keys held during a frame, in camera space: x right, y up, z forward.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CameraInput {
+    pub movement: Vec3,
+    pub sprint: bool,
+    pub jump: bool, // Only used in walk mode
+}
+
+#[derive(Debug, Clone)]
 pub struct CameraController {
     pub position: Vec3,
     pub target: Vec3,
@@ -12,8 +75,25 @@ pub struct CameraController {
     pub far: f32,
     pub yaw: f32,
     pub pitch: f32,
-    pub speed: f32,
+    pub speed: f32, // Top speed in units per second
+    pub acceleration: f32, // Units per second squared while a movement key is held
+    pub damping: f32, // Fraction of velocity lost per second is 1 - exp(-damping)
+    pub sprint_multiplier: f32, // Speed and acceleration scale while sprinting
     pub sensitivity: f32,
+    pub velocity: Vec3,
+    pub previous_position: Vec3, // Position at the previous simulation tick, for interpolation
+    pub walking: bool, // Walk on the terrain instead of flying, see `simulate_walking`
+    pub walker: Walker,
+    pub mode: CameraMode,
+    pub orbit: OrbitState,
+    pub top_down: TopDownState,
+    pub first_person_position: Vec3, // Where first person mode resumes while another mode is active
+    pub zoom_speed: f32, // Zoom factor per second is exp(zoom_speed) in orbit and top-down
+    pub transition_duration: f32, // Seconds a mode switch takes
+    pub transition: Option<CameraTransition>,
+    pub cursor_captured: bool, // Hidden cursor driving mouse look, otherwise the cursor is free for editing
+    pub viewport_size: Vec2, // Window size in pixels, for `screen_point_to_ray`
+    skip_mouse_delta: bool, // The first delta after capturing includes the jump to the window centre
 }
 
 impl CameraController {
@@ -29,116 +109,389 @@ impl CameraController {
             far: 1000.0,                       // Far clipping plane
             yaw: -90.0,                       // Default yaw
             pitch: 0.0,                       // Default pitch
-            speed: 0.1,                       // Default movement speed
+            speed: 20.0,                      // Default top speed
+            acceleration: 120.0,              // Reaches top speed in about a sixth of a second
+            damping: 10.0,                    // Stops within a few tenths of a second
+            sprint_multiplier: 4.0,           // Sprint speed is 80 units per second
             sensitivity: 0.1,                 // Default mouse sensitivity
+            velocity: Vec3::ZERO,
+            previous_position: Vec3::new(0.0, 50.0, 5.0),
+            walking: false,
+            walker: Walker::new(Vec3::new(0.0, 48.4, 5.0), WalkSettings::default()),
+            mode: CameraMode::FirstPerson,
+            orbit: OrbitState { pivot: Vec3::ZERO, distance: 50.0, yaw: -90.0, pitch: -30.0 },
+            top_down: TopDownState { center: Vec3::ZERO, extent: 64.0 },
+            first_person_position: Vec3::new(0.0, 50.0, 5.0),
+            zoom_speed: 2.0,
+            transition_duration: 0.4,
+            transition: None,
+            cursor_captured: false,
+            viewport_size: Vec2::new(aspect_ratio, 1.0),
+            skip_mouse_delta: false,
         }
     }
 
-    /// Updates the camera's position based on input from the window.
+    /// Unit vector the camera looks along in first person, from yaw and pitch.
+    pub fn forward(&self) -> Vec3 {
+        direction_from_angles(self.yaw, self.pitch)
+    }
+
+    /// Reads the movement actions. Call once per rendered frame, the result drives every
+    /// simulation tick of that frame.
+    pub fn read_input(input: &dyn InputSource, bindings: &InputBindings) -> CameraInput {
+        let mut movement = Vec3::ZERO;
+        let axes = [
+            (Action::MoveRight, Vec3::X),
+            (Action::MoveLeft, Vec3::NEG_X),
+            (Action::MoveUp, Vec3::Y),
+            (Action::MoveDown, Vec3::NEG_Y),
+            (Action::MoveForward, Vec3::Z),
+            (Action::MoveBackward, Vec3::NEG_Z),
+        ];
+        for (action, axis) in axes {
+            if bindings.is_active(action, input) {
+                movement += axis;
+            }
+        }
+
+        CameraInput {
+            movement,
+            sprint: bindings.is_active(Action::Sprint, input),
+            jump: bindings.is_active(Action::Jump, input),
+        }
+    }
+
+    /// Updates the view direction from the mouse. Runs every rendered frame so looking
+    /// around stays as responsive as the frame rate allows. The mouse only looks around
+    /// while the cursor is captured.
     pub fn update(&mut self, window: &mut GlWindow) {
-        // Calculate forward vector based on yaw and pitch
-        let forward = Vec3::new(
-            self.yaw.to_radians().cos() * self.pitch.to_radians().cos(),
-            self.pitch.to_radians().sin(),
-            self.yaw.to_radians().sin() * self.pitch.to_radians().cos(),
-        )
-        .normalize();
-
-        // Calculate right vector
-        let right = forward.cross(self.up).normalize();
+        let mouse_delta = window.get_mouse_delta();
+        if self.cursor_captured && !std::mem::take(&mut self.skip_mouse_delta) {
+            self.look(mouse_delta.0 as f32, mouse_delta.1 as f32);
+        }
+
+        // Update aspect ratio
+        let (width, height) = window.get_window_size();
+        self.viewport_size = Vec2::new(width as f32, height as f32);
+        self.aspect_ratio = width as f32 / height as f32;
+    }
+
+    /// Hides and locks the cursor for mouse look, or releases it for pointing at the
+    /// terrain. GLFW keeps reporting unbounded movement while the cursor is captured, so
+    /// there are no window edges to wrap around. Applies to the window whose GL context is
+    /// current.
+    pub fn set_cursor_captured(&mut self, captured: bool) {
+        self.cursor_captured = captured;
+        self.skip_mouse_delta = captured;
+        unsafe {
+            let window = glfw::ffi::glfwGetCurrentContext();
+            if window.is_null() {
+                println!("No current window to capture the cursor in");
+                return;
+            }
+
+            let mode = if captured { glfw::ffi::CURSOR_DISABLED } else { glfw::ffi::CURSOR_NORMAL };
+            glfw::ffi::glfwSetInputMode(window, glfw::ffi::CURSOR, mode);
+            // Unaccelerated movement feels better for looking around where the OS supports it
+            if glfw::ffi::glfwRawMouseMotionSupported() == glfw::ffi::TRUE {
+                let raw = if captured { glfw::ffi::TRUE } else { glfw::ffi::FALSE };
+                glfw::ffi::glfwSetInputMode(window, glfw::ffi::RAW_MOUSE_MOTION, raw);
+            }
+        }
+    }
 
-        // Movement speed
-        let speed = self.speed;
+    /// Rotates the view by a mouse movement in pixels. Turns the head in first person and
+    /// circles the pivot in orbit mode, the top-down view doesn't rotate.
+    pub fn look(&mut self, delta_x: f32, delta_y: f32) {
+        match self.mode {
+            CameraMode::FirstPerson => {
+                self.yaw += delta_x * self.sensitivity;
+                // Clamp pitch to avoid gimbal lock
+                self.pitch = (self.pitch - delta_y * self.sensitivity).clamp(-89.0, 89.0);
+                self.target = self.position + self.forward();
+            }
+            CameraMode::Orbit => {
+                self.orbit.yaw += delta_x * self.sensitivity;
+                self.orbit.pitch = (self.orbit.pitch - delta_y * self.sensitivity).clamp(-89.0, 89.0);
+                self.apply_mode_pose();
+            }
+            CameraMode::TopDown => {}
+        }
+    }
+
+    /// Advances whichever mode is active by one simulation tick.
+    pub fn advance(&mut self, input: &CameraInput, field: &dyn DensityField, delta_seconds: f32) {
+        match self.mode {
+            CameraMode::FirstPerson if self.walking => self.simulate_walking(input, field, delta_seconds),
+            CameraMode::FirstPerson => self.simulate(input, delta_seconds),
+            CameraMode::Orbit => self.simulate_orbit(input, delta_seconds),
+            CameraMode::TopDown => self.simulate_top_down(input, delta_seconds),
+        }
 
-        // Move left/right
-        if window.is_key_held(WindowKey::A) {
-            self.position -= right * speed;
+        if let Some(transition) = &mut self.transition {
+            transition.previous_progress = transition.progress;
+            transition.progress = (transition.progress + delta_seconds / self.transition_duration.max(1e-3)).min(1.0);
+            if transition.previous_progress >= 1.0 {
+                self.transition = None;
+            }
         }
-        if window.is_key_held(WindowKey::D) {
-            self.position += right * speed;
+    }
+
+    /// Orbit mode tick: left/right and up/down pan the pivot in the view plane, forward
+    /// and back zoom towards it. Panning scales with the distance so it feels the same
+    /// at any zoom.
+    pub fn simulate_orbit(&mut self, input: &CameraInput, delta_seconds: f32) {
+        self.previous_position = self.position;
+
+        let boost = if input.sprint { self.sprint_multiplier } else { 1.0 };
+        let forward = (self.orbit.pivot - self.position).normalize_or_zero();
+        let right = forward.cross(Vec3::Y).normalize_or_zero();
+        let up = right.cross(forward);
+        let pan = (right * input.movement.x + up * input.movement.y) * self.orbit.distance * boost * delta_seconds;
+
+        self.orbit.pivot += pan;
+        self.orbit.distance = (self.orbit.distance * (-input.movement.z * self.zoom_speed * delta_seconds).exp()).clamp(1.0, self.far * 0.5);
+        self.apply_mode_pose();
+    }
+
+    /// Top-down mode tick: forward/back and left/right pan over the ground, up and down
+    /// zoom out and in.
+    pub fn simulate_top_down(&mut self, input: &CameraInput, delta_seconds: f32) {
+        self.previous_position = self.position;
+
+        let boost = if input.sprint { self.sprint_multiplier } else { 1.0 };
+        // Screen up is north (-z)
+        let pan = Vec3::new(input.movement.x, 0.0, -input.movement.z) * self.top_down.extent * boost * delta_seconds;
+
+        self.top_down.center += pan;
+        self.top_down.extent = (self.top_down.extent * (input.movement.y * self.zoom_speed * delta_seconds).exp()).clamp(4.0, self.far * 0.5);
+        self.apply_mode_pose();
+    }
+
+    /// Switches to `mode`, blending the rendered view over `transition_duration`.
+    pub fn set_mode(&mut self, mode: CameraMode) {
+        if mode == self.mode {
+            return;
         }
 
-        // Move forward/backward
-        if window.is_key_held(WindowKey::W) {
-            self.position += forward * speed;
+        // Start from what is on screen, which may be halfway through another transition
+        let current = self.interpolated(1.0);
+        self.transition = Some(CameraTransition {
+            from_position: current.position,
+            from_target: current.target,
+            from_up: current.up,
+            from_projection: current.blended_projection(),
+            progress: 0.0,
+            previous_progress: 0.0,
+        });
+
+        if self.mode == CameraMode::FirstPerson {
+            self.first_person_position = self.position;
         }
-        if window.is_key_held(WindowKey::S) {
-            self.position -= forward * speed;
+        self.mode = mode;
+        self.velocity = Vec3::ZERO;
+        self.apply_mode_pose();
+        self.previous_position = self.position;
+    }
+
+    /// Switches to orbit mode around `pivot`, keeping the current distance and angle.
+    pub fn orbit_around(&mut self, pivot: Vec3) {
+        self.orbit.pivot = pivot;
+        if self.mode == CameraMode::Orbit {
+            self.apply_mode_pose();
+        } else {
+            self.set_mode(CameraMode::Orbit);
         }
+    }
 
-        // Move up/down
-        if window.is_key_held(WindowKey::Q) {
-            self.position.y -= speed;
+    /// Puts position, target and up where the active mode's state says.
+    fn apply_mode_pose(&mut self) {
+        match self.mode {
+            CameraMode::FirstPerson => {
+                self.position = self.first_person_position;
+                self.up = Vec3::Y;
+                self.target = self.position + self.forward();
+            }
+            CameraMode::Orbit => {
+                let direction = direction_from_angles(self.orbit.yaw, self.orbit.pitch);
+                self.position = self.orbit.pivot - direction * self.orbit.distance;
+                self.target = self.orbit.pivot;
+                self.up = Vec3::Y;
+            }
+            CameraMode::TopDown => {
+                self.position = self.top_down.center + Vec3::Y * TOP_DOWN_ALTITUDE;
+                self.target = self.top_down.center;
+                // Looking straight down, so up can't be y. North is at the top of the screen
+                self.up = Vec3::NEG_Z;
+            }
         }
-        if window.is_key_held(WindowKey::E) {
-            self.position.y += speed;
+    }
+
+    /// Projection of the active mode, ignoring any transition.
+    pub fn projection(&self) -> Projection {
+        match self.mode {
+            CameraMode::TopDown => Projection::Orthographic { extent: self.top_down.extent },
+            _ => Projection::Perspective,
         }
+    }
 
-        // Reset mouse
-        let mouse_delta = window.get_mouse_delta();
+    /// Advances movement by one simulation tick of `delta_seconds`. Held keys accelerate
+    /// towards top speed in their direction, without input the camera glides to a stop.
+    pub fn simulate(&mut self, input: &CameraInput, delta_seconds: f32) {
+        self.previous_position = self.position;
 
-        // Handle mouse input for rotation
-        self.yaw += mouse_delta.0 as f32 * self.sensitivity;
-        self.pitch -= mouse_delta.1 as f32 * self.sensitivity;
+        let forward = self.forward();
+        let right = forward.cross(self.up).normalize();
+        let direction = (right * input.movement.x + self.up * input.movement.y + forward * input.movement.z).normalize_or_zero();
 
-        // Clamp pitch to avoid gimbal lock
-        self.pitch = self.pitch.clamp(-89.0, 89.0);
+        let boost = if input.sprint { self.sprint_multiplier } else { 1.0 };
+        if direction != Vec3::ZERO {
+            let target_velocity = direction * self.speed * boost;
+            let change = target_velocity - self.velocity;
+            let max_change = self.acceleration * boost * delta_seconds;
+            self.velocity += change.clamp_length_max(max_change);
+        } else {
+            self.velocity *= (-self.damping * delta_seconds).exp();
+            if self.velocity.length_squared() < 1e-6 {
+                self.velocity = Vec3::ZERO;
+            }
+        }
 
-        // Update target based on new forward vector
+        self.position += self.velocity * delta_seconds;
+        self.first_person_position = self.position;
         self.target = self.position + forward;
+    }
 
-        // Update aspect ratio
-        self.aspect_ratio = (window.get_window_size().0 as f32) / (window.get_window_size().1 as f32);
+    /// Switches between flying and walking, returning to first person first. The walker
+    /// starts with its eyes at the camera and falls from there.
+    pub fn toggle_walk_mode(&mut self) {
+        self.set_mode(CameraMode::FirstPerson);
+        self.walking = !self.walking;
+        if self.walking {
+            self.walker.position = self.position - Vec3::Y * self.walker.settings.eye_height;
+            self.walker.velocity = Vec3::ZERO;
+            self.walker.on_ground = false;
+        }
+        self.velocity = Vec3::ZERO;
+    }
 
-        // Handle mouse wrapping
-        let mouse_pos = window.get_mouse_position();
-        let window_pos = window.get_window_position();
-        let window_size = window.get_window_size();
+    /// Walk mode version of `simulate`: moves the walker over the terrain in the direction
+    /// the camera faces and puts the camera at its eyes. Up and down keys are ignored.
+    pub fn simulate_walking(&mut self, input: &CameraInput, field: &dyn DensityField, delta_seconds: f32) {
+        self.previous_position = self.position;
 
-        let window_left = window_pos.0 as i32;
-        let window_top = window_pos.1 as i32;
-        let window_right = window_left + window_size.0 as i32;
-        let window_bottom = window_top + window_size.1 as i32;
+        let forward = self.forward();
+        let flat_forward = Vec3::new(forward.x, 0.0, forward.z).normalize_or_zero();
+        let right = flat_forward.cross(Vec3::Y);
+        let wish_direction = right * input.movement.x + flat_forward * input.movement.z;
 
-        if (mouse_pos.0 <= window_left.into()) {
-            window.set_mouse_position((window_right - 1).into(), mouse_pos.1);
-        } else if (mouse_pos.0 >= window_right.into()) {
-            window.set_mouse_position((window_left + 1).into(), mouse_pos.1);
-        }
+        self.walker.simulate(field, wish_direction, input.jump, input.sprint, delta_seconds);
+        self.position = self.walker.eye_position();
+        self.first_person_position = self.position;
+        self.target = self.position + forward;
+    }
+
+    /// Copy of the camera between the last two ticks, `alpha` 0 is the previous tick and 1
+    /// the current one. Rendering this hides the tick rate at high frame rates.
+    /// A running mode transition is blended in as well.
+    pub fn interpolated(&self, alpha: f32) -> CameraController {
+        let mut camera = self.clone();
+        camera.position = self.previous_position.lerp(self.position, alpha);
+        camera.target = camera.position + (self.target - self.position);
+
+        if let Some(transition) = &mut camera.transition {
+            transition.progress = transition.previous_progress + (transition.progress - transition.previous_progress) * alpha;
+            transition.previous_progress = transition.progress;
 
-        if (mouse_pos.1 <= window_top.into()) {
-            window.set_mouse_position(mouse_pos.0, (window_bottom - 1).into());
-        } else if (mouse_pos.1 >= window_bottom.into()) {
-            window.set_mouse_position(mouse_pos.0, (window_top + 1).into());
+            let t = smoothstep(transition.progress);
+            camera.position = transition.from_position.lerp(camera.position, t);
+            camera.target = transition.from_target.lerp(camera.target, t);
+            camera.up = transition.from_up.lerp(camera.up, t).normalize_or(Vec3::Y);
         }
+        camera
     }
 
     /// Generates the Model-View-Projection (MVP) matrix for the camera.
     pub fn get_vp(&self) -> Mat4 {
-        let projection = Mat4::perspective_rh_gl(self.fov.to_radians(), self.aspect_ratio, self.near, self.far);
-        let view = Mat4::look_at_rh(self.position, self.target, self.up);
-        projection * view
+        self.get_projection() * self.get_view()
     }
 
     pub fn get_view(&self) -> Mat4 {
         Mat4::look_at_rh(self.position, self.target, self.up)
     }
 
+    /// Perspective or orthographic depending on the mode, blended during a transition.
     pub fn get_projection(&self) -> Mat4 {
-        Mat4::perspective_rh_gl(self.fov.to_radians(), self.aspect_ratio, self.near, self.far)
+        self.get_projection_with_depth(self.near, self.far)
+    }
+
+    /// `get_projection` with other clip distances, for slicing the view frustum.
+    pub fn get_projection_with_depth(&self, near: f32, far: f32) -> Mat4 {
+        let projection = self.projection_matrix(self.projection(), near, far);
+        match &self.transition {
+            Some(transition) => {
+                // Lerping the matrices moves smoothly between perspective and orthographic
+                let from = self.projection_matrix(transition.from_projection, near, far);
+                let t = smoothstep(transition.progress);
+                from * (1.0 - t) + projection * t
+            }
+            None => projection,
+        }
+    }
+
+    fn projection_matrix(&self, projection: Projection, near: f32, far: f32) -> Mat4 {
+        match projection {
+            Projection::Perspective => Mat4::perspective_rh_gl(self.fov.to_radians(), self.aspect_ratio, near, far),
+            Projection::Orthographic { extent } => {
+                let width = extent * self.aspect_ratio;
+                Mat4::orthographic_rh_gl(-width, width, -extent, extent, near, far)
+            }
+        }
+    }
+
+    /// Projection shown right now, as a `Projection` to start a new transition from.
+    /// Halfway between perspective and orthographic the closer one is used.
+    fn blended_projection(&self) -> Projection {
+        match &self.transition {
+            Some(transition) if smoothstep(transition.progress) < 0.5 => transition.from_projection,
+            _ => self.projection(),
+        }
+    }
+
+    /// Frustum of the current view-projection, used for culling.
+    pub fn get_frustum(&self) -> Frustum {
+        Frustum::from_matrix(self.get_vp())
     }
 
     /// Generates a ray from the camera's position in the direction it is facing.
     pub fn get_ray(&self) -> Ray {
-        // Calculate the forward vector based on yaw and pitch
-        let forward = Vec3::new(
-            self.yaw.to_radians().cos() * self.pitch.to_radians().cos(),
-            self.pitch.to_radians().sin(),
-            self.yaw.to_radians().sin() * self.pitch.to_radians().cos(),
-        )
-        .normalize();
+        Ray::new(self.position, (self.target - self.position).normalize())
+    }
 
-        // Create and return the ray
-        Ray::new(self.position, forward)
+    /// Ray through a window pixel, `(0, 0)` being the top left corner. Starts on the near
+    /// plane, so it works for the orthographic top-down view as well.
+    pub fn screen_point_to_ray(&self, x: f32, y: f32) -> Ray {
+        let ndc_x = 2.0 * x / self.viewport_size.x - 1.0;
+        let ndc_y = 1.0 - 2.0 * y / self.viewport_size.y;
+
+        let inverse = self.get_vp().inverse();
+        let near = inverse.project_point3(Vec3::new(ndc_x, ndc_y, -1.0));
+        let far = inverse.project_point3(Vec3::new(ndc_x, ndc_y, 1.0));
+        Ray::new(near, far - near)
     }
+}
+
+/// Unit vector for a yaw and pitch in degrees.
+fn direction_from_angles(yaw: f32, pitch: f32) -> Vec3 {
+    Vec3::new(
+        yaw.to_radians().cos() * pitch.to_radians().cos(),
+        pitch.to_radians().sin(),
+        yaw.to_radians().sin() * pitch.to_radians().cos(),
+    )
+    .normalize()
+}
+
+fn smoothstep(t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
 }
\ No newline at end of file
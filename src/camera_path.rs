@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use glam::Vec3;
+
+use crate::camera_controller::{CameraController, CameraMode, Projection};
+use crate::terrain::terrain_generator_config::TerrainGeneratorConfig;
+
+/// Where recordings are saved unless a path is given.
+pub const CAMERA_PATH_DIRECTORY: &str = "camera_paths";
+
+/// Camera transform at one simulation tick.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CameraPathSample {
+    pub position: [f32; 3],
+    pub target: [f32; 3],
+    pub up: [f32; 3],
+    pub fov: f32,
+    /// Half view height of the top-down camera, None for perspective
+    #[serde(default)]
+    pub orthographic_extent: Option<f32>,
+}
+
+impl CameraPathSample {
+    /// What the camera shows at the current tick, including any mode transition.
+    pub fn from_camera(camera: &CameraController) -> Self {
+        let rendered = camera.interpolated(1.0);
+        CameraPathSample {
+            position: rendered.position.to_array(),
+            target: rendered.target.to_array(),
+            up: rendered.up.to_array(),
+            fov: rendered.fov,
+            orthographic_extent: match camera.projection() {
+                Projection::Orthographic { extent } => Some(extent),
+                Projection::Perspective => None,
+            },
+        }
+    }
+
+    /// Puts the camera exactly where the sample was taken, without interpolation or
+    /// transitions.
+    pub fn apply(&self, camera: &mut CameraController) {
+        camera.position = Vec3::from_array(self.position);
+        camera.previous_position = camera.position;
+        camera.target = Vec3::from_array(self.target);
+        camera.up = Vec3::from_array(self.up);
+        camera.fov = self.fov;
+        camera.transition = None;
+        match self.orthographic_extent {
+            Some(extent) => {
+                camera.mode = CameraMode::TopDown;
+                camera.top_down.extent = extent;
+            }
+            None => camera.mode = CameraMode::FirstPerson,
+        }
+    }
+}
+
+/// Camera transforms recorded once per simulation tick, for replaying the exact same
+/// flight, see `headless`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CameraPath {
+    pub ticks_per_second: f32,
+    /// Terrain the path was recorded over, played back with the same one. None for
+    /// recordings that don't store it, those play over the headless scene's terrain.
+    #[serde(default)]
+    pub generator_config: Option<TerrainGeneratorConfig>,
+    pub samples: Vec<CameraPathSample>,
+}
+
+impl CameraPath {
+    pub fn new(ticks_per_second: f32, generator_config: TerrainGeneratorConfig) -> Self {
+        CameraPath {
+            ticks_per_second,
+            generator_config: Some(generator_config),
+            samples: Vec::new(),
+        }
+    }
+
+    /// Appends the camera's transform, call once per tick.
+    pub fn record(&mut self, camera: &CameraController) {
+        self.samples.push(CameraPathSample::from_camera(camera));
+    }
+
+    pub fn duration_seconds(&self) -> f32 {
+        self.samples.len() as f32 / self.ticks_per_second
+    }
+
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        let path: Self = serde_json::from_reader(reader)?;
+        if !path.ticks_per_second.is_finite() || path.ticks_per_second <= 0.0 {
+            return Err(format!("Tick rate {} must be a positive number", path.ticks_per_second).into());
+        }
+        Ok(path)
+    }
+
+    /// Saves the path, creating missing directories.
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(directory) = path.as_ref().parent() {
+            std::fs::create_dir_all(directory)?;
+        }
+        let file = File::create(path)?;
+        let writer = BufWriter::new(file);
+        serde_json::to_writer(writer, self)?;
+        Ok(())
+    }
+}
+
+/// `camera_paths/camera_path_<unix seconds>.json`
+pub fn default_camera_path(time: SystemTime) -> PathBuf {
+    let seconds = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    Path::new(CAMERA_PATH_DIRECTORY).join(format!("camera_path_{}.json", seconds))
+}
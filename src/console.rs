@@ -0,0 +1,209 @@
+use std::io::Write;
+use std::path::PathBuf;
+
+use ferrousgl::GlWindow;
+use glam::{IVec3, Vec3};
+
+use crate::input::action::Action;
+use crate::input::bindings::InputBindings;
+use crate::renderer::graphics_config::GraphicsConfig;
+use crate::renderer::Renderer;
+use crate::terrain::terrain_manager::TerrainManager;
+
+/// Where `/gfx` writes changed graphics settings.
+pub const GRAPHICS_CONFIG_PATH: &str = "./assets/data/graphics_config.json";
+
+/// Engine state commands are allowed to change.
+pub struct ConsoleContext<'a> {
+    pub terrain_manager: &'a mut TerrainManager,
+    pub renderer: &'a mut Renderer,
+    pub graphics_config: &'a mut GraphicsConfig,
+    /// Where chunks are loaded around
+    pub camera_position: Vec3,
+}
+
+/// In-game command line, opened with `/` and submitted with `Enter` by default.
+pub struct Console {
+    pub typing_command: bool,
+    typed_keys: Vec<char>,
+}
+
+impl Console {
+    pub fn new() -> Self {
+        Console {
+            typing_command: false,
+            typed_keys: Vec::new(),
+        }
+    }
+
+    /// Collects typed characters and executes the command on `SubmitConsole`.
+    pub fn update(&mut self, window: &mut GlWindow, bindings: &InputBindings, context: &mut ConsoleContext) {
+        if bindings.was_triggered(Action::OpenConsole, window) {
+            self.typing_command = true;
+            println!("Enter CMD:");
+        }
+        if bindings.was_triggered(Action::SubmitConsole, window) {
+            self.typing_command = false;
+            let command = self.typed_keys.iter().collect::<String>();
+            println!("Typed command: {:?}", command);
+
+            match Self::parse_and_execute_command(&command, context) {
+                Ok(_) => println!("Command executed successfully"),
+                Err(e) => println!("Error parsing command: {}", e),
+            }
+
+            self.typed_keys.clear();
+        }
+        if self.typing_command {
+            window.get_typed_keys().iter().for_each(|key| {
+                self.typed_keys.push(*key);
+                print!("{}", *key);
+                std::io::stdout().flush().unwrap();
+            });
+        }
+    }
+
+    pub fn parse_and_execute_command(command: &str, context: &mut ConsoleContext) -> Result<(), String> {
+        let parts: Vec<&str> = command.split_whitespace().collect();
+
+        match parts.first() {
+            Some(&"/p") => Self::place_voxel_command(&parts, context.terrain_manager),
+            Some(&"/gfx") => Self::graphics_command(&parts, context),
+            Some(&"/time") => Self::time_command(&parts, context.renderer),
+            Some(&"/debug") => Self::debug_command(&parts, context.renderer),
+            Some(&"/screenshot") => Self::screenshot_command(&parts, context.renderer),
+            _ => Err("Unknown command (expected /p, /gfx, /time, /debug or /screenshot)".to_string()),
+        }
+    }
+
+    /// `/p x y z size_x size_y size_z value`
+    fn place_voxel_command(parts: &[&str], terrain_manager: &mut TerrainManager) -> Result<(), String> {
+        if parts.len() != 8 {
+            return Err(format!("Expected 7 arguments (got {}). Usage: place_voxel x y z size_x size_y size_z value", parts.len() - 1));
+        }
+
+        // Parse all numeric arguments
+        let x = parts[1].parse::<i16>().map_err(|e| e.to_string())?;
+        let y = parts[2].parse::<i16>().map_err(|e| e.to_string())?;
+        let z = parts[3].parse::<i16>().map_err(|e| e.to_string())?;
+        let size_x = parts[4].parse::<i16>().map_err(|e| e.to_string())?;
+        let size_y = parts[5].parse::<i16>().map_err(|e| e.to_string())?;
+        let size_z = parts[6].parse::<i16>().map_err(|e| e.to_string())?;
+        let value = parts[7].parse::<f32>().map_err(|e| e.to_string())?;
+
+        // Execute the function
+        terrain_manager.place_voxel_in_chunk(
+            IVec3::new(x as i32, y as i32, z as i32),
+            IVec3::new(size_x as i32, size_y as i32, size_z as i32),
+            value,
+        );
+
+        Ok(())
+    }
+
+    /// `/gfx` lists the settings, `/gfx name value` changes one, applies it and saves the
+    /// config.
+    fn graphics_command(parts: &[&str], context: &mut ConsoleContext) -> Result<(), String> {
+        if parts.len() == 1 {
+            println!("{:#?}", context.graphics_config);
+            return Ok(());
+        }
+        if parts.len() != 3 {
+            return Err("Usage: /gfx [setting value]".to_string());
+        }
+
+        let previous = context.graphics_config.clone();
+        context.graphics_config.set(parts[1], parts[2])?;
+
+        let config = &*context.graphics_config;
+        config.apply_to_window();
+        context.renderer.apply_graphics_config(config)?;
+        if config.view_distance != previous.view_distance {
+            let camera_chunk = context.terrain_manager.world_to_chunk_position(context.camera_position);
+            context.terrain_manager.enqueue_chunks_in_radius(camera_chunk, config.view_distance);
+        }
+        if config.requires_restart(&previous) {
+            println!("{} takes effect after a restart", parts[1]);
+        }
+
+        config
+            .save_to_file(GRAPHICS_CONFIG_PATH)
+            .map_err(|e| format!("Failed to save graphics config: {}", e))
+    }
+
+    /// `/time` prints the clock, `/time hh[:mm]` sets it and `/time scale x` sets how many
+    /// game hours pass per real second.
+    fn time_command(parts: &[&str], renderer: &mut Renderer) -> Result<(), String> {
+        let time_of_day = &mut renderer.time_of_day;
+        match parts.len() {
+            1 => {}
+            2 => time_of_day.set_hours(Self::parse_clock(parts[1])?),
+            3 if parts[1] == "scale" => {
+                time_of_day.time_scale = parts[2].parse::<f32>().map_err(|e| e.to_string())?;
+            }
+            _ => return Err("Usage: /time [hh[:mm] | scale x]".to_string()),
+        }
+
+        println!("Time {} (scale {} h/s)", time_of_day.clock_string(), time_of_day.time_scale);
+        Ok(())
+    }
+
+    /// `/debug` toggles the debug lines, `/debug layer value` changes one layer and makes
+    /// sure the lines are shown.
+    fn debug_command(parts: &[&str], renderer: &mut Renderer) -> Result<(), String> {
+        match parts.len() {
+            1 => {
+                let enabled = !renderer.is_pass_enabled("debug_lines");
+                renderer.set_pass_enabled("debug_lines", enabled);
+            }
+            3 => {
+                renderer.debug_draw.set(parts[1], parts[2])?;
+                renderer.set_pass_enabled("debug_lines", true);
+            }
+            _ => return Err("Usage: /debug [layer value]".to_string()),
+        }
+
+        println!(
+            "Debug lines {}: {:#?}",
+            if renderer.is_pass_enabled("debug_lines") { "on" } else { "off" },
+            renderer.debug_draw
+        );
+        Ok(())
+    }
+
+    /// `/screenshot [path]` saves the next frame as a PNG.
+    fn screenshot_command(parts: &[&str], renderer: &mut Renderer) -> Result<(), String> {
+        match parts.len() {
+            1 => renderer.request_screenshot(None),
+            2 => renderer.request_screenshot(Some(PathBuf::from(parts[1]))),
+            _ => return Err("Usage: /screenshot [path]".to_string()),
+        }
+        Ok(())
+    }
+
+    /// Parses `hh`, `hh.h` or `hh:mm` into hours.
+    pub fn parse_clock(text: &str) -> Result<f32, String> {
+        let hours = match text.split_once(':') {
+            Some((hours, minutes)) => {
+                let hours = hours.parse::<u32>().map_err(|e| e.to_string())?;
+                let minutes = minutes.parse::<u32>().map_err(|e| e.to_string())?;
+                if minutes >= 60 {
+                    return Err(format!("Invalid minutes in '{}'", text));
+                }
+                hours as f32 + minutes as f32 / 60.0
+            }
+            None => text.parse::<f32>().map_err(|e| e.to_string())?,
+        };
+
+        if !(0.0..=24.0).contains(&hours) {
+            return Err(format!("Time '{}' is outside 0..24", text));
+        }
+        Ok(hours)
+    }
+}
+
+impl Default for Console {
+    fn default() -> Self {
+        Self::new()
+    }
+}
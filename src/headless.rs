@@ -0,0 +1,308 @@
+//! Offscreen rendering of a fixed scene for visual regression tests.
+//!
+//! `fallendust --offscreen out.png --golden tests/golden/default.png` renders the scene
+//! without showing the window, writes it to `out.png` and fails if it differs from the
+//! golden image by more than the tolerance. GLFW still needs a display, on CI run it under
+//! `xvfb-run` with `LIBGL_ALWAYS_SOFTWARE=1` to use Mesa's software rasterizer.
+//! `scripts/golden.sh` does both, `scripts/golden.sh --bless` renders a new golden image.
+//!
+//! `--play path.json` flies a recorded camera path instead, one tick per frame, streaming
+//! chunks around the camera like a real flight over the terrain it was recorded in and
+//! printing frame, generation and meshing timings. `--report report.json` also saves them.
+//! Each tick generates a fixed number of chunks, so every run loads the same chunks at the
+//! same frames. The output image is the path's last view once every queued chunk is
+//! generated, so it can be compared like the golden scene.
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use ferrousgl::GlWindow;
+use glam::{IVec3, Vec3};
+
+use crate::assets::asset_manager::AssetManager;
+use crate::benchmark::{BenchmarkReport, FrameTiming};
+use crate::camera_controller::CameraController;
+use crate::camera_path::CameraPath;
+use crate::language::Language;
+use crate::renderer::graphics_config::GraphicsConfig;
+use crate::renderer::screenshot;
+use crate::renderer::Renderer;
+use crate::terrain::terrain_generator_config::TerrainGeneratorConfig;
+use crate::terrain::terrain_manager::TerrainManager;
+
+/// Camera, world and time of a golden image. Nothing is read from the user's config
+/// files, so the image only changes when the code or the assets do.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoldenScene {
+    pub seed: u32,
+    pub width: u32,
+    pub height: u32,
+    pub view_distance: i32,
+    pub camera_position: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub hours: f32,
+}
+
+impl Default for GoldenScene {
+    fn default() -> Self {
+        GoldenScene {
+            seed: 0,
+            width: 640,
+            height: 360,
+            view_distance: 2,
+            camera_position: Vec3::new(32.0, 60.0, 96.0),
+            yaw: -90.0,
+            pitch: -20.0,
+            hours: 10.0,
+        }
+    }
+}
+
+impl GoldenScene {
+    pub fn camera(&self) -> CameraController {
+        let mut camera = CameraController::new(self.width as f32 / self.height as f32);
+        camera.position = self.camera_position;
+        camera.previous_position = self.camera_position;
+        camera.yaw = self.yaw;
+        camera.pitch = self.pitch;
+        camera.target = camera.position + camera.forward();
+        camera
+    }
+
+    pub fn generator_config(&self) -> TerrainGeneratorConfig {
+        TerrainGeneratorConfig {
+            seed: self.seed,
+            ..Default::default()
+        }
+    }
+
+    /// Default graphics settings at the scene size. MSAA is off, so golden images don't
+    /// depend on the driver's sample patterns.
+    pub fn graphics_config(&self) -> GraphicsConfig {
+        GraphicsConfig {
+            width: self.width,
+            height: self.height,
+            fullscreen: false,
+            vsync: false,
+            msaa_samples: 0,
+            view_distance: self.view_distance,
+            ..Default::default()
+        }
+    }
+}
+
+/// What to render and what to compare it with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeadlessOptions {
+    pub output: PathBuf,
+    pub golden: Option<PathBuf>,
+    /// Largest per-channel difference that still counts as the same pixel
+    pub channel_tolerance: u8,
+    /// Fraction of pixels that may differ before the comparison fails
+    pub max_differing_fraction: f32,
+    pub scene: GoldenScene,
+    /// Camera path to play back instead of the still scene
+    pub camera_path: Option<PathBuf>,
+    /// Where to save the playback timings
+    pub report: Option<PathBuf>,
+}
+
+impl HeadlessOptions {
+    pub fn new(output: PathBuf) -> Self {
+        HeadlessOptions {
+            output,
+            golden: None,
+            channel_tolerance: 8,
+            max_differing_fraction: 0.002,
+            scene: GoldenScene::default(),
+            camera_path: None,
+            report: None,
+        }
+    }
+}
+
+/// Parses `--offscreen <out.png> [--golden <file>] [--tolerance <0-255>]
+/// [--max-differing <fraction>] [--seed <n>] [--size <w>x<h>] [--play <path.json>
+/// [--report <report.json>]]`. Returns None when `--offscreen` isn't given and the game
+/// should start normally.
+pub fn parse_args(args: &[String]) -> Result<Option<HeadlessOptions>, String> {
+    if !args.iter().any(|arg| arg == "--offscreen") {
+        return Ok(None);
+    }
+    let mut options = HeadlessOptions::new(PathBuf::new());
+
+    let mut args = args.iter().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().map(String::as_str).ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--offscreen" => options.output = PathBuf::from(value()?),
+            "--golden" => options.golden = Some(PathBuf::from(value()?)),
+            "--tolerance" => options.channel_tolerance = value()?.parse::<u8>().map_err(|e| e.to_string())?,
+            "--max-differing" => {
+                options.max_differing_fraction = value()?.parse::<f32>().map_err(|e| e.to_string())?;
+            }
+            "--seed" => options.scene.seed = value()?.parse::<u32>().map_err(|e| e.to_string())?,
+            "--size" => {
+                let size = value()?;
+                let (width, height) = size.split_once('x').ok_or(format!("Expected <width>x<height>, got '{}'", size))?;
+                options.scene.width = width.parse::<u32>().map_err(|e| e.to_string())?;
+                options.scene.height = height.parse::<u32>().map_err(|e| e.to_string())?;
+            }
+            "--play" => options.camera_path = Some(PathBuf::from(value()?)),
+            "--report" => options.report = Some(PathBuf::from(value()?)),
+            _ => return Err(format!("Unknown argument '{}'", arg)),
+        }
+    }
+
+    if options.report.is_some() && options.camera_path.is_none() {
+        return Err("--report needs a camera path to --play".to_string());
+    }
+    Ok(Some(options))
+}
+
+/// Renders the scene or plays the camera path, saves the image and compares it with the
+/// golden image if one is given.
+pub fn run(options: &HeadlessOptions) -> Result<(), String> {
+    let scene = &options.scene;
+    let graphics_config = scene.graphics_config();
+
+    // Loaded first, the path decides which terrain to generate
+    let camera_path = match &options.camera_path {
+        Some(path) => {
+            Some(CameraPath::load_from_file(path).map_err(|e| format!("Failed to load camera path {:?}: {}", path, e))?)
+        }
+        None => None,
+    };
+    let generator_config = camera_path
+        .as_ref()
+        .and_then(|path| path.generator_config.clone())
+        .unwrap_or_else(|| scene.generator_config());
+
+    // ferrousgl has no hidden window option. Initialize GLFW first and create the window
+    // hidden: hints are ignored before glfwInit, and ferrousgl's own glfwInit returns early
+    // without resetting them once GLFW is initialized
+    unsafe {
+        glfw::ffi::glfwInit();
+        glfw::ffi::glfwWindowHint(glfw::ffi::VISIBLE, glfw::ffi::FALSE);
+    }
+    let mut window = GlWindow::new(graphics_config.window_config("Fallendust offscreen"));
+
+    let mut camera = scene.camera();
+    let mut terrain_manager = TerrainManager::new(&generator_config);
+
+    let assets = AssetManager::new(Language::load_or_default("./assets/data/language.json"));
+    let mut renderer = Renderer::new(&graphics_config, assets)?;
+    renderer.time_of_day.set_hours(scene.hours);
+    renderer.time_of_day.time_scale = 0.0;
+    renderer.set_pass_enabled("debug_overlay", false);
+
+    if let (Some(path), Some(camera_path)) = (&options.camera_path, &camera_path) {
+        println!(
+            "Playing {:?}, {} ticks ({:.1} s)",
+            path,
+            camera_path.samples.len(),
+            camera_path.duration_seconds()
+        );
+        let report = play_camera_path(camera_path, scene, &mut window, &mut renderer, &mut terrain_manager, &mut camera)?;
+        print!("{}", report);
+        if let Some(report_path) = &options.report {
+            report
+                .save_to_file(report_path)
+                .map_err(|e| format!("Failed to save report {:?}: {}", report_path, e))?;
+            println!("Saved report {:?}", report_path);
+        }
+    } else {
+        terrain_manager.enqueue_chunks_in_radius(
+            terrain_manager.world_to_chunk_position(camera.position),
+            scene.view_distance,
+        );
+    }
+
+    while terrain_manager.is_generating() {
+        terrain_manager.process_chunk_generation();
+    }
+    let image = renderer.render_offscreen(&mut window, &terrain_manager, &camera, scene.width, scene.height)?;
+    screenshot::save_png(&image, &options.output)?;
+    println!("Rendered {:?}", options.output);
+
+    let Some(golden_path) = &options.golden else {
+        return Ok(());
+    };
+    let golden = image::open(golden_path)
+        .map_err(|e| format!("Failed to load golden image {:?}: {}", golden_path, e))?
+        .to_rgba8();
+    let difference = screenshot::compare_images(&image, &golden, options.channel_tolerance)?;
+    println!(
+        "{} of {} pixels differ from {:?} (max channel difference {})",
+        difference.differing_pixels, difference.total_pixels, golden_path, difference.max_channel_difference
+    );
+
+    if difference.within(options.max_differing_fraction) {
+        Ok(())
+    } else {
+        Err(format!(
+            "{:.3}% of the pixels differ, at most {:.3}% are allowed",
+            difference.differing_fraction() * 100.0,
+            options.max_differing_fraction * 100.0
+        ))
+    }
+}
+
+/// Chunk generation steps per playback tick, see `step_playback_terrain`.
+pub const PLAYBACK_GENERATION_STEPS_PER_TICK: usize = 4;
+
+/// Renders one frame per recorded tick and times it. The clock is fixed and every frame
+/// reads its image back, so the timings include waiting for the GPU and don't depend on
+/// the frame rate. Leaves the camera at the path's last sample.
+fn play_camera_path(
+    camera_path: &CameraPath,
+    scene: &GoldenScene,
+    window: &mut GlWindow,
+    renderer: &mut Renderer,
+    terrain_manager: &mut TerrainManager,
+    camera: &mut CameraController,
+) -> Result<BenchmarkReport, String> {
+    let mut loaded_around = None;
+    let mut frames = Vec::with_capacity(camera_path.samples.len());
+    terrain_manager.take_timings();
+
+    for sample in &camera_path.samples {
+        let frame_start = Instant::now();
+        sample.apply(camera);
+        step_playback_terrain(terrain_manager, &mut loaded_around, camera.position, scene.view_distance);
+
+        renderer.render_offscreen(window, terrain_manager, camera, scene.width, scene.height)?;
+        frames.push(FrameTiming::new(frame_start.elapsed().as_secs_f64(), &terrain_manager.take_timings()));
+    }
+
+    Ok(BenchmarkReport::new(frames))
+}
+
+/// Loads terrain around the camera for one playback tick. Every tick does the same amount
+/// of work, `PLAYBACK_GENERATION_STEPS_PER_TICK` generation steps and one remesh, instead
+/// of the game's time budgets, so every frame of a playback sees the same chunks no matter
+/// how fast the machine is.
+pub fn step_playback_terrain(
+    terrain_manager: &mut TerrainManager,
+    loaded_around: &mut Option<IVec3>,
+    camera_position: Vec3,
+    view_distance: i32,
+) {
+    // A zero budget makes every call exactly one step
+    terrain_manager.generation_budget = Duration::ZERO;
+    terrain_manager.remesh_budget = Duration::ZERO;
+
+    let camera_chunk = terrain_manager.world_to_chunk_position(camera_position);
+    if *loaded_around != Some(camera_chunk) {
+        terrain_manager.enqueue_chunks_in_radius(camera_chunk, view_distance);
+        *loaded_around = Some(camera_chunk);
+    }
+    for _ in 0..PLAYBACK_GENERATION_STEPS_PER_TICK {
+        if !terrain_manager.is_generating() {
+            break;
+        }
+        terrain_manager.process_chunk_generation();
+    }
+    terrain_manager.process_remeshing(camera_position);
+}
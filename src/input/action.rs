@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+
+/// Everything the player can do with a key or mouse button. Code asks
+/// `InputBindings` about actions instead of checking keys directly, so every control
+/// can be remapped in `input_bindings.json`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Action {
+    MoveForward,
+    MoveBackward,
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    Sprint,
+    Jump,
+    /// Edit the terrain where the camera or the free cursor points
+    Sculpt,
+    OpenConsole,
+    SubmitConsole,
+    ToggleWireframe,
+    ClearChunks,
+    LoadChunks,
+    ToggleDebugLines,
+    ToggleLodColors,
+    Screenshot,
+    ToggleWalk,
+    ToggleOrbit,
+    ToggleTopDown,
+    /// Switch between mouse look and a free cursor
+    ToggleCursor,
+    /// Start recording the camera path, or stop and save it
+    ToggleRecording,
+}
+
+impl Action {
+    pub const ALL: [Action; 22] = [
+        Action::MoveForward,
+        Action::MoveBackward,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::Sprint,
+        Action::Jump,
+        Action::Sculpt,
+        Action::OpenConsole,
+        Action::SubmitConsole,
+        Action::ToggleWireframe,
+        Action::ClearChunks,
+        Action::LoadChunks,
+        Action::ToggleDebugLines,
+        Action::ToggleLodColors,
+        Action::Screenshot,
+        Action::ToggleWalk,
+        Action::ToggleOrbit,
+        Action::ToggleTopDown,
+        Action::ToggleCursor,
+        Action::ToggleRecording,
+    ];
+
+    /// Bindings used when the config file doesn't mention the action.
+    pub fn default_bindings(self) -> &'static [&'static str] {
+        match self {
+            Action::MoveForward => &["W"],
+            Action::MoveBackward => &["S"],
+            Action::MoveLeft => &["A"],
+            Action::MoveRight => &["D"],
+            Action::MoveUp => &["E"],
+            Action::MoveDown => &["Q"],
+            Action::Sprint => &["LeftShift"],
+            Action::Jump => &["Space"],
+            Action::Sculpt => &["MouseLeft"],
+            Action::OpenConsole => &["Slash"],
+            Action::SubmitConsole => &["Enter"],
+            Action::ToggleWireframe => &["F1"],
+            Action::ClearChunks => &["F3"],
+            Action::LoadChunks => &["F4"],
+            Action::ToggleDebugLines => &["F5"],
+            Action::ToggleLodColors => &["F6"],
+            Action::Screenshot => &["F12"],
+            Action::ToggleWalk => &["V"],
+            Action::ToggleOrbit => &["O"],
+            Action::ToggleTopDown => &["T"],
+            Action::ToggleCursor => &["Tab"],
+            Action::ToggleRecording => &["F9"],
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use serde::{Deserialize, Serialize};
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind};
+use std::path::Path;
+use std::str::FromStr;
+
+use super::action::Action;
+use super::input_source::{InputButton, InputSource};
+
+/// A button, optionally with modifiers that have to be held with it, written
+/// `"LeftControl+S"` in the config. Any button can be a modifier, so chords like
+/// `"G+MouseLeft"` work too.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Binding {
+    pub modifiers: Vec<InputButton>,
+    pub button: InputButton,
+}
+
+impl Binding {
+    pub fn new(button: InputButton) -> Self {
+        Binding { modifiers: Vec::new(), button }
+    }
+
+    pub fn with_modifier(mut self, modifier: InputButton) -> Self {
+        self.modifiers.push(modifier);
+        self
+    }
+
+    fn modifiers_held(&self, input: &dyn InputSource) -> bool {
+        self.modifiers.iter().all(|&modifier| input.is_held(modifier))
+    }
+
+    /// True if both need the same buttons, whatever order the modifiers are listed in.
+    pub fn same_buttons(&self, other: &Binding) -> bool {
+        self.button == other.button
+            && self.modifiers.len() == other.modifiers.len()
+            && self.modifiers.iter().all(|modifier| other.modifiers.contains(modifier))
+    }
+
+    /// True if `other` needs everything this does plus more, so it should win when both match.
+    fn is_extended_by(&self, other: &Binding) -> bool {
+        self.button == other.button
+            && other.modifiers.len() > self.modifiers.len()
+            && self.modifiers.iter().all(|modifier| other.modifiers.contains(modifier))
+    }
+}
+
+impl FromStr for Binding {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut buttons = text
+            .split('+')
+            .map(|name| name.trim().parse::<InputButton>())
+            .collect::<Result<Vec<_>, _>>()?;
+        let button = buttons.pop().ok_or_else(|| "Empty binding".to_string())?;
+        Ok(Binding { modifiers: buttons, button })
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for modifier in &self.modifiers {
+            write!(f, "{}+", modifier)?;
+        }
+        write!(f, "{}", self.button)
+    }
+}
+
+impl TryFrom<String> for Binding {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl From<Binding> for String {
+    fn from(binding: Binding) -> Self {
+        binding.to_string()
+    }
+}
+
+/// Two actions that fire from the same buttons.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BindingConflict {
+    pub binding: Binding,
+    pub first: Action,
+    pub second: Action,
+}
+
+impl fmt::Display for BindingConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} is bound to both {:?} and {:?}", self.binding, self.first, self.second)
+    }
+}
+
+/// Bindings per action, loaded from `input_bindings.json`. An action can have several
+/// bindings and fires from any of them.
+///
+/// When a chord matches, bindings of the same button with fewer modifiers stay quiet:
+/// with `S` on MoveBackward and `LeftControl+S` on something else, Ctrl+S doesn't also
+/// walk backwards.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct InputBindings {
+    pub bindings: BTreeMap<Action, Vec<Binding>>,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        let bindings = Action::ALL
+            .iter()
+            .map(|&action| {
+                let defaults = action
+                    .default_bindings()
+                    .iter()
+                    .map(|text| text.parse().expect("default bindings are valid"))
+                    .collect();
+                (action, defaults)
+            })
+            .collect();
+        InputBindings { bindings }
+    }
+}
+
+impl InputBindings {
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        let bindings: Self = serde_json::from_reader(reader)?;
+        Ok(bindings)
+    }
+
+    /// Loads the bindings, adding defaults for actions the file doesn't list. The file is
+    /// written when it was missing or is only missing some actions. A file that doesn't parse
+    /// is left alone so the player's bindings in it aren't lost. Conflicts are reported but
+    /// kept, the player may want them.
+    pub fn load_or_default<P: AsRef<Path>>(path: P) -> Self {
+        let (mut bindings, mut needs_save) = match Self::load_from_file(&path) {
+            Ok(bindings) => (bindings, false),
+            Err(e) => {
+                let missing = e.downcast_ref::<std::io::Error>().is_some_and(|e| e.kind() == ErrorKind::NotFound);
+                println!("Failed to load input bindings {:?}, using defaults: {}", path.as_ref(), e);
+                (Self::default(), missing)
+            }
+        };
+
+        let defaults = Self::default();
+        for action in Action::ALL {
+            if let Entry::Vacant(entry) = bindings.bindings.entry(action) {
+                println!("Input bindings: {:?} is not bound, using {:?}", action, action.default_bindings());
+                entry.insert(defaults.bindings[&action].clone());
+                needs_save = true;
+            }
+        }
+        for conflict in bindings.conflicts() {
+            println!("Input bindings: {}", conflict);
+        }
+
+        if needs_save && let Err(e) = bindings.save_to_file(&path) {
+            println!("Failed to write input bindings {:?}: {}", path.as_ref(), e);
+        }
+
+        bindings
+    }
+
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
+        let file = File::create(path)?;
+        let writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+
+    /// Replaces every binding of `action`.
+    pub fn bind(&mut self, action: Action, bindings: Vec<Binding>) {
+        self.bindings.insert(action, bindings);
+    }
+
+    /// Every pair of actions sharing the same buttons.
+    pub fn conflicts(&self) -> Vec<BindingConflict> {
+        let all: Vec<(Action, &Binding)> = self
+            .bindings
+            .iter()
+            .flat_map(|(&action, bindings)| bindings.iter().map(move |binding| (action, binding)))
+            .collect();
+
+        let mut conflicts = Vec::new();
+        for (i, &(first, binding)) in all.iter().enumerate() {
+            for &(second, other) in &all[i + 1..] {
+                if first != second && binding.same_buttons(other) {
+                    conflicts.push(BindingConflict { binding: binding.clone(), first, second });
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// True while any binding of the action is held.
+    pub fn is_active(&self, action: Action, input: &dyn InputSource) -> bool {
+        self.matches(action, input, |button| input.is_held(button))
+    }
+
+    /// True on the frame any binding of the action is pressed.
+    pub fn was_triggered(&self, action: Action, input: &dyn InputSource) -> bool {
+        self.matches(action, input, |button| input.is_pressed(button))
+    }
+
+    /// Every action triggered this frame.
+    pub fn triggered_actions(&self, input: &dyn InputSource) -> Vec<Action> {
+        Action::ALL.into_iter().filter(|&action| self.was_triggered(action, input)).collect()
+    }
+
+    fn matches(&self, action: Action, input: &dyn InputSource, button_down: impl Fn(InputButton) -> bool) -> bool {
+        let Some(bindings) = self.bindings.get(&action) else {
+            return false;
+        };
+
+        bindings.iter().any(|binding| {
+            button_down(binding.button)
+                && binding.modifiers_held(input)
+                && !self
+                    .bindings
+                    .values()
+                    .flatten()
+                    .any(|other| binding.is_extended_by(other) && other.modifiers_held(input))
+        })
+    }
+}
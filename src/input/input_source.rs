@@ -0,0 +1,151 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use ferrousgl::{GlWindow, WindowKey};
+use glfw::MouseButton;
+
+/// A key or mouse button.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputButton {
+    Key(WindowKey),
+    Mouse(MouseButton),
+}
+
+/// Key names used in `input_bindings.json`.
+const KEY_NAMES: &[(&str, WindowKey)] = &[
+    ("A", WindowKey::A), ("B", WindowKey::B), ("C", WindowKey::C), ("D", WindowKey::D),
+    ("E", WindowKey::E), ("F", WindowKey::F), ("G", WindowKey::G), ("H", WindowKey::H),
+    ("I", WindowKey::I), ("J", WindowKey::J), ("K", WindowKey::K), ("L", WindowKey::L),
+    ("M", WindowKey::M), ("N", WindowKey::N), ("O", WindowKey::O), ("P", WindowKey::P),
+    ("Q", WindowKey::Q), ("R", WindowKey::R), ("S", WindowKey::S), ("T", WindowKey::T),
+    ("U", WindowKey::U), ("V", WindowKey::V), ("W", WindowKey::W), ("X", WindowKey::X),
+    ("Y", WindowKey::Y), ("Z", WindowKey::Z),
+    ("0", WindowKey::Num0), ("1", WindowKey::Num1), ("2", WindowKey::Num2), ("3", WindowKey::Num3),
+    ("4", WindowKey::Num4), ("5", WindowKey::Num5), ("6", WindowKey::Num6), ("7", WindowKey::Num7),
+    ("8", WindowKey::Num8), ("9", WindowKey::Num9),
+    ("F1", WindowKey::F1), ("F2", WindowKey::F2), ("F3", WindowKey::F3), ("F4", WindowKey::F4),
+    ("F5", WindowKey::F5), ("F6", WindowKey::F6), ("F7", WindowKey::F7), ("F8", WindowKey::F8),
+    ("F9", WindowKey::F9), ("F10", WindowKey::F10), ("F11", WindowKey::F11), ("F12", WindowKey::F12),
+    ("Slash", WindowKey::Slash), ("Enter", WindowKey::Enter), ("Space", WindowKey::Space),
+    ("Escape", WindowKey::Escape), ("Tab", WindowKey::Tab), ("Backspace", WindowKey::Backspace),
+    ("LeftShift", WindowKey::LeftShift), ("RightShift", WindowKey::RightShift),
+    ("LeftControl", WindowKey::LeftControl), ("RightControl", WindowKey::RightControl),
+    ("LeftAlt", WindowKey::LeftAlt), ("RightAlt", WindowKey::RightAlt),
+    ("Up", WindowKey::Up), ("Down", WindowKey::Down), ("Left", WindowKey::Left), ("Right", WindowKey::Right),
+];
+
+const MOUSE_NAMES: &[(&str, MouseButton)] = &[
+    ("MouseLeft", MouseButton::Button1),
+    ("MouseRight", MouseButton::Button2),
+    ("MouseMiddle", MouseButton::Button3),
+];
+
+impl FromStr for InputButton {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        KEY_NAMES
+            .iter()
+            .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+            .map(|&(_, key)| InputButton::Key(key))
+            .or_else(|| {
+                MOUSE_NAMES
+                    .iter()
+                    .find(|(button_name, _)| button_name.eq_ignore_ascii_case(name))
+                    .map(|&(_, button)| InputButton::Mouse(button))
+            })
+            .ok_or_else(|| format!("Unknown key or mouse button '{}'", name))
+    }
+}
+
+impl fmt::Display for InputButton {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            InputButton::Key(key) => KEY_NAMES.iter().find(|(_, k)| k == key).map(|(name, _)| *name),
+            InputButton::Mouse(button) => MOUSE_NAMES.iter().find(|(_, b)| b == button).map(|(name, _)| *name),
+        };
+        match name {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "{:?}", self),
+        }
+    }
+}
+
+/// Where button states come from. The game reads the window, tests and replays feed
+/// `ScriptedInput` instead.
+pub trait InputSource {
+    /// Button is down this frame.
+    fn is_held(&self, button: InputButton) -> bool;
+
+    /// Button went down this frame.
+    fn is_pressed(&self, button: InputButton) -> bool;
+}
+
+impl InputSource for GlWindow {
+    fn is_held(&self, button: InputButton) -> bool {
+        match button {
+            InputButton::Key(key) => self.is_key_held(key),
+            // ferrousgl only reports clicks, so held mouse buttons come from GLFW
+            InputButton::Mouse(button) => unsafe {
+                let window = glfw::ffi::glfwGetCurrentContext();
+                !window.is_null() && glfw::ffi::glfwGetMouseButton(window, button as i32) == glfw::ffi::PRESS
+            },
+        }
+    }
+
+    fn is_pressed(&self, button: InputButton) -> bool {
+        match button {
+            InputButton::Key(key) => self.is_key_pressed(key),
+            InputButton::Mouse(button) => self.is_mouse_button_pressed(button),
+        }
+    }
+}
+
+/// Input set from code. Buttons stay held until released, presses last until
+/// `end_frame`.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptedInput {
+    held: HashSet<InputButton>,
+    pressed: HashSet<InputButton>,
+}
+
+impl ScriptedInput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn press(&mut self, button: InputButton) {
+        if self.held.insert(button) {
+            self.pressed.insert(button);
+        }
+    }
+
+    pub fn release(&mut self, button: InputButton) {
+        self.held.remove(&button);
+    }
+
+    /// Press and release within the same frame, like a click.
+    pub fn tap(&mut self, button: InputButton) {
+        self.pressed.insert(button);
+    }
+
+    pub fn release_all(&mut self) {
+        self.held.clear();
+    }
+
+    /// Call after each frame has read its input.
+    pub fn end_frame(&mut self) {
+        self.pressed.clear();
+    }
+}
+
+impl InputSource for ScriptedInput {
+    fn is_held(&self, button: InputButton) -> bool {
+        self.held.contains(&button)
+    }
+
+    fn is_pressed(&self, button: InputButton) -> bool {
+        self.pressed.contains(&button)
+    }
+}
@@ -0,0 +1,3 @@
+pub mod action;
+pub mod bindings;
+pub mod input_source;
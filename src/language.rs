@@ -0,0 +1,57 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use serde_json::Value;
+
+/// User-facing strings from `language.json`, looked up by dotted keys such as
+/// `assets.errors.not_found`.
+#[derive(Debug, Clone, Default)]
+pub struct Language {
+    strings: Value,
+}
+
+impl Language {
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        let root: Value = serde_json::from_reader(reader)?;
+        let strings = root.get("language").cloned().unwrap_or(root);
+        Ok(Language { strings })
+    }
+
+    /// Loads the strings, or an empty table that makes every lookup fall back to its key.
+    pub fn load_or_default<P: AsRef<Path>>(path: P) -> Self {
+        match Self::load_from_file(&path) {
+            Ok(language) => language,
+            Err(e) => {
+                println!("Failed to load language file {:?}: {}", path.as_ref(), e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        key.split('.')
+            .try_fold(&self.strings, |value, part| value.get(part))
+            .and_then(Value::as_str)
+    }
+
+    /// Looks up `key` and fills in its `{name}` placeholders. Missing keys return the key
+    /// followed by the arguments so the information isn't lost.
+    pub fn format(&self, key: &str, arguments: &[(&str, &str)]) -> String {
+        match self.get(key) {
+            Some(template) => arguments.iter().fold(template.to_string(), |text, (name, value)| {
+                text.replace(&format!("{{{}}}", name), value)
+            }),
+            None => {
+                let arguments = arguments
+                    .iter()
+                    .map(|(name, value)| format!("{}={}", name, value))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("{} ({})", key, arguments)
+            }
+        }
+    }
+}
@@ -0,0 +1,29 @@
+//! Fallendust engine library.
+//!
+//! Exposes terrain generation, meshing and editing, the camera controller, input
+//! bindings, walking physics and ray utilities so that the game binary, tools, tests
+//! and benchmarks can all build against the same API.
+
+pub mod assets;
+pub mod benchmark;
+pub mod camera_controller;
+pub mod camera_path;
+pub mod console;
+pub mod headless;
+pub mod input;
+pub mod language;
+pub mod physics;
+pub mod renderer;
+pub mod terrain;
+pub mod utils;
+
+pub use camera_controller::CameraController;
+pub use terrain::marching_cubes::marching_cubes_data_tables::MarchingCubesDataTables;
+pub use terrain::marching_cubes::marching_cubes_generator::MarchingCubesGenerator;
+pub use terrain::scalar::scalar_data::ScalarData;
+pub use terrain::scalar::scalar_generator::ScalarGenerator;
+pub use terrain::terrain_chunk::TerrainChunk;
+pub use terrain::terrain_manager::TerrainManager;
+pub use utils::aabb::Aabb;
+pub use utils::frustum::Frustum;
+pub use utils::ray::Ray;
@@ -1,275 +1,241 @@
-use std::{io::Write, path::Path};
-
-use camera_controller::CameraController;
-use ferrousgl::{DepthType, GlWindow, Mesh, MipmapType, RenderTexture, RenderingType, Shader, WindowConfig, WindowKey};
-use glam::{vec3, IVec3, Mat4, Vec3, Vec4};
-use terrain::terrain_manager::{self, TerrainManager};
-
-mod camera_controller;
-mod terrain;
-mod utils;
-
+use fallendust::assets::asset_manager::AssetManager;
+use fallendust::console::{Console, ConsoleContext, GRAPHICS_CONFIG_PATH};
+use fallendust::headless;
+use fallendust::renderer::graphics_config::GraphicsConfig;
+use fallendust::language::Language;
+use fallendust::renderer::materials::{MaterialLibrary, MATERIAL_LIBRARY_PATH};
+use fallendust::renderer::Renderer;
+use fallendust::terrain::terrain_generator_config::TerrainGeneratorConfig;
+use fallendust::camera_controller::{CameraInput, CameraMode};
+use fallendust::camera_path::{default_camera_path, CameraPath};
+use fallendust::input::action::Action;
+use fallendust::input::bindings::InputBindings;
+use fallendust::{CameraController, Ray, TerrainManager};
+use ferrousgl::GlWindow;
+use fallendust::utils::file_watcher::FileWatcher;
+use fallendust::utils::fixed_timestep::FixedTimestep;
+use glam::IVec3;
+use std::time::{Duration, SystemTime};
+
+const TERRAIN_GENERATOR_CONFIG_PATH: &str = "./assets/data/terrain_generator_config.json";
+const INPUT_BINDINGS_PATH: &str = "./assets/data/input_bindings.json";
+/// Rate of camera movement, edits, chunk processing and the clock, independent of FPS
+const SIMULATION_TICKS_PER_SECOND: f32 = 60.0;
 
 fn main() {
+    // `--offscreen` renders the golden image scene without a visible window and exits
+    let args: Vec<String> = std::env::args().collect();
+    match headless::parse_args(&args) {
+        Ok(Some(options)) => {
+            if let Err(e) = headless::run(&options) {
+                println!("Offscreen render failed: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Ok(None) => {}
+        Err(e) => {
+            println!("{}", e);
+            std::process::exit(2);
+        }
+    }
+
     println!("Initializing Core Engine");
 
-    let mut window = GlWindow::new(WindowConfig {
-        title: "Fallendust".to_string(),
-        width: 1080,
-        height: 720,
-        target_framerate: 1444,
-        anti_aliasing: 4,
-        ..Default::default()
-    });
+    let mut graphics_config = GraphicsConfig::load_or_default(GRAPHICS_CONFIG_PATH);
+
+    let mut window = GlWindow::new(graphics_config.window_config("Fallendust"));
+    graphics_config.apply_to_window();
 
     let mut camera_controller = CameraController::new(
         (window.get_window_size().0 as f32) / (window.get_window_size().1 as f32),
     );
-    let mut terrain_manager = TerrainManager::new();
+    camera_controller.set_cursor_captured(true);
+    let mut generator_config = match load_generator_config() {
+        Ok(config) => config,
+        Err(e) => {
+            println!("Failed to load terrain generator config {:?}, using defaults: {}", TERRAIN_GENERATOR_CONFIG_PATH, e);
+            TerrainGeneratorConfig::default()
+        }
+    };
+    let mut terrain_manager = TerrainManager::new(&generator_config);
 
-    let seed = 123456789;
-    let frequency = 0.5;
+    let mut generator_config_watcher = FileWatcher::new(Duration::from_millis(500));
+    generator_config_watcher.watch_file(TERRAIN_GENERATOR_CONFIG_PATH);
 
-    terrain_manager.enqueue_chunks_in_radius(IVec3::new(0, 0, 0), 4);
+    terrain_manager.enqueue_chunks_in_radius(IVec3::new(0, 0, 0), graphics_config.view_distance);
     
-    // shadow stuff
-    let depth_shader = Shader::new_from_file(
-        Path::new("./assets/shaders/shadows/vertex.glsl"),
-        Path::new("./assets/shaders/shadows/fragment.glsl"),
-    ).unwrap();
+    let assets = AssetManager::new(Language::load_or_default("./assets/data/language.json"));
+    let mut renderer = Renderer::new(&graphics_config, assets).unwrap();
 
-    let mut depth_texture = RenderTexture::new(2048, 2048, true).unwrap();
-    depth_texture.texture().bind(0);
-    depth_texture.set_mipmap_type(MipmapType::Nearest);
+    let mut console = Console::new();
+    let bindings = InputBindings::load_or_default(INPUT_BINDINGS_PATH);
+    let mut wireframe = false;
+    let mut recording: Option<CameraPath> = None;
 
-    // debug quad for depth texture
-    let quad_shader = Shader::new_from_file(
-        Path::new("./assets/shaders/debug_quad/vertex.glsl"),
-        Path::new("./assets/shaders/debug_quad/fragment.glsl"),
-    ).unwrap();
+    let mut timestep = FixedTimestep::new(SIMULATION_TICKS_PER_SECOND);
+    let mut pending_edit: Option<Ray> = None;
 
-    let mut quad_mesh = Mesh::new();
-    
-    let quad_vertices = [
-        // positions   // texture coords
-        -1.0, -1.0,   0.0, 0.0,  // bottom-left
-        -0.25, -1.0,   1.0, 0.0,  // bottom-right
-        -0.25, -0.25,   1.0, 1.0,  // top-right
-        -1.0, -0.25,   0.0, 1.0   // top-left
-    ];
-
-    let quad_indices = [0, 1, 3, 1, 2, 3];
+    while !window.should_window_close() {
+        console.update(
+            &mut window,
+            &bindings,
+            &mut ConsoleContext {
+                terrain_manager: &mut terrain_manager,
+                renderer: &mut renderer,
+                graphics_config: &mut graphics_config,
+                camera_position: camera_controller.position,
+            },
+        );
 
-    quad_mesh.update_vertices(&quad_vertices);
-    quad_mesh.update_indices(&quad_indices);
-    quad_mesh.add_vertex_attributes(&[
-        (0, 2, gl::FLOAT, false),  // position
-        (1, 2, gl::FLOAT, false)   // texture coord
-    ]);
+        // Keys typed into the console don't also control the game
+        let triggered = if console.typing_command { Vec::new() } else { bindings.triggered_actions(&window) };
 
-    // typing
-    let mut typing_command = false;
-    let mut typed_keys: Vec<char> = Vec::new();
+        // Looking around follows every rendered frame, movement runs on the simulation tick
+        camera_controller.update(&mut window);
+        let camera_input = if console.typing_command {
+            CameraInput::default()
+        } else {
+            CameraController::read_input(&window, &bindings)
+        };
+
+        // Clicks wait for the next tick, frames without a tick would lose them otherwise.
+        // A captured cursor edits where the camera looks, a free one where it points
+        if triggered.contains(&Action::Sculpt) {
+            pending_edit = Some(if camera_controller.cursor_captured {
+                camera_controller.get_ray()
+            } else {
+                let (x, y) = window.get_mouse_position();
+                camera_controller.screen_point_to_ray(x as f32, y as f32)
+            });
+        }
 
-    while !window.should_window_close() {
-        fn parse_and_execute_command(command: &str, terrain_manager: &mut TerrainManager) -> Result<(), String> {
-            let parts: Vec<&str> = command.split_whitespace().collect();
-            
-            // Check command name and argument count
-            if parts.is_empty() || parts[0] != "/p" {
-            return Err("Command must start with '/p'".to_string());
+        // Frame time is reported in microseconds
+        let frame_seconds = (window.get_frame_time() / 1_000_000.0) as f32;
+        for _ in 0..timestep.advance(frame_seconds) {
+            let tick = timestep.tick;
+            camera_controller.advance(&camera_input, &terrain_manager, tick);
+            if let Some(path) = &mut recording {
+                path.record(&camera_controller);
             }
-            
-            if parts.len() != 8 {
-            return Err(format!("Expected 7 arguments (got {}). Usage: place_voxel x y z size_x size_y size_z value", parts.len() - 1));
+            renderer.time_of_day.update(tick);
+
+            if let Some(ray) = pending_edit.take() {
+                let hit_position = terrain_manager.raycast(&ray, 1000.0);
+                renderer.debug_draw.raycast_hit_position = hit_position;
+                match hit_position {
+                    Some(hit) => terrain_manager.create_sphere(hit, 4.0),
+                    None => println!("Raycast didn't hit any terrain"),
+                }
             }
-            
-            // Parse all numeric arguments
-            let x = parts[1].parse::<i16>().map_err(|e| e.to_string())?;
-            let y = parts[2].parse::<i16>().map_err(|e| e.to_string())?;
-            let z = parts[3].parse::<i16>().map_err(|e| e.to_string())?;
-            let size_x = parts[4].parse::<i16>().map_err(|e| e.to_string())?;
-            let size_y = parts[5].parse::<i16>().map_err(|e| e.to_string())?;
-            let size_z = parts[6].parse::<i16>().map_err(|e| e.to_string())?;
-            let value = parts[7].parse::<f32>().map_err(|e| e.to_string())?;
-            
-            // Execute the function
-            terrain_manager.place_voxel_in_chunk(
-            IVec3::new(x as i32, y as i32, z as i32),
-            IVec3::new(size_x as i32, size_y as i32, size_z as i32),
-            value,
-            );
-            
-            Ok(())
-        }
 
-        if window.is_key_pressed(WindowKey::Slash) {
-            typing_command = true;
-            println!("Enter CMD:");
+            // Generate queued chunks within the tick budget
+            terrain_manager.process_chunk_generation();
+
+            // Remesh edited chunks within the tick budget, nearest first
+            terrain_manager.process_remeshing(camera_controller.position);
         }
-        if window.is_key_pressed(WindowKey::Enter) {
-            typing_command = false;
-            let command = typed_keys.iter().collect::<String>();
-            println!("Typed command: {:?}", command);
-            
-            match parse_and_execute_command(&command, &mut terrain_manager) {
-            Ok(_) => println!("Command executed successfully"),
-            Err(e) => println!("Error parsing command: {}", e),
+
+        // Hot reload edited shaders, textures and generator settings
+        renderer.reload_assets();
+        if !generator_config_watcher.poll().is_empty() {
+            match load_generator_config() {
+                Ok(config) if config != generator_config => {
+                    let queued = terrain_manager.apply_generator_config(&config, camera_controller.position);
+                    println!("Terrain generator config changed, regenerating {} chunks", queued);
+                    if recording.is_some() {
+                        println!("The camera path being recorded keeps the generator config it started with");
+                    }
+                    generator_config = config;
+                }
+                Ok(_) => {}
+                Err(e) => println!("Failed to reload terrain generator config, keeping the current one: {}", e),
             }
-            
-            typed_keys.clear();
-        }
-        if typing_command {
-            window.get_typed_keys().iter().for_each(|key| {
-            typed_keys.push(*key);
-            print!("{}", *key);
-            std::io::stdout().flush().unwrap();
-            });
         }
 
-        let vp = camera_controller.get_vp();
-        camera_controller.update(&mut window);
-
-        // Process one chunk per frame
-        terrain_manager.process_chunk_generation();
-
-        if window.is_key_pressed(WindowKey::F1) {
-            window.set_rendering_type(ferrousgl::RenderingType::Wireframe);
-        } else if window.is_key_pressed(WindowKey::F2) {
-            window.set_rendering_type(ferrousgl::RenderingType::Solid);
-        } else if window.is_key_pressed(WindowKey::F3) {
+        if triggered.contains(&Action::ToggleWireframe) {
+            wireframe = !wireframe;
+            window.set_rendering_type(if wireframe { ferrousgl::RenderingType::Wireframe } else { ferrousgl::RenderingType::Solid });
+        }
+        if triggered.contains(&Action::ClearChunks) {
             terrain_manager.clear_chunks();
-        } else if window.is_key_pressed(WindowKey::F4) {
+        }
+        if triggered.contains(&Action::LoadChunks) {
             terrain_manager.enqueue_chunks_in_radius(IVec3::new(0,0,0), 16);
         }
+        if triggered.contains(&Action::ToggleDebugLines) {
+            let enabled = !renderer.is_pass_enabled("debug_lines");
+            renderer.set_pass_enabled("debug_lines", enabled);
+        }
+        if triggered.contains(&Action::ToggleLodColors) {
+            renderer.debug_draw.color_by_lod = !renderer.debug_draw.color_by_lod;
+        }
+        if triggered.contains(&Action::Screenshot) {
+            renderer.request_screenshot(None);
+        }
 
-        if window.is_mouse_button_pressed(glfw::MouseButton::Left) {
-            let ray = camera_controller.get_ray();
-            terrain_manager.create_sphere(camera_controller.position, 4.0);
-            
-            if let Some(hit_position) = terrain_manager.raycast(&ray, 1000.0) {
-                //terrain_manager.create_sphere(hit_position, 2.0);
+        if triggered.contains(&Action::ToggleWalk) {
+            camera_controller.toggle_walk_mode();
+            println!("Walk mode {}", if camera_controller.walking { "on" } else { "off" });
+        }
+        if triggered.contains(&Action::ToggleOrbit) {
+            if camera_controller.mode == CameraMode::Orbit {
+                camera_controller.set_mode(CameraMode::FirstPerson);
             } else {
-                // Handle case when raycast doesn't hit anything
-                println!("Raycast didn't hit any terrain");
+                // Orbit what the camera looks at, or a point ahead of it if that's nothing
+                let ray = camera_controller.get_ray();
+                let pivot = terrain_manager.raycast(&ray, 1000.0).unwrap_or(ray.at(camera_controller.orbit.distance));
+                camera_controller.orbit_around(pivot);
             }
         }
-        if window.is_mouse_button_pressed(glfw::MouseButton::Left) {
-            //terrain_manager.place_voxel(camera_controller.position);
+        if triggered.contains(&Action::ToggleTopDown) {
+            let mode = if camera_controller.mode == CameraMode::TopDown { CameraMode::FirstPerson } else { CameraMode::TopDown };
+            camera_controller.set_mode(mode);
         }
-
-        // begin rendering
-        let ortho_projection = Mat4::orthographic_rh(-128.0, 128.0, -128.0, 128.0, 0.0, 155.0);
-
-        // Light comes from above (higher Y value) but moves with camera
-        let light_height = 10.0; // How high above the camera the light is
-        let light_offset = Vec3::new(10.0, light_height, 0.0); // Directly above
-        let light_pos = camera_controller.position + light_offset;
-
-        // Light points downward (toward camera position)
-        let light_target = camera_controller.position; 
-        let light_view = Mat4::look_at_rh(light_pos, light_target, Vec3::Z); // Using Z as up vector for a vertical light
-        let light_dir = (light_target - light_pos).normalize();
-
-        depth_texture.bind();
-        depth_shader.bind_program();
-        depth_shader.set_uniform_matrix_4fv("lightSpaceMatrix", 
-        (ortho_projection * light_view).to_cols_array().as_ref());
-
-        window.clear_color(Vec4::new(0.4, 0.4, 0.9, 1.0));
-        window.clear_depth();
-        window.set_depth_testing(DepthType::LessOrEqual);
-
-        for (_, chunk) in &terrain_manager.chunks {
-            if chunk.is_empty {
-                continue;
+        if triggered.contains(&Action::ToggleRecording) {
+            match recording.take() {
+                Some(path) => {
+                    // Play back with `--offscreen <out.png> --play <path>`
+                    let file = default_camera_path(SystemTime::now());
+                    match path.save_to_file(&file) {
+                        Ok(()) => println!("Saved {:.1} s camera path to {:?}", path.duration_seconds(), file),
+                        Err(e) => println!("Failed to save camera path {:?}: {}", file, e),
+                    }
+                }
+                None => {
+                    println!("Recording camera path");
+                    recording = Some(CameraPath::new(SIMULATION_TICKS_PER_SECOND, generator_config.clone()));
+                }
             }
-
-            let model = Mat4::from_translation(
-                Vec3::new(
-                    chunk.position.x as f32 * terrain_manager.chunk_size as f32,
-                    chunk.position.y as f32 * terrain_manager.chunk_size as f32,
-                    chunk.position.z as f32 * terrain_manager.chunk_size as f32
-                ));
-            depth_shader.set_uniform_matrix_4fv("model", model.to_cols_array().as_ref());
-
-            window.render_mesh(chunk.get_mesh());
         }
-
-        depth_shader.unbind_program();
-        depth_texture.unbind();
-
-        window.update_viewport(window.get_window_size().0, window.get_window_size().1);
-        
-        window.clear_color(Vec4::new(0.4, 0.4, 0.9, 1.0));
-        window.clear_depth();
-        //window.set_depth_testing(DepthType::LessOrEqual);
-        
-        // begin actual color rendering
-
-        terrain_manager.terrain_shader.bind_program();
-        terrain_manager.terrain_shader.set_uniform_3f("lightPos", 0.0, 0.0, 0.0);
-        terrain_manager.terrain_shader.set_uniform_3f("viewPos", camera_controller.position.x, camera_controller.position.y, camera_controller.position.z);
-        terrain_manager.terrain_shader.set_uniform_3f("lightDir", light_dir.x, light_dir.y, light_dir.z);
-        terrain_manager.terrain_shader.set_uniform_matrix_4fv("lightSpaceMatrix", 
-            (ortho_projection * light_view).to_cols_array().as_ref());
-
-        // Set projection and view matrices
-        terrain_manager.terrain_shader.set_uniform_matrix_4fv("projection", &camera_controller.get_projection().to_cols_array());
-        terrain_manager.terrain_shader.set_uniform_matrix_4fv("view", &camera_controller.get_view().to_cols_array());
-
-        terrain_manager.textures[0].bind(0); // Bind the first texture to texture unit 0
-        terrain_manager.textures[1].bind(1);
-        terrain_manager.textures[2].bind(2);
-        terrain_manager.textures[3].bind(3);
-        depth_texture.depth_texture().unwrap().bind(4);
-        terrain_manager.terrain_shader.set_uniform_texture("uGrassTex", 0);
-        terrain_manager.terrain_shader.set_uniform_texture("uGrassNormal", 1);
-        terrain_manager.terrain_shader.set_uniform_texture("uRockTex", 2);
-        terrain_manager.terrain_shader.set_uniform_texture("uRockNormal", 3);
-        terrain_manager.terrain_shader.set_uniform_texture("shadowMap", 4);
-        //terrain_manager.terrain_shader
-
-        for (_, chunk) in &terrain_manager.chunks {
-            if chunk.is_empty {
-                continue;
-            }
-
-            let model = Mat4::from_translation(
-                Vec3::new(
-                    chunk.position.x as f32 * terrain_manager.chunk_size as f32,
-                    chunk.position.y as f32 * terrain_manager.chunk_size as f32,
-                    chunk.position.z as f32 * terrain_manager.chunk_size as f32
-                ));
-            terrain_manager.terrain_shader.set_uniform_matrix_4fv("model", &model.to_cols_array());
-                
-            //window.set_rendering_type(RenderingType::Solid);
-            window.render_mesh(chunk.get_mesh());
-            //window.set_rendering_type(RenderingType::Wireframe);
-            //window.render_mesh(chunk.get_bounding_box_mesh());
+        if triggered.contains(&Action::ToggleCursor) {
+            // Free the cursor to point at the terrain, or capture it again to look around
+            camera_controller.set_cursor_captured(!camera_controller.cursor_captured);
         }
 
-        terrain_manager.terrain_shader.unbind_program();
-
-        // debug quad
-
-        quad_shader.bind_program();
-        depth_texture.depth_texture().unwrap().bind(0);
-        quad_shader.set_uniform_texture("screenTexture", 0);
-        window.set_depth_testing(DepthType::None);
-        window.render_mesh(&quad_mesh);
-        depth_texture.depth_texture().unwrap().unbind();
-
-        // end rendering
+        let render_camera = camera_controller.interpolated(timestep.alpha());
+        let frame_stats = renderer.render(&mut window, &terrain_manager, &render_camera);
 
         let title = format!(
-            "EngineCore Fallendust x64 - FPS: {:.2} - FT: {:.2}ms - camPos: {:?} - RNDR: {:?} [DEBUG F1, F2, F3]",
+            "EngineCore Fallendust x64 - FPS: {:.2} - FT: {:.2}ms - camPos: {:?} - time: {} - culled: {} color / {} shadow - RNDR: {:?} [controls in input_bindings.json]",
             1.0 / (window.get_frame_time() / 1_000_000.0),
             window.get_frame_time(),
             camera_controller.position,
+            renderer.time_of_day.clock_string(),
+            frame_stats.color_culled,
+            frame_stats.shadow_culled,
             unsafe { window.get_renderer() }
         );
         window.set_window_title(&title);
         window.update();
     }
 }
+
+/// Loads the terrain generator config and checks that its strata only use materials the
+/// material library defines.
+fn load_generator_config() -> Result<TerrainGeneratorConfig, Box<dyn std::error::Error>> {
+    let config = TerrainGeneratorConfig::load_from_file(TERRAIN_GENERATOR_CONFIG_PATH)?;
+    let library = MaterialLibrary::load_from_file(MATERIAL_LIBRARY_PATH)?;
+    library.validate_strata(&config.material_strata)?;
+    Ok(config)
+}
@@ -0,0 +1,41 @@
+use glam::Vec3;
+
+/// Step of the central differences used for gradients, half the sample spacing.
+const GRADIENT_STEP: f32 = 0.5;
+
+/// Scalar field that is solid where the density is above `isolevel`, such as the
+/// terrain. Collision code only needs point queries, so tests can use analytic fields.
+pub trait DensityField {
+    /// Density at a world position, None where no data is loaded.
+    fn density(&self, position: Vec3) -> Option<f32>;
+
+    fn isolevel(&self) -> f32;
+
+    fn is_solid(&self, position: Vec3) -> bool {
+        self.density(position).is_some_and(|density| density > self.isolevel())
+    }
+
+    /// Central difference gradient, points into the solid.
+    fn gradient(&self, position: Vec3) -> Option<Vec3> {
+        let mut gradient = Vec3::ZERO;
+        for axis in 0..3 {
+            let mut offset = Vec3::ZERO;
+            offset[axis] = GRADIENT_STEP;
+            gradient[axis] = (self.density(position + offset)? - self.density(position - offset)?) / (2.0 * GRADIENT_STEP);
+        }
+        Some(gradient)
+    }
+
+    /// Approximate signed distance to the surface, positive outside the solid, and the
+    /// direction out of it. Treats the field as linear around `position`, which holds
+    /// close to the surface. None where the field is flat or not loaded.
+    fn surface_distance(&self, position: Vec3) -> Option<(f32, Vec3)> {
+        let density = self.density(position)?;
+        let gradient = self.gradient(position)?;
+        let length = gradient.length();
+        if length < 1e-6 {
+            return None;
+        }
+        Some(((self.isolevel() - density) / length, -gradient / length))
+    }
+}
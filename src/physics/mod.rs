@@ -0,0 +1,2 @@
+pub mod density_field;
+pub mod walker;
@@ -0,0 +1,230 @@
+use glam::Vec3;
+
+use super::density_field::DensityField;
+
+/// Collision passes per move, more settle corners better
+const RESOLVE_ITERATIONS: usize = 4;
+
+/// Shape and movement tuning of a walking character.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WalkSettings {
+    /// Capsule radius
+    pub radius: f32,
+    /// Capsule height from the feet to the top of the head
+    pub height: f32,
+    /// Height of the camera above the feet
+    pub eye_height: f32,
+    /// Downward acceleration in units per second squared
+    pub gravity: f32,
+    /// Upward speed when jumping
+    pub jump_speed: f32,
+    pub walk_speed: f32,
+    pub sprint_multiplier: f32,
+    /// How quickly the horizontal velocity follows the input, units per second squared
+    pub acceleration: f32,
+    /// Fraction of `acceleration` available in the air
+    pub air_control: f32,
+    /// Ledges up to this height are climbed without jumping
+    pub step_height: f32,
+    /// Steepest ground in degrees that can be stood on, steeper ground is slid down
+    pub max_slope: f32,
+    /// Ground this far below the feet pulls a grounded walker down, so it follows
+    /// slopes and steps down instead of flying off them
+    pub ground_snap_distance: f32,
+}
+
+impl Default for WalkSettings {
+    fn default() -> Self {
+        WalkSettings {
+            radius: 0.4,
+            height: 1.8,
+            eye_height: 1.6,
+            gravity: 25.0,
+            jump_speed: 8.0,
+            walk_speed: 5.0,
+            sprint_multiplier: 1.8,
+            acceleration: 50.0,
+            air_control: 0.2,
+            step_height: 0.5,
+            max_slope: 50.0,
+            ground_snap_distance: 0.3,
+        }
+    }
+}
+
+/// First-person character: a vertical capsule pushed out of a density field, with
+/// gravity, jumping, step-up, a slope limit and ground snapping.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Walker {
+    /// Bottom of the capsule
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub on_ground: bool,
+    /// Normal of the ground last stood on
+    pub ground_normal: Vec3,
+    pub settings: WalkSettings,
+}
+
+impl Walker {
+    pub fn new(position: Vec3, settings: WalkSettings) -> Self {
+        Walker {
+            position,
+            velocity: Vec3::ZERO,
+            on_ground: false,
+            ground_normal: Vec3::Y,
+            settings,
+        }
+    }
+
+    pub fn eye_position(&self) -> Vec3 {
+        self.position + Vec3::Y * self.settings.eye_height
+    }
+
+    /// Centres of the spheres that make up the capsule, feet first.
+    fn sphere_centers(&self, position: Vec3) -> [Vec3; 3] {
+        let radius = self.settings.radius;
+        let bottom = position + Vec3::Y * radius;
+        let top = position + Vec3::Y * (self.settings.height - radius).max(radius);
+        [bottom, (bottom + top) * 0.5, top]
+    }
+
+    fn is_walkable(&self, normal: Vec3) -> bool {
+        normal.y >= self.settings.max_slope.to_radians().cos()
+    }
+
+    /// Pushes the capsule at `position` out of the solid. Returns the corrected position
+    /// and the walkable ground normal it rests on, if any.
+    fn resolve(&self, field: &dyn DensityField, mut position: Vec3) -> (Vec3, Option<Vec3>) {
+        let mut ground = None;
+        for _ in 0..RESOLVE_ITERATIONS {
+            let mut moved = false;
+            for (index, center) in self.sphere_centers(position).into_iter().enumerate() {
+                let Some((distance, normal)) = field.surface_distance(center) else {
+                    continue;
+                };
+                if distance >= self.settings.radius {
+                    continue;
+                }
+
+                let push = self.settings.radius - distance;
+                // Feet on walkable ground are pushed straight up so the walker doesn't creep
+                // down slopes it can stand on
+                if index == 0 && self.is_walkable(normal) {
+                    position.y += push / normal.y;
+                    ground = Some(normal);
+                } else {
+                    position += normal * push;
+                }
+                moved = true;
+            }
+            if !moved {
+                break;
+            }
+        }
+        (position, ground)
+    }
+
+    /// Distance from the feet down to walkable ground within `max_distance`, with its normal.
+    fn find_ground(&self, field: &dyn DensityField, position: Vec3, max_distance: f32) -> Option<(f32, Vec3)> {
+        let radius = self.settings.radius;
+        let step = 0.05;
+        let mut drop = 0.0;
+        while drop <= max_distance {
+            let center = position + Vec3::Y * (radius - drop);
+            if let Some((distance, normal)) = field.surface_distance(center)
+                && distance <= radius + 0.01
+            {
+                return self.is_walkable(normal).then_some((drop, normal));
+            }
+            drop += step;
+        }
+        None
+    }
+
+    /// Advances the walker by one tick. `wish_direction` is the horizontal direction the
+    /// player wants to go in, its length is ignored. Nothing moves while the ground below
+    /// isn't loaded, so the walker doesn't fall through unloaded chunks.
+    pub fn simulate(&mut self, field: &dyn DensityField, wish_direction: Vec3, jump: bool, sprint: bool, delta_seconds: f32) {
+        if field.density(self.position).is_none() {
+            return;
+        }
+        let settings = self.settings;
+
+        // Horizontal velocity follows the input, with little control in the air
+        let wish = Vec3::new(wish_direction.x, 0.0, wish_direction.z).normalize_or_zero();
+        let speed = settings.walk_speed * if sprint { settings.sprint_multiplier } else { 1.0 };
+        let control = if self.on_ground { 1.0 } else { settings.air_control };
+        let horizontal = Vec3::new(self.velocity.x, 0.0, self.velocity.z);
+        let change = (wish * speed - horizontal).clamp_length_max(settings.acceleration * control * delta_seconds);
+        self.velocity += change;
+
+        if self.on_ground && jump {
+            self.velocity.y = settings.jump_speed;
+            self.on_ground = false;
+        }
+        let was_on_ground = self.on_ground;
+        if !self.on_ground {
+            self.velocity.y -= settings.gravity * delta_seconds;
+        }
+
+        // Horizontal move, climbing ledges up to the step height
+        let horizontal_move = Vec3::new(self.velocity.x, 0.0, self.velocity.z) * delta_seconds;
+        let (mut position, _) = self.resolve(field, self.position + horizontal_move);
+        if was_on_ground && horizontal_move.length_squared() > 0.0 {
+            let walked = (position - self.position).dot(horizontal_move.normalize());
+            if walked < horizontal_move.length() * 0.5 {
+                let raised = self.position + Vec3::Y * settings.step_height;
+                if !self.capsule_blocked(field, raised) {
+                    let (stepped, _) = self.resolve(field, raised + horizontal_move);
+                    let stepped_walked = (stepped - self.position).dot(horizontal_move.normalize());
+                    if stepped_walked > walked
+                        && let Some((drop, _)) = self.find_ground(field, stepped, settings.step_height)
+                    {
+                        position = stepped - Vec3::Y * drop;
+                    }
+                }
+            }
+        }
+
+        // Vertical move
+        let (resolved, ground) = self.resolve(field, position + Vec3::Y * self.velocity.y * delta_seconds);
+        let pushed_up = resolved.y - (position.y + self.velocity.y * delta_seconds);
+        position = resolved;
+
+        self.on_ground = false;
+        if let Some(normal) = ground
+            && self.velocity.y <= 0.0
+        {
+            self.on_ground = true;
+            self.ground_normal = normal;
+        } else if pushed_up < -1e-4 && self.velocity.y > 0.0 {
+            // Hit the ceiling
+            self.velocity.y = 0.0;
+        }
+
+        // Ground snapping keeps a walking character on slopes and down small steps
+        if !self.on_ground
+            && was_on_ground
+            && self.velocity.y <= 0.0
+            && let Some((drop, normal)) = self.find_ground(field, position, settings.ground_snap_distance)
+        {
+            position.y -= drop;
+            self.on_ground = true;
+            self.ground_normal = normal;
+        }
+
+        if self.on_ground {
+            self.velocity.y = 0.0;
+        }
+        self.position = position;
+    }
+
+    /// True if the capsule at `position` overlaps the solid.
+    fn capsule_blocked(&self, field: &dyn DensityField, position: Vec3) -> bool {
+        self.sphere_centers(position).into_iter().any(|center| {
+            field
+                .surface_distance(center)
+                .is_some_and(|(distance, _)| distance < self.settings.radius - 0.01)
+        })
+    }
+}
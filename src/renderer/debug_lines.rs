@@ -0,0 +1,164 @@
+use glam::{Mat4, Vec3};
+
+use crate::terrain::chunk_lifecycle::ChunkState;
+use crate::utils::aabb::Aabb;
+
+/// Floats per line vertex: position then colour.
+pub const LINE_VERTEX_STRIDE: usize = 6;
+
+/// Which debug layers the debug line pass draws.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DebugDrawSettings {
+    pub chunk_bounds: bool,
+    /// Colour chunk bounds by LOD instead of lifecycle state
+    pub color_by_lod: bool,
+    pub normals: bool,
+    /// Chunks around the camera that get normal lines, drawing all of them is too slow
+    pub normals_radius: i32,
+    pub normal_length: f32,
+    pub raycast_hit: bool,
+    pub generation_queue: bool,
+    pub shadow_frusta: bool,
+    /// Last terrain raycast hit, set by whoever casts the ray
+    pub raycast_hit_position: Option<Vec3>,
+}
+
+impl Default for DebugDrawSettings {
+    fn default() -> Self {
+        DebugDrawSettings {
+            chunk_bounds: true,
+            color_by_lod: false,
+            normals: false,
+            normals_radius: 0,
+            normal_length: 0.5,
+            raycast_hit: true,
+            generation_queue: true,
+            shadow_frusta: false,
+            raycast_hit_position: None,
+        }
+    }
+}
+
+impl DebugDrawSettings {
+    /// Sets a layer by name from console text, `on`/`off` for layers and numbers for
+    /// `normals_radius` and `normal_length`.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let flag = || match value {
+            "on" | "true" | "1" => Ok(true),
+            "off" | "false" | "0" => Ok(false),
+            _ => Err(format!("Expected on or off for {}, got '{}'", name, value)),
+        };
+
+        match name {
+            "chunk_bounds" => self.chunk_bounds = flag()?,
+            "color_by_lod" => self.color_by_lod = flag()?,
+            "normals" => self.normals = flag()?,
+            "raycast_hit" => self.raycast_hit = flag()?,
+            "generation_queue" => self.generation_queue = flag()?,
+            "shadow_frusta" => self.shadow_frusta = flag()?,
+            "normals_radius" => self.normals_radius = value.parse::<i32>().map_err(|e| e.to_string())?.max(0),
+            "normal_length" => self.normal_length = value.parse::<f32>().map_err(|e| e.to_string())?,
+            _ => return Err(format!("Unknown debug layer '{}'", name)),
+        }
+        Ok(())
+    }
+}
+
+/// Lines collected on the CPU during a frame and uploaded in one batch.
+#[derive(Debug, Clone, Default)]
+pub struct DebugLines {
+    pub vertices: Vec<f32>,
+}
+
+impl DebugLines {
+    pub fn new() -> Self {
+        DebugLines { vertices: Vec::new() }
+    }
+
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.vertices.len() / (LINE_VERTEX_STRIDE * 2)
+    }
+
+    pub fn line(&mut self, from: Vec3, to: Vec3, color: Vec3) {
+        for point in [from, to] {
+            self.vertices.extend_from_slice(&[point.x, point.y, point.z, color.x, color.y, color.z]);
+        }
+    }
+
+    /// The twelve edges of a box given by its corners in `Aabb::corners` order.
+    pub fn box_edges(&mut self, corners: &[Vec3; 8], color: Vec3) {
+        // Corner index bits are x, y, z, so edges connect indices differing in one bit
+        for i in 0..8 {
+            for bit in [1, 2, 4] {
+                if i & bit == 0 {
+                    self.line(corners[i], corners[i | bit], color);
+                }
+            }
+        }
+    }
+
+    pub fn aabb(&mut self, aabb: &Aabb, color: Vec3) {
+        self.box_edges(&aabb.corners(), color);
+    }
+
+    /// Edges of the volume a view-projection or light-space matrix maps to clip space.
+    pub fn frustum(&mut self, view_projection: Mat4, color: Vec3) {
+        let inverse = view_projection.inverse();
+        let corners = std::array::from_fn(|i| {
+            let ndc = Vec3::new(
+                if i & 1 == 0 { -1.0 } else { 1.0 },
+                if i & 2 == 0 { -1.0 } else { 1.0 },
+                if i & 4 == 0 { -1.0 } else { 1.0 },
+            );
+            inverse.project_point3(ndc)
+        });
+        self.box_edges(&corners, color);
+    }
+
+    /// Three axis-aligned lines crossing at `position`.
+    pub fn marker(&mut self, position: Vec3, size: f32, color: Vec3) {
+        for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+            self.line(position - axis * size, position + axis * size, color);
+        }
+    }
+}
+
+pub fn chunk_state_color(state: ChunkState) -> Vec3 {
+    match state {
+        ChunkState::Queued => Vec3::new(0.5, 0.5, 0.5),
+        ChunkState::Generating => Vec3::new(1.0, 1.0, 0.0),
+        ChunkState::Meshed => Vec3::new(0.0, 1.0, 0.0),
+        ChunkState::Dirty => Vec3::new(1.0, 0.5, 0.0),
+    }
+}
+
+/// Distinct colours for LOD 1, 2, 4 and 8 and above.
+pub fn lod_color(lod: usize) -> Vec3 {
+    match lod {
+        0 | 1 => Vec3::new(0.0, 1.0, 0.0),
+        2 => Vec3::new(1.0, 1.0, 0.0),
+        3 | 4 => Vec3::new(1.0, 0.5, 0.0),
+        _ => Vec3::new(1.0, 0.0, 0.0),
+    }
+}
+
+/// Red for the front of the generation queue fading to blue at the back.
+pub fn heatmap_color(index: usize, count: usize) -> Vec3 {
+    let t = if count > 1 { index as f32 / (count - 1) as f32 } else { 0.0 };
+    Vec3::new(1.0 - t, 0.0, t)
+}
+
+/// One colour per shadow cascade, nearest first.
+pub fn cascade_color(cascade: usize) -> Vec3 {
+    const COLORS: [Vec3; 4] = [
+        Vec3::new(1.0, 0.2, 0.2),
+        Vec3::new(0.2, 1.0, 0.2),
+        Vec3::new(0.2, 0.4, 1.0),
+        Vec3::new(1.0, 0.2, 1.0),
+    ];
+    COLORS[cascade % COLORS.len()]
+}
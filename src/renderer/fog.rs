@@ -0,0 +1,104 @@
+use ferrousgl::Shader;
+use glam::Vec3;
+
+use super::lighting::Lighting;
+
+/// Relative extinction per colour channel, blue fades first like Rayleigh scattering
+const AERIAL_EXTINCTION: Vec3 = Vec3::new(0.7, 0.85, 1.0);
+
+/// Tunables for the height fog. Density and range follow the view distance, so these
+/// only shape it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FogSettings {
+    pub enabled: bool,
+    /// Loaded radius in chunks, fog is opaque at this distance
+    pub view_distance: i32,
+    /// Multiplier on the density derived from the view distance
+    pub density_scale: f32,
+    /// How quickly the fog thins out with height, per world unit
+    pub height_falloff: f32,
+    /// Height at which the fog has its base density
+    pub base_height: f32,
+    /// Fraction of the view distance where the edge fade starts hiding streaming chunks
+    pub edge_fade_start: f32,
+}
+
+impl Default for FogSettings {
+    fn default() -> Self {
+        FogSettings {
+            enabled: true,
+            view_distance: 4,
+            density_scale: 1.0,
+            height_falloff: 0.015,
+            base_height: 0.0,
+            edge_fade_start: 0.7,
+        }
+    }
+}
+
+/// Fog parameters for one frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fog {
+    /// Extinction per world unit at `base_height`
+    pub density: f32,
+    pub height_falloff: f32,
+    pub base_height: f32,
+    pub edge_start: f32,
+    pub edge_end: f32,
+    /// Inscattered light looking along the horizon, and looking towards the sun
+    pub color: Vec3,
+    pub sun_color: Vec3,
+}
+
+impl Fog {
+    /// Scales the fog so a horizontal ray at the base height keeps a tenth of its light
+    /// over the view distance, and takes its colour from the sky at the horizon.
+    pub fn new(settings: &FogSettings, chunk_size: u16, lighting: &Lighting) -> Self {
+        let view_distance = (settings.view_distance.max(1) * chunk_size as i32) as f32;
+        let density = if settings.enabled { 10.0f32.ln() / view_distance * settings.density_scale } else { 0.0 };
+
+        Fog {
+            density,
+            height_falloff: settings.height_falloff,
+            base_height: settings.base_height,
+            edge_start: if settings.enabled { view_distance * settings.edge_fade_start } else { f32::MAX },
+            edge_end: if settings.enabled { view_distance } else { f32::MAX },
+            color: lighting.horizon_color,
+            sun_color: lighting.horizon_sun_color,
+        }
+    }
+
+    /// Optical depth between two points through the height fog, integrated analytically.
+    pub fn optical_depth(&self, from: Vec3, to: Vec3) -> f32 {
+        let offset = to - from;
+        let distance = offset.length();
+        let rise = offset.y * self.height_falloff;
+        let start_density = self.density * (-self.height_falloff * (from.y - self.base_height)).exp();
+
+        if rise.abs() < 1e-4 {
+            start_density * distance
+        } else {
+            start_density * distance * (1.0 - (-rise).exp()) / rise
+        }
+    }
+
+    /// Fraction of light per colour channel that reaches `from` from `to`.
+    pub fn transmittance(&self, from: Vec3, to: Vec3) -> Vec3 {
+        let distance = from.distance(to);
+        let edge = ((distance - self.edge_start) / (self.edge_end - self.edge_start).max(1e-4)).clamp(0.0, 1.0);
+        let edge = edge * edge * (3.0 - 2.0 * edge);
+        (-AERIAL_EXTINCTION * self.optical_depth(from, to)).exp() * (1.0 - edge)
+    }
+
+    /// Sets the `fog*` uniforms.
+    pub fn apply(&self, shader: &Shader) {
+        shader.set_uniform_1f("fogDensity", self.density);
+        shader.set_uniform_1f("fogHeightFalloff", self.height_falloff);
+        shader.set_uniform_1f("fogBaseHeight", self.base_height);
+        shader.set_uniform_1f("fogEdgeStart", self.edge_start);
+        shader.set_uniform_1f("fogEdgeEnd", self.edge_end);
+        shader.set_uniform_3f("fogColor", self.color.x, self.color.y, self.color.z);
+        shader.set_uniform_3f("fogSunColor", self.sun_color.x, self.sun_color.y, self.sun_color.z);
+        shader.set_uniform_3f("fogExtinction", AERIAL_EXTINCTION.x, AERIAL_EXTINCTION.y, AERIAL_EXTINCTION.z);
+    }
+}
@@ -0,0 +1,75 @@
+use ferrousgl::Shader;
+use glam::{Mat4, Vec3, Vec4};
+
+use crate::camera_controller::CameraController;
+
+use super::fog::Fog;
+use super::lighting::Lighting;
+use super::shadow_cascades::{CascadedShadowMap, ShadowCascade};
+
+/// Camera and light values shared by every pass in a frame. Built once per frame and
+/// uploaded to each shader that needs it, so all passes agree on the same state.
+#[derive(Debug, Clone)]
+pub struct FrameUniforms {
+    pub view: Mat4,
+    pub projection: Mat4,
+    pub view_projection: Mat4,
+    pub camera_position: Vec3,
+    /// Direction the sun or moon light travels, shared by the shadow and terrain passes
+    pub sun_direction: Vec3,
+    pub clear_color: Vec4,
+    pub lighting: Lighting,
+    pub fog: Fog,
+    pub cascades: Vec<ShadowCascade>,
+    pub pcf_radius: u32,
+}
+
+impl FrameUniforms {
+    pub fn new(camera: &CameraController, lighting: Lighting, fog: Fog, shadow_map: &CascadedShadowMap) -> Self {
+        let view = camera.get_view();
+        let projection = camera.get_projection();
+
+        FrameUniforms {
+            view,
+            projection,
+            view_projection: projection * view,
+            camera_position: camera.position,
+            sun_direction: lighting.light_direction,
+            clear_color: lighting.clear_color(),
+            lighting,
+            fog,
+            cascades: shadow_map.cascades.clone(),
+            pcf_radius: shadow_map.settings.pcf_radius,
+        }
+    }
+
+    /// Sets `projection`, `view` and `viewPos`.
+    pub fn apply_camera(&self, shader: &Shader) {
+        shader.set_uniform_matrix_4fv("projection", &self.projection.to_cols_array());
+        shader.set_uniform_matrix_4fv("view", &self.view.to_cols_array());
+        shader.set_uniform_3f("viewPos", self.camera_position.x, self.camera_position.y, self.camera_position.z);
+    }
+
+    /// Sets `lightDir`, the light colours and the per-cascade shadow uniforms.
+    pub fn apply_light(&self, shader: &Shader) {
+        shader.set_uniform_3f("lightDir", self.sun_direction.x, self.sun_direction.y, self.sun_direction.z);
+        self.lighting.apply(shader);
+        shader.set_uniform_1i("cascadeCount", self.cascades.len() as i32);
+        shader.set_uniform_1i("pcfRadius", self.pcf_radius as i32);
+        for (cascade_index, cascade) in self.cascades.iter().enumerate() {
+            shader.set_uniform_matrix_4fv(
+                &format!("lightSpaceMatrices[{}]", cascade_index),
+                cascade.light_space_matrix.to_cols_array().as_ref(),
+            );
+            shader.set_uniform_1f(&format!("cascadeSplits[{}]", cascade_index), cascade.split_far);
+            shader.set_uniform_1f(&format!("cascadeDepthBias[{}]", cascade_index), cascade.depth_bias);
+        }
+    }
+
+    /// Sets the fog uniforms and `sunPosition`, which tints the fog towards the sun.
+    pub fn apply_fog(&self, shader: &Shader) {
+        self.fog.apply(shader);
+        let sun = self.lighting.sun_position;
+        shader.set_uniform_3f("sunPosition", sun.x, sun.y, sun.z);
+    }
+}
@@ -0,0 +1,259 @@
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind};
+use std::path::Path;
+
+use ferrousgl::WindowConfig;
+
+use super::post_processing::PostSettings;
+use super::shadow_cascades::{ShadowSettings, MAX_SHADOW_CASCADES};
+
+/// MSAA sample counts accepted for the scene. 0 disables multisampling.
+pub const SUPPORTED_MSAA_SAMPLES: [u32; 5] = [0, 2, 4, 8, 16];
+
+/// Display and rendering settings loaded from `graphics_config.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GraphicsConfig {
+    pub width: u32,
+    pub height: u32,
+    pub fullscreen: bool,
+    pub vsync: bool,
+    /// Upper bound for frames per second when vsync is off
+    pub frame_rate_limit: u32,
+    /// Applies to the window's framebuffer and to the HDR scene target of the post chain
+    pub msaa_samples: u32,
+    /// Edge length of each shadow cascade's depth map
+    pub shadow_resolution: u32,
+    pub shadow_cascades: usize,
+    /// Radius in chunks loaded around the camera
+    pub view_distance: i32,
+    /// Half width of the PCF kernel in texels, 0 gives hard shadows
+    pub pcf_radius: u32,
+    /// Screen-space ambient occlusion
+    pub ssao: bool,
+    pub bloom: bool,
+    /// ACES tonemapping, off clamps the HDR image instead
+    pub tonemapping: bool,
+    /// Linear brightness scale before tonemapping
+    pub exposure: f32,
+    /// Edge smoothing on the final image, cheaper than MSAA
+    pub fxaa: bool,
+}
+
+impl Default for GraphicsConfig {
+    fn default() -> Self {
+        GraphicsConfig {
+            width: 1080,
+            height: 720,
+            fullscreen: false,
+            vsync: false,
+            frame_rate_limit: 1444,
+            msaa_samples: 4,
+            shadow_resolution: 2048,
+            shadow_cascades: 4,
+            view_distance: 4,
+            pcf_radius: 3,
+            ssao: true,
+            bloom: true,
+            tonemapping: true,
+            exposure: 1.0,
+            fxaa: true,
+        }
+    }
+}
+
+impl GraphicsConfig {
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        let config: Self = serde_json::from_reader(reader)?;
+        Ok(config)
+    }
+
+    /// Loads the config and replaces invalid values with defaults. A missing file is created
+    /// with the defaults and a corrected one is rewritten. A file that doesn't parse is left
+    /// alone so the settings in it aren't lost, the game runs on defaults instead.
+    pub fn load_or_default<P: AsRef<Path>>(path: P) -> Self {
+        let (mut config, mut needs_save) = match Self::load_from_file(&path) {
+            Ok(config) => (config, false),
+            Err(e) => {
+                let missing = e.downcast_ref::<std::io::Error>().is_some_and(|e| e.kind() == ErrorKind::NotFound);
+                println!("Failed to load graphics config {:?}, using defaults: {}", path.as_ref(), e);
+                (Self::default(), missing)
+            }
+        };
+
+        for warning in config.sanitize() {
+            println!("Graphics config: {}", warning);
+            needs_save = true;
+        }
+
+        if needs_save && let Err(e) = config.save_to_file(&path) {
+            println!("Failed to write graphics config {:?}: {}", path.as_ref(), e);
+        }
+
+        config
+    }
+
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
+        let file = File::create(path)?;
+        let writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+
+    /// Resets every out of range value to its default and returns a warning for each one.
+    pub fn sanitize(&mut self) -> Vec<String> {
+        let defaults = Self::default();
+        let mut warnings = Vec::new();
+
+        if self.width < 320 || self.height < 240 {
+            warnings.push(format!(
+                "Resolution {}x{} is below 320x240, using {}x{}",
+                self.width, self.height, defaults.width, defaults.height
+            ));
+            self.width = defaults.width;
+            self.height = defaults.height;
+        }
+        if self.frame_rate_limit == 0 {
+            warnings.push(format!("Frame rate limit must be above 0, using {}", defaults.frame_rate_limit));
+            self.frame_rate_limit = defaults.frame_rate_limit;
+        }
+        if !SUPPORTED_MSAA_SAMPLES.contains(&self.msaa_samples) {
+            warnings.push(format!(
+                "Unsupported MSAA sample count {} (expected one of {:?}), using {}",
+                self.msaa_samples, SUPPORTED_MSAA_SAMPLES, defaults.msaa_samples
+            ));
+            self.msaa_samples = defaults.msaa_samples;
+        }
+        if !self.shadow_resolution.is_power_of_two() || !(256..=8192).contains(&self.shadow_resolution) {
+            warnings.push(format!(
+                "Shadow resolution {} must be a power of two between 256 and 8192, using {}",
+                self.shadow_resolution, defaults.shadow_resolution
+            ));
+            self.shadow_resolution = defaults.shadow_resolution;
+        }
+        if !(1..=MAX_SHADOW_CASCADES).contains(&self.shadow_cascades) {
+            warnings.push(format!(
+                "Shadow cascade count {} must be between 1 and {}, using {}",
+                self.shadow_cascades, MAX_SHADOW_CASCADES, defaults.shadow_cascades
+            ));
+            self.shadow_cascades = defaults.shadow_cascades;
+        }
+        if !(1..=32).contains(&self.view_distance) {
+            warnings.push(format!("View distance {} must be between 1 and 32 chunks, using {}", self.view_distance, defaults.view_distance));
+            self.view_distance = defaults.view_distance;
+        }
+        if self.pcf_radius > 8 {
+            warnings.push(format!("PCF radius {} must be at most 8, using {}", self.pcf_radius, defaults.pcf_radius));
+            self.pcf_radius = defaults.pcf_radius;
+        }
+        if !(self.exposure > 0.0 && self.exposure <= 16.0) {
+            warnings.push(format!("Exposure {} must be above 0 and at most 16, using {}", self.exposure, defaults.exposure));
+            self.exposure = defaults.exposure;
+        }
+
+        warnings
+    }
+
+    /// Changes one setting by its JSON name. The value is rejected if it doesn't parse or
+    /// doesn't pass validation.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let mut changed = self.clone();
+        match name {
+            "width" => changed.width = parse_value(name, value)?,
+            "height" => changed.height = parse_value(name, value)?,
+            "fullscreen" => changed.fullscreen = parse_value(name, value)?,
+            "vsync" => changed.vsync = parse_value(name, value)?,
+            "frame_rate_limit" => changed.frame_rate_limit = parse_value(name, value)?,
+            "msaa_samples" => changed.msaa_samples = parse_value(name, value)?,
+            "shadow_resolution" => changed.shadow_resolution = parse_value(name, value)?,
+            "shadow_cascades" => changed.shadow_cascades = parse_value(name, value)?,
+            "view_distance" => changed.view_distance = parse_value(name, value)?,
+            "pcf_radius" => changed.pcf_radius = parse_value(name, value)?,
+            "ssao" => changed.ssao = parse_value(name, value)?,
+            "bloom" => changed.bloom = parse_value(name, value)?,
+            "tonemapping" => changed.tonemapping = parse_value(name, value)?,
+            "exposure" => changed.exposure = parse_value(name, value)?,
+            "fxaa" => changed.fxaa = parse_value(name, value)?,
+            _ => return Err(format!("Unknown graphics setting '{}'", name)),
+        }
+
+        if let Some(warning) = changed.sanitize().into_iter().next() {
+            return Err(warning);
+        }
+
+        *self = changed;
+        Ok(())
+    }
+
+    /// Settings that only take effect when the window is created.
+    pub fn requires_restart(&self, other: &GraphicsConfig) -> bool {
+        self.msaa_samples != other.msaa_samples || self.frame_rate_limit != other.frame_rate_limit
+    }
+
+    pub fn window_config(&self, title: &str) -> WindowConfig {
+        WindowConfig {
+            title: title.to_string(),
+            width: self.width,
+            height: self.height,
+            target_framerate: self.frame_rate_limit,
+            anti_aliasing: self.msaa_samples,
+            ..Default::default()
+        }
+    }
+
+    pub fn shadow_settings(&self) -> ShadowSettings {
+        ShadowSettings {
+            cascade_count: self.shadow_cascades,
+            resolution: self.shadow_resolution,
+            pcf_radius: self.pcf_radius,
+            ..Default::default()
+        }
+    }
+
+    pub fn post_settings(&self) -> PostSettings {
+        PostSettings {
+            ssao: self.ssao,
+            bloom: self.bloom,
+            tonemapping: self.tonemapping,
+            exposure: self.exposure,
+            fxaa: self.fxaa,
+            msaa_samples: self.msaa_samples,
+            ..Default::default()
+        }
+    }
+
+    /// Applies resolution, fullscreen and vsync to the window whose GL context is current.
+    /// ferrousgl doesn't expose these, so this goes through GLFW directly.
+    pub fn apply_to_window(&self) {
+        unsafe {
+            let window = glfw::ffi::glfwGetCurrentContext();
+            if window.is_null() {
+                println!("No current window to apply graphics settings to");
+                return;
+            }
+
+            let monitor = glfw::ffi::glfwGetWindowMonitor(window);
+            if self.fullscreen {
+                let primary = glfw::ffi::glfwGetPrimaryMonitor();
+                let mode = glfw::ffi::glfwGetVideoMode(primary);
+                let refresh_rate = if mode.is_null() { glfw::ffi::DONT_CARE } else { (*mode).refreshRate };
+                glfw::ffi::glfwSetWindowMonitor(window, primary, 0, 0, self.width as i32, self.height as i32, refresh_rate);
+            } else if !monitor.is_null() {
+                glfw::ffi::glfwSetWindowMonitor(window, std::ptr::null_mut(), 100, 100, self.width as i32, self.height as i32, glfw::ffi::DONT_CARE);
+            } else {
+                glfw::ffi::glfwSetWindowSize(window, self.width as i32, self.height as i32);
+            }
+
+            glfw::ffi::glfwSwapInterval(if self.vsync { 1 } else { 0 });
+        }
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .parse::<T>()
+        .map_err(|_| format!("Invalid value '{}' for {}", value, name))
+}
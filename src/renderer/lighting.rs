@@ -0,0 +1,50 @@
+use ferrousgl::Shader;
+use glam::Vec3;
+
+use super::time_of_day::TimeOfDay;
+
+/// Light used to shade one frame, produced by `TimeOfDay::lighting`. Colours are linear.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lighting {
+    /// Direction the light of the sun, or the moon at night, travels
+    pub light_direction: Vec3,
+    /// Irradiance of that light at the ground
+    pub light_color: Vec3,
+    /// Ambient light arriving from the sky, used on faces pointing up
+    pub sky_ambient: Vec3,
+    /// Ambient light bounced off the ground, used on faces pointing down
+    pub ground_ambient: Vec3,
+    /// Unit vectors towards the sun and moon, for the sky dome
+    pub sun_position: Vec3,
+    pub moon_position: Vec3,
+    /// Average sky radiance just above the horizon
+    pub horizon_color: Vec3,
+    /// Sky radiance just above the horizon below the sun
+    pub horizon_sun_color: Vec3,
+}
+
+impl Default for Lighting {
+    fn default() -> Self {
+        TimeOfDay::default().lighting()
+    }
+}
+
+impl Lighting {
+    /// Sets `lightColor`, `skyAmbient` and `groundAmbient`.
+    pub fn apply(&self, shader: &Shader) {
+        shader.set_uniform_3f("lightColor", self.light_color.x, self.light_color.y, self.light_color.z);
+        shader.set_uniform_3f("skyAmbient", self.sky_ambient.x, self.sky_ambient.y, self.sky_ambient.z);
+        shader.set_uniform_3f("groundAmbient", self.ground_ambient.x, self.ground_ambient.y, self.ground_ambient.z);
+    }
+
+    /// Sets `sunPosition` and `moonPosition` for the sky dome.
+    pub fn apply_sky(&self, shader: &Shader) {
+        shader.set_uniform_3f("sunPosition", self.sun_position.x, self.sun_position.y, self.sun_position.z);
+        shader.set_uniform_3f("moonPosition", self.moon_position.x, self.moon_position.y, self.moon_position.z);
+    }
+
+    /// Linear horizon colour, used to clear the HDR scene target.
+    pub fn clear_color(&self) -> glam::Vec4 {
+        self.horizon_color.extend(1.0)
+    }
+}
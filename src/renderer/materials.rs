@@ -0,0 +1,275 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use ferrousgl::Shader;
+use image::imageops::FilterType;
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+
+use crate::assets::asset_manager::{AssetManager, ImageKind};
+use crate::assets::placeholder;
+use crate::terrain::terrain_generator_config::MaterialStrata;
+
+pub const MATERIAL_LIBRARY_PATH: &str = "./assets/data/terrain_materials.json";
+
+/// Number of materials the terrain shader has uniform slots for.
+pub const MAX_TERRAIN_MATERIALS: usize = 64;
+
+/// Largest texture array layer edge, 4096² RGBA8 layers already take 64 MiB each.
+pub const MAX_LAYER_SIZE: u32 = 4096;
+
+/// One terrain material as described in `terrain_materials.json`. Its position in the
+/// list is the material id stored per voxel.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MaterialDefinition {
+    pub name: String,
+    pub albedo: String,
+    pub normal: Option<String>,
+    pub roughness: Option<String>,
+    /// World units per texture repeat
+    #[serde(default = "default_tiling")]
+    pub tiling: f32,
+    /// Exponent applied to the triplanar weights, higher values give harder transitions
+    #[serde(default = "default_triplanar_sharpness")]
+    pub triplanar_sharpness: f32,
+    /// Material drawn on steep faces instead of this one, e.g. rock under grass
+    #[serde(default)]
+    pub steep_material: Option<String>,
+}
+
+fn default_tiling() -> f32 {
+    8.0
+}
+
+fn default_triplanar_sharpness() -> f32 {
+    4.0
+}
+
+/// All terrain materials, loaded from `terrain_materials.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MaterialLibrary {
+    /// Edge length every texture is resized to so they fit in one texture array
+    pub layer_size: u32,
+    pub materials: Vec<MaterialDefinition>,
+}
+
+impl MaterialLibrary {
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        let library: Self = serde_json::from_reader(reader)?;
+        library.validate()?;
+        Ok(library)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.materials.is_empty() {
+            return Err("Material library contains no materials".to_string());
+        }
+        if self.materials.len() > MAX_TERRAIN_MATERIALS {
+            return Err(format!("Material library has {} materials, at most {} are supported", self.materials.len(), MAX_TERRAIN_MATERIALS));
+        }
+        if !(1..=MAX_LAYER_SIZE).contains(&self.layer_size) {
+            return Err(format!("Material library has layer size {}, it must be between 1 and {}", self.layer_size, MAX_LAYER_SIZE));
+        }
+        for material in &self.materials {
+            if material.tiling <= 0.0 || !material.tiling.is_finite() {
+                return Err(format!("Material '{}' has tiling {}, it must be greater than 0", material.name, material.tiling));
+            }
+            if material.triplanar_sharpness <= 0.0 || !material.triplanar_sharpness.is_finite() {
+                return Err(format!(
+                    "Material '{}' has triplanar sharpness {}, it must be greater than 0",
+                    material.name, material.triplanar_sharpness
+                ));
+            }
+            if let Some(steep) = &material.steep_material
+                && self.id_of(steep).is_none()
+            {
+                return Err(format!("Material '{}' refers to unknown steep material '{}'", material.name, steep));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that every material id the strata assign exists in this library.
+    pub fn validate_strata(&self, strata: &MaterialStrata) -> Result<(), String> {
+        let ids = std::iter::once(strata.surface_material).chain(strata.strata_materials.iter().copied());
+        for id in ids {
+            if id as usize >= self.materials.len() {
+                return Err(format!("Material strata use material id {}, but the library only has {} materials", id, self.materials.len()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Material id of the named material.
+    pub fn id_of(&self, name: &str) -> Option<u8> {
+        self.materials
+            .iter()
+            .position(|material| material.name == name)
+            .map(|index| index as u8)
+    }
+
+    /// Material id drawn on steep faces of each material.
+    pub fn steep_ids(&self) -> Vec<u8> {
+        self.materials
+            .iter()
+            .enumerate()
+            .map(|(id, material)| {
+                material
+                    .steep_material
+                    .as_deref()
+                    .and_then(|name| self.id_of(name))
+                    .unwrap_or(id as u8)
+            })
+            .collect()
+    }
+}
+
+/// `GL_TEXTURE_2D_ARRAY` with one RGBA8 layer per material. Colour data is stored as sRGB
+/// so the sampler returns linear values.
+pub struct TextureArray {
+    id: u32,
+    pub layers: u32,
+    pub size: u32,
+}
+
+impl TextureArray {
+    /// Uploads the layers and builds mipmaps. Every layer must be `size` x `size`.
+    pub fn new(size: u32, layers: &[RgbaImage], srgb: bool) -> Self {
+        let internal_format = if srgb { gl::SRGB8_ALPHA8 } else { gl::RGBA8 };
+        let mut id = 0;
+        unsafe {
+            gl::GenTextures(1, &mut id);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, id);
+            gl::TexImage3D(
+                gl::TEXTURE_2D_ARRAY,
+                0,
+                internal_format as i32,
+                size as i32,
+                size as i32,
+                layers.len() as i32,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                std::ptr::null(),
+            );
+
+            for (layer, image) in layers.iter().enumerate() {
+                gl::TexSubImage3D(
+                    gl::TEXTURE_2D_ARRAY,
+                    0,
+                    0,
+                    0,
+                    layer as i32,
+                    size as i32,
+                    size as i32,
+                    1,
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    image.as_raw().as_ptr() as *const _,
+                );
+            }
+
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_MIN_FILTER, gl::LINEAR_MIPMAP_LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+            gl::GenerateMipmap(gl::TEXTURE_2D_ARRAY);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, 0);
+        }
+
+        TextureArray {
+            id,
+            layers: layers.len() as u32,
+            size,
+        }
+    }
+
+    pub fn bind(&self, unit: u32) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + unit);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, self.id);
+        }
+    }
+}
+
+impl Drop for TextureArray {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.id);
+        }
+    }
+}
+
+/// GPU side of a `MaterialLibrary`: albedo, normal and roughness texture arrays plus
+/// the per-material shader parameters.
+pub struct MaterialTextures {
+    pub library: MaterialLibrary,
+    pub albedo: TextureArray,
+    pub normal: TextureArray,
+    pub roughness: TextureArray,
+}
+
+impl MaterialTextures {
+    /// Builds the arrays from the library's images. Missing or broken images are
+    /// reported by the asset manager and replaced by placeholders.
+    pub fn new(library: MaterialLibrary, assets: &mut AssetManager) -> Self {
+        let size = library.layer_size;
+
+        let mut albedo_layers = Vec::with_capacity(library.materials.len());
+        let mut normal_layers = Vec::with_capacity(library.materials.len());
+        let mut roughness_layers = Vec::with_capacity(library.materials.len());
+        for material in &library.materials {
+            albedo_layers.push(Self::load_layer(assets, &material.albedo, ImageKind::Color, size));
+            normal_layers.push(match &material.normal {
+                Some(path) => Self::load_layer(assets, path, ImageKind::Normal, size),
+                None => placeholder::flat_normal(size),
+            });
+            roughness_layers.push(match &material.roughness {
+                Some(path) => Self::load_layer(assets, path, ImageKind::Data, size),
+                None => placeholder::solid(size, 255), // Fully rough
+            });
+        }
+
+        MaterialTextures {
+            albedo: TextureArray::new(size, &albedo_layers, true),
+            normal: TextureArray::new(size, &normal_layers, false),
+            roughness: TextureArray::new(size, &roughness_layers, false),
+            library,
+        }
+    }
+
+    /// Loads an image through the asset manager and resizes it to the array's layer size.
+    pub fn load_layer(assets: &mut AssetManager, path: &str, kind: ImageKind, size: u32) -> RgbaImage {
+        let handle = assets.load_image(path, kind);
+        Self::resize_layer(assets.image(handle), size)
+    }
+
+    pub fn resize_layer(image: &RgbaImage, size: u32) -> RgbaImage {
+        if image.width() == size && image.height() == size {
+            image.clone()
+        } else {
+            image::imageops::resize(image, size, size, FilterType::Triangle)
+        }
+    }
+
+    /// Binds the three arrays to consecutive units starting at `first_unit` and uploads
+    /// the per-material parameters.
+    pub fn bind(&self, shader: &Shader, first_unit: u32) {
+        self.albedo.bind(first_unit);
+        self.normal.bind(first_unit + 1);
+        self.roughness.bind(first_unit + 2);
+        shader.set_uniform_texture("materialAlbedo", first_unit);
+        shader.set_uniform_texture("materialNormal", first_unit + 1);
+        shader.set_uniform_texture("materialRoughness", first_unit + 2);
+
+        let steep_ids = self.library.steep_ids();
+        for (id, material) in self.library.materials.iter().enumerate() {
+            shader.set_uniform_1f(&format!("materialTiling[{}]", id), material.tiling);
+            shader.set_uniform_1f(&format!("materialSharpness[{}]", id), material.triplanar_sharpness);
+            shader.set_uniform_1i(&format!("materialSteep[{}]", id), steep_ids[id] as i32);
+        }
+    }
+}
@@ -0,0 +1,382 @@
+pub mod debug_lines;
+pub mod fog;
+pub mod frame_uniforms;
+pub mod graphics_config;
+pub mod lighting;
+pub mod materials;
+pub mod offscreen;
+pub mod passes;
+pub mod post_processing;
+pub mod render_target;
+pub mod screenshot;
+pub mod shadow_cascades;
+pub mod time_of_day;
+
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use ferrousgl::{GlWindow, Shader};
+use glam::Mat4;
+use image::RgbaImage;
+
+use crate::assets::asset_manager::AssetManager;
+use crate::camera_controller::CameraController;
+use crate::terrain::terrain_manager::TerrainManager;
+use crate::utils::frustum::Frustum;
+
+use debug_lines::DebugDrawSettings;
+use fog::{Fog, FogSettings};
+use frame_uniforms::FrameUniforms;
+use graphics_config::GraphicsConfig;
+use offscreen::OffscreenTarget;
+use post_processing::{PostSettings, PostTargets};
+use passes::debug_line_pass::DebugLinePass;
+use passes::debug_overlay_pass::DebugOverlayPass;
+use passes::post_pass::PostPass;
+use passes::shadow_pass::ShadowPass;
+use passes::sky_pass::SkyPass;
+use passes::terrain_pass::TerrainPass;
+use shadow_cascades::CascadedShadowMap;
+use time_of_day::TimeOfDay;
+
+/// Counters collected while rendering one frame.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrameStats {
+    pub chunks_drawn: usize,
+    pub color_culled: usize,
+    pub shadow_culled: usize,
+}
+
+/// GPU resources produced by one pass and consumed by later ones.
+pub struct FrameResources {
+    pub shadow_map: CascadedShadowMap,
+    /// Where the frame ends up, the window when None
+    pub frame_target: Option<OffscreenTarget>,
+    /// HDR scene and post chain targets, None until the first frame, while the post pass
+    /// is disabled or if they couldn't be created
+    pub post_targets: Option<PostTargets>,
+    /// Request that last failed, so a broken driver doesn't retry every frame
+    failed_post_targets: Option<((u32, u32), usize, u32)>,
+}
+
+impl FrameResources {
+    /// Size of the final image, the offscreen target or the window.
+    pub fn output_size(&self, window: &GlWindow) -> (u32, u32) {
+        match &self.frame_target {
+            Some(target) => (target.width, target.height),
+            None => window.get_window_size(),
+        }
+    }
+
+    /// Binds the target scene passes draw into: the HDR scene target, or the output if the
+    /// post chain is disabled or unavailable. Returns its size.
+    ///
+    /// Without the HDR target the scene shaders tonemap and gamma-encode themselves, see
+    /// `scene_needs_encoding` and `PostSettings::apply_output`.
+    pub fn bind_scene_target(&self, window: &GlWindow) -> (u32, u32) {
+        match &self.post_targets {
+            Some(targets) => {
+                targets.bind_scene();
+                (targets.scene.width, targets.scene.height)
+            }
+            None => self.bind_output_target(window),
+        }
+    }
+
+    /// True when scene passes draw straight to the output and must encode their colour.
+    pub fn scene_needs_encoding(&self) -> bool {
+        self.post_targets.is_none()
+    }
+
+    /// Binds the frame's final target and sets the viewport to it. Returns its size.
+    pub fn bind_output_target(&self, window: &GlWindow) -> (u32, u32) {
+        match &self.frame_target {
+            Some(target) => {
+                target.bind(window);
+                (target.width, target.height)
+            }
+            None => {
+                unsafe {
+                    gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+                }
+                let window_size = window.get_window_size();
+                window.update_viewport(window_size.0, window_size.1);
+                window_size
+            }
+        }
+    }
+
+    /// (Re)creates the post targets when the output size, bloom chain or MSAA sample count
+    /// changed. A minimised window has no size, the old targets are kept until it comes back.
+    pub fn ensure_post_targets(&mut self, size: (u32, u32), bloom_levels: usize, msaa_samples: u32) -> Result<(), String> {
+        let request = (size, bloom_levels, msaa_samples);
+        if size.0 == 0 || size.1 == 0 || self.failed_post_targets == Some(request) {
+            return Ok(());
+        }
+        if let Some(targets) = &self.post_targets
+            && targets.matches(size.0, size.1, bloom_levels, msaa_samples)
+        {
+            return Ok(());
+        }
+
+        // Free the old targets before allocating new ones
+        self.post_targets = None;
+        match PostTargets::new(size.0, size.1, bloom_levels, msaa_samples) {
+            Ok(targets) => {
+                self.post_targets = Some(targets);
+                self.failed_post_targets = None;
+                Ok(())
+            }
+            Err(e) => {
+                self.failed_post_targets = Some(request);
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Everything a pass can read or write while it executes.
+pub struct FrameContext<'a> {
+    pub window: &'a mut GlWindow,
+    pub world: &'a TerrainManager,
+    pub camera: &'a CameraController,
+    pub uniforms: &'a FrameUniforms,
+    pub assets: &'a AssetManager,
+    pub debug: &'a DebugDrawSettings,
+    pub post: &'a PostSettings,
+    pub resources: &'a mut FrameResources,
+    pub stats: &'a mut FrameStats,
+}
+
+/// One node of the frame graph. Passes run in the order they were added.
+pub trait RenderPass {
+    fn name(&self) -> &str;
+
+    fn execute(&mut self, context: &mut FrameContext);
+
+    /// Called after the asset manager reloaded changed files, so passes can rebuild
+    /// anything derived from them.
+    fn assets_reloaded(&mut self, _assets: &mut AssetManager, _changed: &[PathBuf]) {}
+}
+
+pub struct Renderer {
+    passes: Vec<Box<dyn RenderPass>>,
+    disabled_passes: Vec<String>,
+    resources: FrameResources,
+    pub assets: AssetManager,
+    pub time_of_day: TimeOfDay,
+    pub fog: FogSettings,
+    pub debug_draw: DebugDrawSettings,
+    pub post: PostSettings,
+    pending_screenshot: Option<PathBuf>,
+    /// Kept between offscreen renders so repeated renders don't reallocate
+    offscreen_target: Option<OffscreenTarget>,
+}
+
+impl Renderer {
+    /// Creates the renderer with the default pass chain: shadow, opaque terrain, sky,
+    /// debug lines, post and debug overlay. The debug lines start disabled.
+    pub fn new(config: &GraphicsConfig, assets: AssetManager) -> Result<Self, String> {
+        let mut assets = assets;
+        let resources = FrameResources {
+            shadow_map: CascadedShadowMap::new(config.shadow_settings())?,
+            frame_target: None,
+            post_targets: None,
+            failed_post_targets: None,
+        };
+
+        let passes: Vec<Box<dyn RenderPass>> = vec![
+            Box::new(ShadowPass::new(&mut assets)?),
+            Box::new(TerrainPass::new(&mut assets)?),
+            Box::new(SkyPass::new(&mut assets)?),
+            Box::new(DebugLinePass::new(&mut assets)?),
+            Box::new(PostPass::new(&mut assets)?),
+            Box::new(DebugOverlayPass::new(&mut assets)?),
+        ];
+
+        Ok(Renderer {
+            passes,
+            disabled_passes: vec!["debug_lines".to_string()],
+            resources,
+            assets,
+            time_of_day: TimeOfDay::default(),
+            fog: FogSettings {
+                view_distance: config.view_distance,
+                ..Default::default()
+            },
+            debug_draw: DebugDrawSettings::default(),
+            post: config.post_settings(),
+            pending_screenshot: None,
+            offscreen_target: None,
+        })
+    }
+
+    pub fn add_pass(&mut self, pass: Box<dyn RenderPass>) {
+        self.passes.push(pass);
+    }
+
+    pub fn pass_names(&self) -> Vec<&str> {
+        self.passes.iter().map(|pass| pass.name()).collect()
+    }
+
+    /// Enables or disables a pass by name. Returns false if no pass has that name.
+    pub fn set_pass_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        if !self.passes.iter().any(|pass| pass.name() == name) {
+            return false;
+        }
+
+        self.disabled_passes.retain(|disabled| disabled != name);
+        if !enabled {
+            self.disabled_passes.push(name.to_string());
+        }
+        true
+    }
+
+    pub fn is_pass_enabled(&self, name: &str) -> bool {
+        !self.disabled_passes.iter().any(|disabled| disabled == name)
+    }
+
+    /// Applies the renderer side of a graphics config. Shadow maps are only recreated when
+    /// their resolution or cascade count changed.
+    pub fn apply_graphics_config(&mut self, config: &GraphicsConfig) -> Result<(), String> {
+        self.fog.view_distance = config.view_distance;
+        self.post = config.post_settings();
+
+        let settings = config.shadow_settings();
+        let current = &self.resources.shadow_map.settings;
+        if settings.resolution != current.resolution || settings.cascade_count != current.cascade_count {
+            self.resources.shadow_map = CascadedShadowMap::new(settings)?;
+        } else {
+            self.resources.shadow_map.settings = settings;
+        }
+        Ok(())
+    }
+
+    /// Reloads shaders and textures edited on disk and lets the passes react. Returns the
+    /// changed files.
+    pub fn reload_assets(&mut self) -> Vec<PathBuf> {
+        let changed = self.assets.reload_changed();
+        if !changed.is_empty() {
+            for pass in &mut self.passes {
+                pass.assets_reloaded(&mut self.assets, &changed);
+            }
+        }
+        changed
+    }
+
+    /// Saves the window contents at the end of the next frame, to `path` or a timestamped
+    /// file in the screenshot directory.
+    pub fn request_screenshot(&mut self, path: Option<PathBuf>) {
+        self.pending_screenshot = Some(path.unwrap_or_else(|| screenshot::default_screenshot_path(SystemTime::now())));
+    }
+
+    pub fn resources(&self) -> &FrameResources {
+        &self.resources
+    }
+
+    /// Renders one frame of the world as seen by the camera.
+    pub fn render(&mut self, window: &mut GlWindow, world: &TerrainManager, camera: &CameraController) -> FrameStats {
+        let lighting = self.time_of_day.lighting();
+        self.resources.shadow_map.update(camera, lighting.light_direction);
+        let fog = Fog::new(&self.fog, world.chunk_size, &lighting);
+        let uniforms = FrameUniforms::new(camera, lighting, fog, &self.resources.shadow_map);
+        let mut stats = FrameStats::default();
+
+        // The HDR scene target only exists while the post pass is there to process it,
+        // otherwise the scene passes draw and encode straight into the output
+        if self.is_pass_enabled("post") {
+            let output_size = self.resources.output_size(window);
+            if let Err(e) = self.resources.ensure_post_targets(output_size, self.post.bloom_levels, self.post.msaa_samples) {
+                println!("Failed to create post processing targets, rendering without them: {}", e);
+            }
+        } else {
+            self.resources.post_targets = None;
+        }
+
+        for pass in &mut self.passes {
+            if self.disabled_passes.iter().any(|disabled| disabled == pass.name()) {
+                continue;
+            }
+
+            let mut context = FrameContext {
+                window: &mut *window,
+                world,
+                camera,
+                uniforms: &uniforms,
+                assets: &self.assets,
+                debug: &self.debug_draw,
+                post: &self.post,
+                resources: &mut self.resources,
+                stats: &mut stats,
+            };
+            pass.execute(&mut context);
+        }
+
+        if self.resources.frame_target.is_none()
+            && let Some(path) = self.pending_screenshot.take()
+        {
+            let window_size = window.get_window_size();
+            let image = screenshot::read_window_pixels(window_size.0, window_size.1);
+            match screenshot::save_png(&image, &path) {
+                Ok(()) => println!("Saved screenshot {:?}", path),
+                Err(e) => println!("{}", e),
+            }
+        }
+
+        stats
+    }
+
+    /// Renders one frame into a `width` x `height` texture instead of the window and reads
+    /// it back. The window isn't touched, so this works with a hidden window.
+    pub fn render_offscreen(
+        &mut self,
+        window: &mut GlWindow,
+        world: &TerrainManager,
+        camera: &CameraController,
+        width: u32,
+        height: u32,
+    ) -> Result<RgbaImage, String> {
+        let target = match self.offscreen_target.take() {
+            Some(target) if target.width == width && target.height == height => target,
+            _ => OffscreenTarget::new(width, height)?,
+        };
+
+        self.resources.frame_target = Some(target);
+        self.render(window, world, camera);
+        let target = self.resources.frame_target.take().unwrap();
+        target.unbind();
+
+        let image = target.read_pixels();
+        self.offscreen_target = Some(target);
+        Ok(image)
+    }
+}
+
+/// Draws every non-empty chunk whose bounds intersect `frustum` with `shader`, setting its
+/// `model` uniform. Returns how many chunks were drawn and how many were culled.
+pub fn draw_visible_chunks(window: &GlWindow, world: &TerrainManager, frustum: &Frustum, shader: &Shader) -> (usize, usize) {
+    let mut drawn = 0;
+    let mut culled = 0;
+
+    for chunk in world.chunks.values() {
+        if chunk.is_empty {
+            continue;
+        }
+
+        match world.chunk_bounds(chunk) {
+            Some(bounds) if frustum.intersects_aabb(&bounds) => {}
+            _ => {
+                culled += 1;
+                continue;
+            }
+        }
+
+        let model = Mat4::from_translation(world.chunk_origin(chunk.position));
+        shader.set_uniform_matrix_4fv("model", &model.to_cols_array());
+
+        window.render_mesh(chunk.get_mesh());
+        drawn += 1;
+    }
+
+    (drawn, culled)
+}
@@ -0,0 +1,39 @@
+use ferrousgl::{GlWindow, RenderTexture};
+use image::RgbaImage;
+
+use super::screenshot;
+
+/// Colour and depth target a frame can render into instead of the window, used for
+/// headless renders and golden image tests.
+pub struct OffscreenTarget {
+    render_texture: RenderTexture,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl OffscreenTarget {
+    pub fn new(width: u32, height: u32) -> Result<Self, String> {
+        Ok(OffscreenTarget {
+            render_texture: RenderTexture::new(width, height, true)?,
+            width,
+            height,
+        })
+    }
+
+    pub fn bind(&self, window: &GlWindow) {
+        self.render_texture.bind();
+        window.update_viewport(self.width, self.height);
+    }
+
+    pub fn unbind(&self) {
+        self.render_texture.unbind();
+    }
+
+    /// Reads back the colour attachment.
+    pub fn read_pixels(&self) -> RgbaImage {
+        self.render_texture.bind();
+        let image = screenshot::read_pixels(self.width, self.height);
+        self.render_texture.unbind();
+        image
+    }
+}
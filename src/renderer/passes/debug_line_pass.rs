@@ -0,0 +1,160 @@
+use ferrousgl::{DepthType, Shader};
+use glam::{IVec3, Vec3};
+
+use crate::assets::asset_manager::AssetManager;
+use crate::assets::handle::Handle;
+use crate::renderer::debug_lines::{self, DebugLines, LINE_VERTEX_STRIDE};
+use crate::renderer::{FrameContext, RenderPass};
+
+/// Draws the debug layers enabled in `DebugDrawSettings` as coloured lines. Everything is
+/// collected into one `DebugLines` batch and drawn with a single `GL_LINES` call.
+pub struct DebugLinePass {
+    line_shader: Handle<Shader>,
+    lines: DebugLines,
+    vao: u32,
+    vbo: u32,
+    /// Size of the vertex buffer in floats, it only grows
+    capacity: usize,
+}
+
+impl DebugLinePass {
+    pub fn new(assets: &mut AssetManager) -> Result<Self, String> {
+        let line_shader = assets.load_shader(
+            "./assets/shaders/debug_lines/vertex.glsl",
+            "./assets/shaders/debug_lines/fragment.glsl",
+        )?;
+
+        let (mut vao, mut vbo) = (0, 0);
+        let stride = (LINE_VERTEX_STRIDE * std::mem::size_of::<f32>()) as i32;
+        unsafe {
+            gl::GenVertexArrays(1, &mut vao);
+            gl::GenBuffers(1, &mut vbo);
+            gl::BindVertexArray(vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, stride, std::ptr::null());
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, stride, (3 * std::mem::size_of::<f32>()) as *const _);
+            gl::EnableVertexAttribArray(1);
+            gl::BindVertexArray(0);
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        }
+
+        Ok(DebugLinePass {
+            line_shader,
+            lines: DebugLines::new(),
+            vao,
+            vbo,
+            capacity: 0,
+        })
+    }
+
+    fn collect(&mut self, context: &FrameContext) {
+        let settings = context.debug;
+        let world = context.world;
+        self.lines.clear();
+
+        if settings.chunk_bounds {
+            for chunk in world.chunks.values() {
+                let Some(bounds) = world.chunk_bounds(chunk) else {
+                    continue;
+                };
+                let color = if settings.color_by_lod {
+                    debug_lines::lod_color(chunk.lod)
+                } else {
+                    debug_lines::chunk_state_color(chunk.state)
+                };
+                self.lines.aabb(&bounds, color);
+            }
+        }
+
+        if settings.generation_queue {
+            let queued: Vec<&IVec3> = world.queued_chunks().collect();
+            let size = world.chunk_size as f32;
+            for (index, position) in queued.iter().enumerate() {
+                // Small box in the middle of the chunk, full boxes overlap the bounds layer
+                let center = world.chunk_origin(**position) + Vec3::splat(size * 0.5);
+                let color = debug_lines::heatmap_color(index, queued.len());
+                self.lines.marker(center, size * 0.25, color);
+            }
+        }
+
+        if settings.normals {
+            let camera_chunk = world.world_to_chunk_position(context.camera.position);
+            let radius = settings.normals_radius;
+            for chunk in world.chunks.values() {
+                if (chunk.position - camera_chunk).abs().max_element() > radius {
+                    continue;
+                }
+                let origin = world.chunk_origin(chunk.position);
+                for vertex in chunk.mesh_vertices() {
+                    let position = origin + Vec3::new(vertex[0], vertex[1], vertex[2]);
+                    // Density normals point into the solid, draw them facing out like the shader does
+                    let normal = -Vec3::new(vertex[3], vertex[4], vertex[5]).normalize_or_zero();
+                    self.lines.line(position, position + normal * settings.normal_length, Vec3::new(0.2, 0.6, 1.0));
+                }
+            }
+        }
+
+        if settings.shadow_frusta {
+            for (index, cascade) in context.uniforms.cascades.iter().enumerate() {
+                self.lines.frustum(cascade.light_space_matrix, debug_lines::cascade_color(index));
+            }
+        }
+
+        if settings.raycast_hit
+            && let Some(hit) = settings.raycast_hit_position
+        {
+            self.lines.marker(hit, 0.5, Vec3::new(1.0, 1.0, 1.0));
+        }
+    }
+
+    fn upload(&mut self) {
+        let vertices = &self.lines.vertices;
+        let bytes = (vertices.len() * std::mem::size_of::<f32>()) as isize;
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+            if vertices.len() > self.capacity {
+                gl::BufferData(gl::ARRAY_BUFFER, bytes, vertices.as_ptr() as *const _, gl::DYNAMIC_DRAW);
+                self.capacity = vertices.len();
+            } else {
+                gl::BufferSubData(gl::ARRAY_BUFFER, 0, bytes, vertices.as_ptr() as *const _);
+            }
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        }
+    }
+}
+
+impl RenderPass for DebugLinePass {
+    fn name(&self) -> &str {
+        "debug_lines"
+    }
+
+    fn execute(&mut self, context: &mut FrameContext) {
+        self.collect(context);
+        if self.lines.vertices.is_empty() {
+            return;
+        }
+        self.upload();
+
+        let shader = context.assets.shader(self.line_shader);
+        shader.bind_program();
+        shader.set_uniform_matrix_4fv("viewProjection", &context.uniforms.view_projection.to_cols_array());
+
+        context.window.set_depth_testing(DepthType::LessOrEqual);
+        unsafe {
+            gl::BindVertexArray(self.vao);
+            gl::DrawArrays(gl::LINES, 0, (self.lines.line_count() * 2) as i32);
+            gl::BindVertexArray(0);
+        }
+        shader.unbind_program();
+    }
+}
+
+impl Drop for DebugLinePass {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteVertexArrays(1, &self.vao);
+        }
+    }
+}
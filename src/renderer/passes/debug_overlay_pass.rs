@@ -0,0 +1,69 @@
+use ferrousgl::{DepthType, Mesh, Shader};
+
+use crate::assets::asset_manager::AssetManager;
+use crate::assets::handle::Handle;
+use crate::renderer::{FrameContext, RenderPass};
+
+/// Draws the first shadow cascade's depth texture in the bottom-left corner.
+pub struct DebugOverlayPass {
+    quad_shader: Handle<Shader>,
+    quad_mesh: Mesh,
+    pub cascade: usize,
+}
+
+impl DebugOverlayPass {
+    pub fn new(assets: &mut AssetManager) -> Result<Self, String> {
+        let quad_shader = assets.load_shader(
+            "./assets/shaders/debug_quad/vertex.glsl",
+            "./assets/shaders/debug_quad/fragment.glsl",
+        )?;
+
+        let mut quad_mesh = Mesh::new();
+
+        let quad_vertices = [
+            // positions   // texture coords
+            -1.0, -1.0,   0.0, 0.0,  // bottom-left
+            -0.25, -1.0,   1.0, 0.0,  // bottom-right
+            -0.25, -0.25,   1.0, 1.0,  // top-right
+            -1.0, -0.25,   0.0, 1.0   // top-left
+        ];
+
+        let quad_indices = [0, 1, 3, 1, 2, 3];
+
+        quad_mesh.update_vertices(&quad_vertices);
+        quad_mesh.update_indices(&quad_indices);
+        quad_mesh.add_vertex_attributes(&[
+            (0, 2, gl::FLOAT, false),  // position
+            (1, 2, gl::FLOAT, false)   // texture coord
+        ]);
+
+        Ok(DebugOverlayPass {
+            quad_shader,
+            quad_mesh,
+            cascade: 0,
+        })
+    }
+}
+
+impl RenderPass for DebugOverlayPass {
+    fn name(&self) -> &str {
+        "debug_overlay"
+    }
+
+    fn execute(&mut self, context: &mut FrameContext) {
+        let shadow_map = &context.resources.shadow_map;
+        let cascade = self.cascade.min(shadow_map.cascades.len().saturating_sub(1));
+        let Some(depth_texture) = shadow_map.render_texture(cascade).depth_texture() else {
+            return;
+        };
+
+        let quad_shader = context.assets.shader(self.quad_shader);
+        quad_shader.bind_program();
+        depth_texture.bind(0);
+        quad_shader.set_uniform_texture("screenTexture", 0);
+        context.window.set_depth_testing(DepthType::None);
+        context.window.render_mesh(&self.quad_mesh);
+        depth_texture.unbind();
+        quad_shader.unbind_program();
+    }
+}
@@ -0,0 +1,6 @@
+pub mod debug_line_pass;
+pub mod debug_overlay_pass;
+pub mod post_pass;
+pub mod shadow_pass;
+pub mod sky_pass;
+pub mod terrain_pass;
\ No newline at end of file
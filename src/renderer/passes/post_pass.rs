@@ -0,0 +1,209 @@
+use ferrousgl::{DepthType, Mesh, Shader};
+use glam::Vec3;
+
+use crate::assets::asset_manager::AssetManager;
+use crate::assets::handle::Handle;
+use crate::renderer::post_processing::{self, SSAO_KERNEL_SIZE};
+use crate::renderer::render_target::RenderTarget;
+use crate::renderer::{FrameContext, RenderPass};
+
+/// Turns the HDR scene into the final image: ambient occlusion, bloom, exposure and
+/// tonemapping, then FXAA. Which steps run comes from `PostSettings`, tonemapping to sRGB
+/// always happens because the scene passes write linear colour. Without post targets the
+/// scene shaders do the tonemapping themselves.
+pub struct PostPass {
+    ssao_shader: Handle<Shader>,
+    ssao_blur_shader: Handle<Shader>,
+    bloom_downsample_shader: Handle<Shader>,
+    bloom_upsample_shader: Handle<Shader>,
+    composite_shader: Handle<Shader>,
+    fxaa_shader: Handle<Shader>,
+    quad_mesh: Mesh,
+    ssao_kernel: Vec<Vec3>,
+}
+
+impl PostPass {
+    pub fn new(assets: &mut AssetManager) -> Result<Self, String> {
+        let vertex_path = "./assets/shaders/post/vertex.glsl";
+        let mut load = |fragment: &str| assets.load_shader(vertex_path, &format!("./assets/shaders/post/{}.glsl", fragment));
+
+        let ssao_shader = load("ssao")?;
+        let ssao_blur_shader = load("ssao_blur")?;
+        let bloom_downsample_shader = load("bloom_downsample")?;
+        let bloom_upsample_shader = load("bloom_upsample")?;
+        let composite_shader = load("composite")?;
+        let fxaa_shader = load("fxaa")?;
+
+        let mut quad_mesh = Mesh::new();
+        let quad_vertices = [
+            -1.0, -1.0, // bottom-left
+            1.0, -1.0,  // bottom-right
+            1.0, 1.0,   // top-right
+            -1.0, 1.0,  // top-left
+        ];
+        let quad_indices = [0, 1, 3, 1, 2, 3];
+
+        quad_mesh.update_vertices(&quad_vertices);
+        quad_mesh.update_indices(&quad_indices);
+        quad_mesh.add_vertex_attributes(&[
+            (0, 2, gl::FLOAT, false), // position
+        ]);
+
+        Ok(PostPass {
+            ssao_shader,
+            ssao_blur_shader,
+            bloom_downsample_shader,
+            bloom_upsample_shader,
+            composite_shader,
+            fxaa_shader,
+            quad_mesh,
+            ssao_kernel: post_processing::ssao_kernel(SSAO_KERNEL_SIZE),
+        })
+    }
+
+    fn draw_quad(&self, context: &FrameContext, shader: &Shader) {
+        shader.bind_program();
+        context.window.render_mesh(&self.quad_mesh);
+        shader.unbind_program();
+    }
+
+    fn set_texel_size(shader: &Shader, name: &str, target: &RenderTarget) {
+        shader.set_uniform_2f(name, 1.0 / target.width as f32, 1.0 / target.height as f32);
+    }
+
+    fn ambient_occlusion(&self, context: &FrameContext) {
+        let Some(targets) = &context.resources.post_targets else {
+            return;
+        };
+        let settings = context.post;
+
+        let shader = context.assets.shader(self.ssao_shader);
+        shader.bind_program();
+        targets.ambient_occlusion.bind();
+        targets.scene.bind_depth(0);
+        shader.set_uniform_texture("sceneDepth", 0);
+        shader.set_uniform_matrix_4fv("projection", &context.uniforms.projection.to_cols_array());
+        shader.set_uniform_matrix_4fv("inverseProjection", &context.uniforms.projection.inverse().to_cols_array());
+        shader.set_uniform_1f("radius", settings.ssao_radius);
+        for (index, sample) in self.ssao_kernel.iter().enumerate() {
+            shader.set_uniform_3f(&format!("ssaoKernel[{}]", index), sample.x, sample.y, sample.z);
+        }
+        self.draw_quad(context, shader);
+
+        let shader = context.assets.shader(self.ssao_blur_shader);
+        shader.bind_program();
+        targets.ambient_occlusion_blurred.bind();
+        targets.ambient_occlusion.bind_color(0);
+        shader.set_uniform_texture("ambientOcclusion", 0);
+        Self::set_texel_size(shader, "texelSize", &targets.ambient_occlusion);
+        self.draw_quad(context, shader);
+    }
+
+    /// Downsamples the scene through the mip chain, then walks back up adding each level
+    /// onto the next larger one. The result ends up in the first level.
+    fn bloom(&self, context: &FrameContext) {
+        let Some(targets) = &context.resources.post_targets else {
+            return;
+        };
+        let settings = context.post;
+
+        let shader = context.assets.shader(self.bloom_downsample_shader);
+        shader.bind_program();
+        shader.set_uniform_texture("sourceTexture", 0);
+        shader.set_uniform_1f("threshold", settings.bloom_threshold);
+        let mut source = &targets.scene;
+        for (level, target) in targets.bloom.iter().enumerate() {
+            target.bind();
+            source.bind_color(0);
+            Self::set_texel_size(shader, "sourceTexelSize", source);
+            shader.set_uniform_1i("firstLevel", (level == 0) as i32);
+            self.draw_quad(context, shader);
+            source = target;
+        }
+
+        let shader = context.assets.shader(self.bloom_upsample_shader);
+        shader.bind_program();
+        shader.set_uniform_texture("sourceTexture", 0);
+        unsafe {
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::ONE, gl::ONE);
+        }
+        for level in (1..targets.bloom.len()).rev() {
+            let source = &targets.bloom[level];
+            targets.bloom[level - 1].bind();
+            source.bind_color(0);
+            Self::set_texel_size(shader, "sourceTexelSize", source);
+            self.draw_quad(context, shader);
+        }
+        unsafe {
+            gl::Disable(gl::BLEND);
+        }
+    }
+}
+
+impl RenderPass for PostPass {
+    fn name(&self) -> &str {
+        "post"
+    }
+
+    fn execute(&mut self, context: &mut FrameContext) {
+        let Some(targets) = &context.resources.post_targets else {
+            // Scene went straight to the output and the scene shaders encoded it already
+            context.window.set_depth_testing(DepthType::LessOrEqual);
+            return;
+        };
+        let settings = *context.post;
+        let bloom_available = settings.bloom && !targets.bloom.is_empty();
+
+        context.window.set_depth_testing(DepthType::None);
+
+        // SSAO and bloom sample the scene, which needs the MSAA samples averaged first
+        targets.resolve_scene();
+
+        if settings.ssao {
+            self.ambient_occlusion(context);
+        }
+        if bloom_available {
+            self.bloom(context);
+        }
+
+        let shader = context.assets.shader(self.composite_shader);
+        shader.bind_program();
+        if settings.fxaa {
+            targets.display.bind();
+        } else {
+            context.resources.bind_output_target(context.window);
+        }
+        targets.scene.bind_color(0);
+        shader.set_uniform_texture("sceneColor", 0);
+        targets.ambient_occlusion_blurred.bind_color(1);
+        shader.set_uniform_texture("ambientOcclusion", 1);
+        shader.set_uniform_1i("ssaoEnabled", settings.ssao as i32);
+        shader.set_uniform_1f("ssaoIntensity", settings.ssao_intensity);
+        if let Some(bloom) = targets.bloom.first() {
+            bloom.bind_color(2);
+        }
+        shader.set_uniform_texture("bloomTexture", 2);
+        shader.set_uniform_1i("bloomEnabled", bloom_available as i32);
+        shader.set_uniform_1f("bloomIntensity", settings.bloom_intensity);
+        shader.set_uniform_1i("tonemapping", settings.tonemapping as i32);
+        shader.set_uniform_1f("exposure", settings.exposure);
+        self.draw_quad(context, shader);
+
+        if settings.fxaa {
+            let shader = context.assets.shader(self.fxaa_shader);
+            shader.bind_program();
+            context.resources.bind_output_target(context.window);
+            targets.display.bind_color(0);
+            shader.set_uniform_texture("displayTexture", 0);
+            Self::set_texel_size(shader, "texelSize", &targets.display);
+            self.draw_quad(context, shader);
+        }
+
+        // Restore the state the next frame's passes expect
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+        }
+        context.window.set_depth_testing(DepthType::LessOrEqual);
+    }
+}
@@ -0,0 +1,52 @@
+use ferrousgl::{DepthType, Shader};
+
+use crate::assets::asset_manager::AssetManager;
+use crate::assets::handle::Handle;
+use crate::renderer::{draw_visible_chunks, FrameContext, RenderPass};
+
+/// Renders terrain depth into every shadow cascade from the sun's point of view.
+pub struct ShadowPass {
+    depth_shader: Handle<Shader>,
+}
+
+impl ShadowPass {
+    pub fn new(assets: &mut AssetManager) -> Result<Self, String> {
+        let depth_shader = assets.load_shader(
+            "./assets/shaders/shadows/vertex.glsl",
+            "./assets/shaders/shadows/fragment.glsl",
+        )?;
+
+        Ok(ShadowPass { depth_shader })
+    }
+}
+
+impl RenderPass for ShadowPass {
+    fn name(&self) -> &str {
+        "shadow"
+    }
+
+    fn execute(&mut self, context: &mut FrameContext) {
+        let depth_shader = context.assets.shader(self.depth_shader);
+        depth_shader.bind_program();
+        context.window.set_depth_testing(DepthType::LessOrEqual);
+
+        let shadow_map = &context.resources.shadow_map;
+        for (cascade_index, cascade) in context.uniforms.cascades.iter().enumerate() {
+            let render_texture = shadow_map.render_texture(cascade_index);
+
+            render_texture.bind();
+            depth_shader.set_uniform_matrix_4fv("lightSpaceMatrix", cascade.light_space_matrix.to_cols_array().as_ref());
+
+            context.window.clear_color(context.uniforms.clear_color);
+            context.window.clear_depth();
+
+            // Skip chunks the light can't see in this cascade
+            let (_, culled) = draw_visible_chunks(context.window, context.world, &cascade.frustum(), depth_shader);
+            context.stats.shadow_culled += culled;
+
+            render_texture.unbind();
+        }
+
+        depth_shader.unbind_program();
+    }
+}
@@ -0,0 +1,60 @@
+use ferrousgl::{DepthType, Mesh, Shader};
+
+use crate::assets::asset_manager::AssetManager;
+use crate::assets::handle::Handle;
+use crate::renderer::{FrameContext, RenderPass};
+
+/// Fills every pixel the terrain didn't cover with the scattered sky. The quad sits on the
+/// far plane, so with `LessOrEqual` depth testing it only lands on cleared depth.
+pub struct SkyPass {
+    sky_shader: Handle<Shader>,
+    quad_mesh: Mesh,
+}
+
+impl SkyPass {
+    pub fn new(assets: &mut AssetManager) -> Result<Self, String> {
+        let sky_shader = assets.load_shader(
+            "./assets/shaders/sky/vertex.glsl",
+            "./assets/shaders/sky/fragment.glsl",
+        )?;
+
+        let mut quad_mesh = Mesh::new();
+        let quad_vertices = [
+            -1.0, -1.0, // bottom-left
+            1.0, -1.0,  // bottom-right
+            1.0, 1.0,   // top-right
+            -1.0, 1.0,  // top-left
+        ];
+        let quad_indices = [0, 1, 3, 1, 2, 3];
+
+        quad_mesh.update_vertices(&quad_vertices);
+        quad_mesh.update_indices(&quad_indices);
+        quad_mesh.add_vertex_attributes(&[
+            (0, 2, gl::FLOAT, false), // position
+        ]);
+
+        Ok(SkyPass { sky_shader, quad_mesh })
+    }
+}
+
+impl RenderPass for SkyPass {
+    fn name(&self) -> &str {
+        "sky"
+    }
+
+    fn execute(&mut self, context: &mut FrameContext) {
+        let shader = context.assets.shader(self.sky_shader);
+        shader.bind_program();
+
+        let inverse_view_projection = context.uniforms.view_projection.inverse();
+        shader.set_uniform_matrix_4fv("inverseViewProjection", &inverse_view_projection.to_cols_array());
+        let camera_position = context.uniforms.camera_position;
+        shader.set_uniform_3f("viewPos", camera_position.x, camera_position.y, camera_position.z);
+        context.uniforms.lighting.apply_sky(shader);
+        context.post.apply_output(shader, context.resources.scene_needs_encoding());
+
+        context.window.set_depth_testing(DepthType::LessOrEqual);
+        context.window.render_mesh(&self.quad_mesh);
+        shader.unbind_program();
+    }
+}
@@ -0,0 +1,101 @@
+use std::path::{Path, PathBuf};
+
+use ferrousgl::Shader;
+
+use crate::assets::asset_manager::AssetManager;
+use crate::assets::handle::Handle;
+use crate::renderer::materials::{MaterialLibrary, MaterialTextures, MATERIAL_LIBRARY_PATH};
+use crate::renderer::shadow_cascades::MAX_SHADOW_CASCADES;
+use crate::renderer::{draw_visible_chunks, FrameContext, RenderPass};
+
+/// First texture unit used by the material texture arrays.
+const MATERIAL_TEXTURE_UNIT: u32 = 0;
+/// First texture unit used by the shadow cascades, after the material textures.
+const SHADOW_TEXTURE_UNIT: u32 = 4;
+
+/// Shades the opaque terrain into the window's framebuffer.
+pub struct TerrainPass {
+    pub terrain_shader: Handle<Shader>,
+    pub materials: MaterialTextures,
+}
+
+impl TerrainPass {
+    pub fn new(assets: &mut AssetManager) -> Result<Self, String> {
+        let terrain_shader = assets.load_shader(
+            "./assets/shaders/terrain/vertex.glsl",
+            "./assets/shaders/terrain/fragment.glsl",
+        )?;
+
+        let library = MaterialLibrary::load_from_file(MATERIAL_LIBRARY_PATH)
+            .map_err(|e| format!("Failed to load terrain materials: {}", e))?;
+        let materials = MaterialTextures::new(library, assets);
+        assets.watch_file(MATERIAL_LIBRARY_PATH);
+
+        Ok(TerrainPass { terrain_shader, materials })
+    }
+
+    fn uses_file(&self, path: &Path) -> bool {
+        path == Path::new(MATERIAL_LIBRARY_PATH)
+            || self.materials.library.materials.iter().any(|material| {
+                [Some(&material.albedo), material.normal.as_ref(), material.roughness.as_ref()]
+                    .into_iter()
+                    .flatten()
+                    .any(|texture| Path::new(texture) == path)
+            })
+    }
+}
+
+impl RenderPass for TerrainPass {
+    fn name(&self) -> &str {
+        "terrain"
+    }
+
+    /// Rebuilds the material arrays when the library or one of its textures changed. A
+    /// library that no longer parses is reported and the current materials are kept.
+    fn assets_reloaded(&mut self, assets: &mut AssetManager, changed: &[PathBuf]) {
+        if !changed.iter().any(|path| self.uses_file(path)) {
+            return;
+        }
+
+        match MaterialLibrary::load_from_file(MATERIAL_LIBRARY_PATH) {
+            Ok(library) => {
+                self.materials = MaterialTextures::new(library, assets);
+                println!("Rebuilt terrain material textures");
+            }
+            Err(e) => println!("Failed to reload terrain materials, keeping the previous ones: {}", e),
+        }
+    }
+
+    fn execute(&mut self, context: &mut FrameContext) {
+        context.resources.bind_scene_target(context.window);
+
+        context.window.clear_color(context.uniforms.clear_color);
+        context.window.clear_depth();
+
+        let shader = context.assets.shader(self.terrain_shader);
+        shader.bind_program();
+        context.uniforms.apply_camera(shader);
+        context.uniforms.apply_light(shader);
+        context.uniforms.apply_fog(shader);
+        context.post.apply_output(shader, context.resources.scene_needs_encoding());
+        shader.set_uniform_1f("chunkSize", context.world.chunk_size as f32);
+
+        self.materials.bind(shader, MATERIAL_TEXTURE_UNIT);
+
+        // Unused cascade samplers still need a valid texture, reuse the first cascade
+        let shadow_map = &context.resources.shadow_map;
+        for cascade_index in 0..MAX_SHADOW_CASCADES {
+            let texture_index = if cascade_index < shadow_map.cascades.len() { cascade_index } else { 0 };
+            let unit = SHADOW_TEXTURE_UNIT + cascade_index as u32;
+            shadow_map.render_texture(texture_index).depth_texture().unwrap().bind(unit);
+            shader.set_uniform_texture(&format!("shadowMap{}", cascade_index), unit);
+        }
+
+        // Skip chunks outside the camera frustum
+        let (drawn, culled) = draw_visible_chunks(context.window, context.world, &context.camera.get_frustum(), shader);
+        context.stats.chunks_drawn += drawn;
+        context.stats.color_culled += culled;
+
+        shader.unbind_program();
+    }
+}
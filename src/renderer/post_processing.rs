@@ -0,0 +1,168 @@
+use ferrousgl::Shader;
+use glam::Vec3;
+
+use super::render_target::{ColorFormat, MultisampleTarget, RenderTarget};
+
+/// Samples in the SSAO kernel, `ssao.glsl` declares the same count.
+pub const SSAO_KERNEL_SIZE: usize = 16;
+
+/// Settings of the post chain run by the post pass.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PostSettings {
+    pub ssao: bool,
+    /// World units around a point searched for occluders
+    pub ssao_radius: f32,
+    /// Exponent applied to the occlusion, above 1 darkens creases more
+    pub ssao_intensity: f32,
+    pub bloom: bool,
+    /// Brightness where bloom starts, in linear scene units
+    pub bloom_threshold: f32,
+    /// Fraction of the bloom blended into the image
+    pub bloom_intensity: f32,
+    /// Number of half resolution steps the bloom blurs over
+    pub bloom_levels: usize,
+    pub tonemapping: bool,
+    /// Linear scale applied before tonemapping
+    pub exposure: f32,
+    pub fxaa: bool,
+    /// MSAA samples of the scene target, 0 draws the scene single sampled
+    pub msaa_samples: u32,
+}
+
+impl Default for PostSettings {
+    fn default() -> Self {
+        PostSettings {
+            ssao: true,
+            ssao_radius: 1.5,
+            ssao_intensity: 1.5,
+            bloom: true,
+            bloom_threshold: 1.0,
+            bloom_intensity: 0.04,
+            bloom_levels: 5,
+            tonemapping: true,
+            exposure: 1.0,
+            fxaa: true,
+            msaa_samples: 0,
+        }
+    }
+}
+
+impl PostSettings {
+    /// Sets the uniforms scene shaders use to tonemap and gamma-encode their own output.
+    /// `encode` is true when they draw straight to the output because the post chain
+    /// isn't running.
+    pub fn apply_output(&self, shader: &Shader, encode: bool) {
+        shader.set_uniform_1i("encodeOutput", encode as i32);
+        shader.set_uniform_1i("tonemapping", self.tonemapping as i32);
+        shader.set_uniform_1f("exposure", self.exposure);
+    }
+}
+
+/// Sample offsets in the +Z hemisphere for SSAO, in a unit sphere. Samples are packed
+/// towards the centre so nearby geometry counts more. The sequence is fixed, so golden
+/// images don't change between runs.
+pub fn ssao_kernel(count: usize) -> Vec<Vec3> {
+    // Small xorshift generator, the exact distribution doesn't matter
+    let mut state = 0x9e37_79b9u32;
+    let mut random = || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state as f32 / u32::MAX as f32
+    };
+
+    (0..count)
+        .map(|i| {
+            let direction = loop {
+                let candidate = Vec3::new(random() * 2.0 - 1.0, random() * 2.0 - 1.0, random());
+                let length = candidate.length();
+                if length > 0.05 && length <= 1.0 {
+                    break candidate / length;
+                }
+            };
+
+            let t = i as f32 / count as f32;
+            let scale = 0.1 + 0.9 * t * t;
+            direction * random().max(0.1) * scale
+        })
+        .collect()
+}
+
+/// Sizes of the bloom mip chain, each level half the previous one starting at half the
+/// screen. Stops early once a level would be smaller than 2 pixels.
+pub fn bloom_mip_sizes(width: u32, height: u32, levels: usize) -> Vec<(u32, u32)> {
+    let mut sizes = Vec::new();
+    let (mut level_width, mut level_height) = (width / 2, height / 2);
+    while sizes.len() < levels && level_width >= 2 && level_height >= 2 {
+        sizes.push((level_width, level_height));
+        level_width /= 2;
+        level_height /= 2;
+    }
+    sizes
+}
+
+/// Intermediate targets of the post chain, all sized for one output resolution.
+pub struct PostTargets {
+    pub width: u32,
+    pub height: u32,
+    /// HDR colour and depth the scene passes draw into, or the resolved scene with MSAA
+    pub scene: RenderTarget,
+    /// Multisampled scene the scene passes draw into when MSAA is on
+    pub scene_multisampled: Option<MultisampleTarget>,
+    /// Raw and blurred ambient occlusion at half resolution
+    pub ambient_occlusion: RenderTarget,
+    pub ambient_occlusion_blurred: RenderTarget,
+    pub bloom: Vec<RenderTarget>,
+    /// Tonemapped image waiting for FXAA
+    pub display: RenderTarget,
+}
+
+impl PostTargets {
+    pub fn new(width: u32, height: u32, bloom_levels: usize, msaa_samples: u32) -> Result<Self, String> {
+        let (half_width, half_height) = ((width / 2).max(1), (height / 2).max(1));
+        let bloom = bloom_mip_sizes(width, height, bloom_levels)
+            .into_iter()
+            .map(|(level_width, level_height)| RenderTarget::new(level_width, level_height, ColorFormat::Rgba16F, false))
+            .collect::<Result<Vec<_>, _>>()?;
+        let scene_multisampled = if msaa_samples > 1 {
+            Some(MultisampleTarget::new(width, height, ColorFormat::Rgba16F, msaa_samples)?)
+        } else {
+            None
+        };
+
+        Ok(PostTargets {
+            width,
+            height,
+            scene: RenderTarget::new(width, height, ColorFormat::Rgba16F, true)?,
+            scene_multisampled,
+            ambient_occlusion: RenderTarget::new(half_width, half_height, ColorFormat::R8, false)?,
+            ambient_occlusion_blurred: RenderTarget::new(half_width, half_height, ColorFormat::R8, false)?,
+            bloom,
+            display: RenderTarget::new(width, height, ColorFormat::Rgba8, false)?,
+        })
+    }
+
+    /// True if the targets were made for this output size, bloom chain and MSAA sample count.
+    pub fn matches(&self, width: u32, height: u32, bloom_levels: usize, msaa_samples: u32) -> bool {
+        let samples = self.scene_multisampled.as_ref().map_or(0, |target| target.samples);
+        self.width == width
+            && self.height == height
+            && self.bloom.len() == bloom_mip_sizes(width, height, bloom_levels).len()
+            && samples == if msaa_samples > 1 { msaa_samples } else { 0 }
+    }
+
+    /// Binds the target the scene passes draw into, the multisampled one with MSAA.
+    pub fn bind_scene(&self) {
+        match &self.scene_multisampled {
+            Some(multisampled) => multisampled.bind(),
+            None => self.scene.bind(),
+        }
+    }
+
+    /// Resolves the multisampled scene into `scene`, does nothing without MSAA.
+    pub fn resolve_scene(&self) {
+        if let Some(multisampled) = &self.scene_multisampled {
+            multisampled.resolve(&self.scene);
+        }
+    }
+}
@@ -0,0 +1,252 @@
+/// Pixel format of a render target's colour texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorFormat {
+    /// Half float HDR colour
+    Rgba16F,
+    /// Display colour
+    Rgba8,
+    /// Single channel, used for ambient occlusion
+    R8,
+}
+
+impl ColorFormat {
+    fn gl_formats(self) -> (u32, u32, u32) {
+        match self {
+            ColorFormat::Rgba16F => (gl::RGBA16F, gl::RGBA, gl::HALF_FLOAT),
+            ColorFormat::Rgba8 => (gl::RGBA8, gl::RGBA, gl::UNSIGNED_BYTE),
+            ColorFormat::R8 => (gl::R8, gl::RED, gl::UNSIGNED_BYTE),
+        }
+    }
+}
+
+/// Framebuffer with one colour texture and an optional depth texture. ferrousgl's
+/// `RenderTexture` only does 8 bit colour, the post chain needs float targets.
+pub struct RenderTarget {
+    framebuffer: u32,
+    color: u32,
+    depth: Option<u32>,
+    pub width: u32,
+    pub height: u32,
+    pub format: ColorFormat,
+}
+
+impl RenderTarget {
+    pub fn new(width: u32, height: u32, format: ColorFormat, with_depth: bool) -> Result<Self, String> {
+        let (internal_format, pixel_format, pixel_type) = format.gl_formats();
+        let (mut framebuffer, mut color) = (0, 0);
+        let mut depth = None;
+
+        unsafe {
+            gl::GenFramebuffers(1, &mut framebuffer);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer);
+
+            gl::GenTextures(1, &mut color);
+            gl::BindTexture(gl::TEXTURE_2D, color);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                internal_format as i32,
+                width as i32,
+                height as i32,
+                0,
+                pixel_format,
+                pixel_type,
+                std::ptr::null(),
+            );
+            Self::set_sampling(gl::LINEAR);
+            gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_2D, color, 0);
+
+            if with_depth {
+                let mut depth_texture = 0;
+                gl::GenTextures(1, &mut depth_texture);
+                gl::BindTexture(gl::TEXTURE_2D, depth_texture);
+                gl::TexImage2D(
+                    gl::TEXTURE_2D,
+                    0,
+                    gl::DEPTH_COMPONENT24 as i32,
+                    width as i32,
+                    height as i32,
+                    0,
+                    gl::DEPTH_COMPONENT,
+                    gl::UNSIGNED_INT,
+                    std::ptr::null(),
+                );
+                Self::set_sampling(gl::NEAREST);
+                gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, gl::TEXTURE_2D, depth_texture, 0);
+                depth = Some(depth_texture);
+            }
+
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+
+        // Build the struct first so Drop cleans up if the framebuffer is incomplete
+        let target = RenderTarget {
+            framebuffer,
+            color,
+            depth,
+            width,
+            height,
+            format,
+        };
+
+        let status = unsafe { gl::CheckFramebufferStatus(gl::FRAMEBUFFER) };
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+        if status != gl::FRAMEBUFFER_COMPLETE {
+            return Err(format!(
+                "Incomplete {:?} render target {}x{} (status 0x{:x})",
+                format, width, height, status
+            ));
+        }
+
+        Ok(target)
+    }
+
+    unsafe fn set_sampling(filter: u32) {
+        unsafe {
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, filter as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, filter as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+        }
+    }
+
+    /// Binds the framebuffer for drawing and sets the viewport to cover it.
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer);
+            gl::Viewport(0, 0, self.width as i32, self.height as i32);
+        }
+    }
+
+    pub fn bind_color(&self, unit: u32) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + unit);
+            gl::BindTexture(gl::TEXTURE_2D, self.color);
+        }
+    }
+
+    /// Binds the depth texture, does nothing for targets without one.
+    pub fn bind_depth(&self, unit: u32) {
+        if let Some(depth) = self.depth {
+            unsafe {
+                gl::ActiveTexture(gl::TEXTURE0 + unit);
+                gl::BindTexture(gl::TEXTURE_2D, depth);
+            }
+        }
+    }
+}
+
+impl Drop for RenderTarget {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.framebuffer);
+            gl::DeleteTextures(1, &self.color);
+            if let Some(depth) = self.depth {
+                gl::DeleteTextures(1, &depth);
+            }
+        }
+    }
+}
+
+/// Multisampled colour and depth renderbuffers for drawing the scene with MSAA. They can't
+/// be sampled, `resolve` copies them into a regular target of the same size.
+pub struct MultisampleTarget {
+    framebuffer: u32,
+    color: u32,
+    depth: u32,
+    pub width: u32,
+    pub height: u32,
+    pub format: ColorFormat,
+    pub samples: u32,
+}
+
+impl MultisampleTarget {
+    pub fn new(width: u32, height: u32, format: ColorFormat, samples: u32) -> Result<Self, String> {
+        let (internal_format, _, _) = format.gl_formats();
+        let (mut framebuffer, mut color, mut depth) = (0, 0, 0);
+
+        unsafe {
+            gl::GenFramebuffers(1, &mut framebuffer);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer);
+
+            gl::GenRenderbuffers(1, &mut color);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, color);
+            gl::RenderbufferStorageMultisample(gl::RENDERBUFFER, samples as i32, internal_format, width as i32, height as i32);
+            gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::RENDERBUFFER, color);
+
+            // Same depth format as `RenderTarget`, blitting depth needs matching formats
+            gl::GenRenderbuffers(1, &mut depth);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, depth);
+            gl::RenderbufferStorageMultisample(gl::RENDERBUFFER, samples as i32, gl::DEPTH_COMPONENT24, width as i32, height as i32);
+            gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, gl::RENDERBUFFER, depth);
+
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+        }
+
+        // Build the struct first so Drop cleans up if the framebuffer is incomplete
+        let target = MultisampleTarget {
+            framebuffer,
+            color,
+            depth,
+            width,
+            height,
+            format,
+            samples,
+        };
+
+        let status = unsafe { gl::CheckFramebufferStatus(gl::FRAMEBUFFER) };
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+        if status != gl::FRAMEBUFFER_COMPLETE {
+            return Err(format!(
+                "Incomplete {}x MSAA {:?} render target {}x{} (status 0x{:x})",
+                samples, format, width, height, status
+            ));
+        }
+
+        Ok(target)
+    }
+
+    /// Binds the framebuffer for drawing and sets the viewport to cover it.
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer);
+            gl::Viewport(0, 0, self.width as i32, self.height as i32);
+        }
+    }
+
+    /// Averages the samples into `target`'s colour and copies depth, so later passes can
+    /// sample both. `target` must have the same size and a depth texture.
+    pub fn resolve(&self, target: &RenderTarget) {
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.framebuffer);
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, target.framebuffer);
+            gl::BlitFramebuffer(
+                0,
+                0,
+                self.width as i32,
+                self.height as i32,
+                0,
+                0,
+                target.width as i32,
+                target.height as i32,
+                gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT,
+                gl::NEAREST,
+            );
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+    }
+}
+
+impl Drop for MultisampleTarget {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.framebuffer);
+            gl::DeleteRenderbuffers(1, &self.color);
+            gl::DeleteRenderbuffers(1, &self.depth);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use image::RgbaImage;
+
+/// Where screenshots go when no path is given.
+pub const SCREENSHOT_DIRECTORY: &str = "./screenshots";
+
+/// Reads the colour of the currently bound framebuffer. OpenGL rows start at the bottom,
+/// the image is flipped so it starts at the top like every image file.
+pub fn read_pixels(width: u32, height: u32) -> RgbaImage {
+    let mut pixels = vec![0u8; width as usize * height as usize * 4];
+    unsafe {
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+        gl::ReadPixels(
+            0,
+            0,
+            width as i32,
+            height as i32,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            pixels.as_mut_ptr() as *mut _,
+        );
+    }
+
+    let image = RgbaImage::from_raw(width, height, pixels).expect("pixel buffer matches the image size");
+    image::imageops::flip_vertical(&image)
+}
+
+/// Reads the window's back buffer, call after rendering and before swapping.
+pub fn read_window_pixels(width: u32, height: u32) -> RgbaImage {
+    unsafe {
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, 0);
+        gl::ReadBuffer(gl::BACK);
+    }
+    read_pixels(width, height)
+}
+
+/// Writes a PNG, creating missing directories.
+pub fn save_png(image: &RgbaImage, path: &Path) -> Result<(), String> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+    }
+    image.save(path).map_err(|e| format!("Failed to save {:?}: {}", path, e))
+}
+
+/// `screenshots/screenshot_<unix milliseconds>.png`, unique enough for a key press.
+pub fn default_screenshot_path(time: SystemTime) -> PathBuf {
+    let millis = time.duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or(0);
+    Path::new(SCREENSHOT_DIRECTORY).join(format!("screenshot_{}.png", millis))
+}
+
+/// How far a rendered image is from a reference.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageDifference {
+    /// Largest difference of any channel of any pixel
+    pub max_channel_difference: u8,
+    /// Pixels with a channel further off than the tolerance
+    pub differing_pixels: usize,
+    pub total_pixels: usize,
+}
+
+impl ImageDifference {
+    pub fn differing_fraction(&self) -> f32 {
+        if self.total_pixels == 0 {
+            return 0.0;
+        }
+        self.differing_pixels as f32 / self.total_pixels as f32
+    }
+
+    /// True if at most `max_differing_fraction` of the pixels are off.
+    pub fn within(&self, max_differing_fraction: f32) -> bool {
+        self.differing_fraction() <= max_differing_fraction
+    }
+}
+
+/// Compares two images pixel by pixel. Channels may differ by up to `channel_tolerance`
+/// before a pixel counts as different, software and hardware rasterizers round a little
+/// differently. Images of different sizes can't be compared.
+pub fn compare_images(image: &RgbaImage, reference: &RgbaImage, channel_tolerance: u8) -> Result<ImageDifference, String> {
+    if image.dimensions() != reference.dimensions() {
+        return Err(format!(
+            "Image is {:?} but the reference is {:?}",
+            image.dimensions(),
+            reference.dimensions()
+        ));
+    }
+
+    let mut difference = ImageDifference {
+        max_channel_difference: 0,
+        differing_pixels: 0,
+        total_pixels: (image.width() * image.height()) as usize,
+    };
+
+    for (pixel, reference_pixel) in image.pixels().zip(reference.pixels()) {
+        let pixel_difference = pixel
+            .0
+            .iter()
+            .zip(reference_pixel.0.iter())
+            .map(|(a, b)| a.abs_diff(*b))
+            .max()
+            .unwrap_or(0);
+
+        difference.max_channel_difference = difference.max_channel_difference.max(pixel_difference);
+        if pixel_difference > channel_tolerance {
+            difference.differing_pixels += 1;
+        }
+    }
+
+    Ok(difference)
+}
@@ -0,0 +1,178 @@
+use ferrousgl::{MipmapType, RenderTexture};
+use glam::{Mat4, Vec3, Vec4};
+
+use crate::camera_controller::CameraController;
+use crate::utils::frustum::Frustum;
+
+/// Highest cascade count the terrain shader has samplers for.
+pub const MAX_SHADOW_CASCADES: usize = 4;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShadowSettings {
+    pub cascade_count: usize,
+    pub resolution: u32,
+    /// Distance from the camera covered by the last cascade
+    pub max_distance: f32,
+    /// Blend between uniform (0.0) and logarithmic (1.0) split placement
+    pub split_lambda: f32,
+    /// Extra depth behind each cascade so off-screen casters still throw shadows
+    pub caster_margin: f32,
+    /// Half width of the PCF kernel in texels
+    pub pcf_radius: u32,
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self {
+        ShadowSettings {
+            cascade_count: 4,
+            resolution: 2048,
+            max_distance: 400.0,
+            split_lambda: 0.75,
+            caster_margin: 128.0,
+            pcf_radius: 3,
+        }
+    }
+}
+
+/// One slice of the camera frustum and the light projection fitted to it.
+#[derive(Debug, Clone, Copy)]
+pub struct ShadowCascade {
+    pub split_near: f32,
+    pub split_far: f32,
+    pub light_space_matrix: Mat4,
+    /// World units covered by one shadow map texel
+    pub texel_size: f32,
+    /// One and a half texels of world depth expressed in shadow map depth units
+    pub depth_bias: f32,
+}
+
+impl ShadowCascade {
+    pub fn frustum(&self) -> Frustum {
+        Frustum::from_matrix(self.light_space_matrix)
+    }
+}
+
+/// Shadow maps for a directional light, one per slice of the camera frustum.
+pub struct CascadedShadowMap {
+    pub settings: ShadowSettings,
+    pub cascades: Vec<ShadowCascade>,
+    render_textures: Vec<RenderTexture>,
+}
+
+impl CascadedShadowMap {
+    pub fn new(settings: ShadowSettings) -> Result<Self, String> {
+        let mut settings = settings;
+        settings.cascade_count = settings.cascade_count.clamp(1, MAX_SHADOW_CASCADES);
+
+        let mut render_textures = Vec::with_capacity(settings.cascade_count);
+        for _ in 0..settings.cascade_count {
+            let mut render_texture = RenderTexture::new(settings.resolution, settings.resolution, true)
+                .map_err(|e| format!("Failed to create shadow map: {:?}", e))?;
+            render_texture.texture().bind(0);
+            render_texture.set_mipmap_type(MipmapType::Nearest);
+            render_textures.push(render_texture);
+        }
+
+        Ok(CascadedShadowMap {
+            settings,
+            cascades: Vec::new(),
+            render_textures,
+        })
+    }
+
+    pub fn render_texture(&self, cascade: usize) -> &RenderTexture {
+        &self.render_textures[cascade]
+    }
+
+    /// Refits every cascade to the camera for this frame.
+    pub fn update(&mut self, camera: &CameraController, sun_direction: Vec3) {
+        self.cascades = Self::fit_cascades(camera, sun_direction, &self.settings);
+    }
+
+    /// Far distance of each split, placed with the "practical split scheme" that blends
+    /// logarithmic and uniform distributions.
+    pub fn compute_splits(near: f32, far: f32, count: usize, lambda: f32) -> Vec<f32> {
+        (1..=count)
+            .map(|i| {
+                let p = i as f32 / count as f32;
+                let logarithmic = near * (far / near).powf(p);
+                let uniform = near + (far - near) * p;
+                lambda * logarithmic + (1.0 - lambda) * uniform
+            })
+            .collect()
+    }
+
+    pub fn fit_cascades(camera: &CameraController, sun_direction: Vec3, settings: &ShadowSettings) -> Vec<ShadowCascade> {
+        let far = settings.max_distance.min(camera.far);
+        let splits = Self::compute_splits(camera.near, far, settings.cascade_count, settings.split_lambda);
+
+        let mut split_near = camera.near;
+        splits
+            .into_iter()
+            .map(|split_far| {
+                let cascade = Self::fit_cascade(camera, split_near, split_far, sun_direction, settings);
+                split_near = split_far;
+                cascade
+            })
+            .collect()
+    }
+
+    /// Builds an orthographic light projection around the bounding sphere of one frustum
+    /// slice. Using a sphere keeps the projection size constant while the camera rotates,
+    /// and snapping the origin to whole texels stops shadow edges from shimmering while
+    /// it moves.
+    pub fn fit_cascade(
+        camera: &CameraController,
+        split_near: f32,
+        split_far: f32,
+        sun_direction: Vec3,
+        settings: &ShadowSettings,
+    ) -> ShadowCascade {
+        let corners = Self::frustum_slice_corners(camera, split_near, split_far);
+
+        let center = corners.iter().copied().sum::<Vec3>() / corners.len() as f32;
+        let radius = corners
+            .iter()
+            .map(|corner| corner.distance(center))
+            .fold(0.0f32, f32::max);
+        // Round the radius so floating point noise can't change the texel size
+        let radius = (radius * 16.0).ceil() / 16.0;
+
+        let direction = sun_direction.normalize();
+        let up = if direction.y.abs() > 0.99 { Vec3::Z } else { Vec3::Y };
+        let eye = center - direction * (radius + settings.caster_margin);
+        let light_view = Mat4::look_at_rh(eye, center, up);
+        let depth_range = 2.0 * radius + settings.caster_margin;
+        let mut light_projection = Mat4::orthographic_rh_gl(-radius, radius, -radius, radius, 0.0, depth_range);
+
+        // Snap the world origin to the texel grid of the shadow map
+        let half_resolution = settings.resolution as f32 * 0.5;
+        let origin = (light_projection * light_view).project_point3(Vec3::ZERO) * half_resolution;
+        let offset = (origin.round() - origin) / half_resolution;
+        light_projection.w_axis += Vec4::new(offset.x, offset.y, 0.0, 0.0);
+
+        let texel_size = 2.0 * radius / settings.resolution as f32;
+        ShadowCascade {
+            split_near,
+            split_far,
+            light_space_matrix: light_projection * light_view,
+            texel_size,
+            depth_bias: 1.5 * texel_size / depth_range,
+        }
+    }
+
+    /// World-space corners of the camera frustum between two view distances.
+    pub fn frustum_slice_corners(camera: &CameraController, split_near: f32, split_far: f32) -> [Vec3; 8] {
+        let projection = camera.get_projection_with_depth(split_near, split_far);
+        let inverse = (projection * camera.get_view()).inverse();
+
+        std::array::from_fn(|i| {
+            let ndc = Vec3::new(
+                if i & 1 == 0 { -1.0 } else { 1.0 },
+                if i & 2 == 0 { -1.0 } else { 1.0 },
+                if i & 4 == 0 { -1.0 } else { 1.0 },
+            );
+            inverse.project_point3(ndc)
+        })
+    }
+}
@@ -0,0 +1,198 @@
+use glam::Vec3;
+
+use super::lighting::Lighting;
+
+// Atmosphere constants, the sky shader (assets/shaders/sky/fragment.glsl) uses the same values
+/// Rayleigh scattering coefficients at sea level per km for red, green and blue
+const RAYLEIGH: Vec3 = Vec3::new(5.8e-3, 13.5e-3, 33.1e-3);
+/// Mie scattering coefficient at sea level per km, low for a clear day
+const MIE: f32 = 4.0e-3;
+/// Mie extinction is scattering plus absorption
+const MIE_EXTINCTION: f32 = MIE / 0.9;
+const RAYLEIGH_SCALE_HEIGHT: f32 = 8.0;
+const MIE_SCALE_HEIGHT: f32 = 1.2;
+/// Forward scattering of the Henyey-Greenstein phase function used for Mie
+const MIE_ANISOTROPY: f32 = 0.76;
+
+/// Irradiance of the sun and moon before the atmosphere
+const SUN_ILLUMINANCE: f32 = 3.2;
+const MOON_ILLUMINANCE: f32 = 0.12;
+/// Scale of the light scattered into the sky by the sun and moon
+const SUN_SKY_INTENSITY: f32 = 12.0;
+const MOON_SKY_INTENSITY: f32 = 0.25;
+const MOON_COLOR: Vec3 = Vec3::new(0.6, 0.7, 1.0);
+/// Faint sky radiance left at night without the moon
+const NIGHT_SKY: Vec3 = Vec3::new(0.002, 0.003, 0.008);
+/// Albedo of the ground for light bounced back up
+const GROUND_ALBEDO: Vec3 = Vec3::new(0.25, 0.22, 0.18);
+
+/// Clock driving the sun and moon. The sun rises in +X at 6:00, peaks at noon and sets
+/// in -X at 18:00, the moon is always opposite the sun.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeOfDay {
+    /// Hour of the day in `0.0..24.0`
+    pub hours: f32,
+    /// Game hours that pass per real second, 0 stops the clock
+    pub time_scale: f32,
+    /// Tilt of the sun's path towards -Z in degrees, keeps the noon sun off the zenith
+    pub axial_tilt: f32,
+}
+
+impl Default for TimeOfDay {
+    fn default() -> Self {
+        TimeOfDay {
+            hours: 10.0,
+            time_scale: 1.0 / 60.0,
+            axial_tilt: 30.0,
+        }
+    }
+}
+
+impl TimeOfDay {
+    pub fn update(&mut self, delta_seconds: f32) {
+        self.set_hours(self.hours + delta_seconds * self.time_scale);
+    }
+
+    pub fn set_hours(&mut self, hours: f32) {
+        self.hours = hours.rem_euclid(24.0);
+    }
+
+    /// `hh:mm` for display.
+    pub fn clock_string(&self) -> String {
+        let minutes = (self.hours * 60.0).floor() as u32;
+        format!("{:02}:{:02}", minutes / 60 % 24, minutes % 60)
+    }
+
+    /// Unit vector pointing at the sun.
+    pub fn sun_position(&self) -> Vec3 {
+        let angle = (self.hours - 6.0) / 24.0 * std::f32::consts::TAU;
+        let tilt = self.axial_tilt.to_radians();
+        Vec3::new(angle.cos(), angle.sin() * tilt.cos(), -angle.sin() * tilt.sin()).normalize()
+    }
+
+    /// Unit vector pointing at the moon.
+    pub fn moon_position(&self) -> Vec3 {
+        -self.sun_position()
+    }
+
+    /// Builds the light for this time: the sun by day, the moon by night. Both fade out
+    /// at the horizon so switching between them is invisible.
+    pub fn lighting(&self) -> Lighting {
+        let sun_position = self.sun_position();
+        let moon_position = self.moon_position();
+
+        let (light_position, light_color) = if sun_position.y >= 0.0 {
+            (sun_position, Self::sun_irradiance(sun_position))
+        } else {
+            (moon_position, Self::moon_irradiance(moon_position))
+        };
+
+        let sky_ambient = self.sky_ambient();
+        let bounced = Self::sun_irradiance(sun_position) * sun_position.y.max(0.0) / std::f32::consts::PI;
+        let ground_ambient = GROUND_ALBEDO * (bounced + sky_ambient);
+
+        let horizon_color = [Vec3::X, Vec3::Z, Vec3::NEG_X, Vec3::NEG_Z]
+            .iter()
+            .map(|direction| self.sky_radiance((*direction + Vec3::Y * 0.05).normalize()))
+            .sum::<Vec3>()
+            / 4.0;
+
+        let horizon_sun_color = self.sky_radiance(Vec3::new(sun_position.x, 0.05, sun_position.z).normalize());
+
+        Lighting {
+            light_direction: -light_position,
+            light_color,
+            sky_ambient,
+            ground_ambient,
+            sun_position,
+            moon_position,
+            horizon_color,
+            horizon_sun_color,
+        }
+    }
+
+    /// Sun light arriving at the ground, reddened by the air it passed through.
+    pub fn sun_irradiance(sun_position: Vec3) -> Vec3 {
+        SUN_ILLUMINANCE * transmittance(sun_position.y) * horizon_fade(sun_position.y)
+    }
+
+    pub fn moon_irradiance(moon_position: Vec3) -> Vec3 {
+        MOON_ILLUMINANCE * MOON_COLOR * transmittance(moon_position.y) * horizon_fade(moon_position.y)
+    }
+
+    /// Sky radiance seen along `direction`. Directions below the horizon see the horizon.
+    pub fn sky_radiance(&self, direction: Vec3) -> Vec3 {
+        let sun = self.sun_position();
+        let moon = self.moon_position();
+        NIGHT_SKY
+            + single_scattering(direction, sun, SUN_SKY_INTENSITY * twilight(sun.y))
+            + single_scattering(direction, moon, MOON_SKY_INTENSITY * twilight(moon.y)) * MOON_COLOR
+    }
+
+    /// Cosine-weighted average sky radiance over the upper hemisphere. Lambertian
+    /// surfaces facing up reflect `albedo * sky_ambient`.
+    pub fn sky_ambient(&self) -> Vec3 {
+        let mut total = self.sky_radiance(Vec3::Y);
+        let mut weight = 1.0;
+        for elevation in [10.0f32, 35.0, 60.0] {
+            for step in 0..8 {
+                let azimuth = step as f32 / 8.0 * std::f32::consts::TAU;
+                let elevation = elevation.to_radians();
+                let direction = Vec3::new(
+                    elevation.cos() * azimuth.cos(),
+                    elevation.sin(),
+                    elevation.cos() * azimuth.sin(),
+                );
+                total += self.sky_radiance(direction) * elevation.sin();
+                weight += elevation.sin();
+            }
+        }
+        total / weight
+    }
+}
+
+/// Relative optical air mass towards a direction with the given elevation sine, using
+/// the Kasten-Young formula so the horizon stays finite.
+pub fn air_mass(elevation_sin: f32) -> f32 {
+    let mu = elevation_sin.clamp(0.0, 1.0);
+    let elevation_degrees = mu.asin().to_degrees();
+    1.0 / (mu + 0.50572 * (elevation_degrees + 6.07995).powf(-1.6364))
+}
+
+/// Fraction of light that makes it through the atmosphere from a direction.
+pub fn transmittance(elevation_sin: f32) -> Vec3 {
+    let optical_depth = RAYLEIGH * RAYLEIGH_SCALE_HEIGHT + Vec3::splat(MIE_EXTINCTION * MIE_SCALE_HEIGHT);
+    (-optical_depth * air_mass(elevation_sin)).exp()
+}
+
+/// Light from a source at `light_position` scattered towards the viewer along `view`,
+/// treating the atmosphere along the view ray as one homogeneous slab.
+pub fn single_scattering(view: Vec3, light_position: Vec3, intensity: f32) -> Vec3 {
+    if intensity <= 0.0 {
+        return Vec3::ZERO;
+    }
+
+    let cos_theta = view.dot(light_position);
+    let rayleigh_phase = 3.0 / (16.0 * std::f32::consts::PI) * (1.0 + cos_theta * cos_theta);
+    let g = MIE_ANISOTROPY;
+    let mie_phase = (1.0 - g * g) / (4.0 * std::f32::consts::PI * (1.0 + g * g - 2.0 * g * cos_theta).powf(1.5));
+
+    let view_air_mass = air_mass(view.y);
+    let rayleigh_depth = RAYLEIGH * RAYLEIGH_SCALE_HEIGHT * view_air_mass;
+    let mie_depth = MIE * MIE_SCALE_HEIGHT * view_air_mass;
+    let extinction = rayleigh_depth + Vec3::splat(mie_depth / 0.9);
+    let scattering = rayleigh_depth * rayleigh_phase + Vec3::splat(mie_depth * mie_phase);
+
+    intensity * transmittance(light_position.y) * scattering / extinction * (Vec3::ONE - (-extinction).exp())
+}
+
+/// Sky brightness from a light source just below the horizon fades out over a few degrees.
+fn twilight(elevation_sin: f32) -> f32 {
+    ((elevation_sin + 0.12) / 0.14).clamp(0.0, 1.0)
+}
+
+/// Direct light fades to zero as its source reaches the horizon.
+fn horizon_fade(elevation_sin: f32) -> f32 {
+    let t = (elevation_sin / 0.05).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
@@ -0,0 +1,110 @@
+use std::collections::{HashMap, VecDeque};
+
+use glam::IVec3;
+
+/// Where a chunk is in its lifecycle. Transitions are driven by `TerrainManager`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChunkState {
+    /// Waiting in the generation queue, no data yet
+    Queued,
+    /// Density field is loaded, the first mesh hasn't been built yet
+    Generating,
+    /// Mesh matches the density field
+    Meshed,
+    /// Density was edited and the mesh is waiting for a rebuild
+    Dirty,
+}
+
+/// Lifecycle notifications published by `TerrainManager`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChunkEvent {
+    /// Chunk data was generated and inserted into the world
+    Loaded(IVec3),
+    /// First edit after the chunk was last meshed; further edits are coalesced until the next `Meshed`
+    Modified(IVec3),
+    /// A new mesh was uploaded for the chunk
+    Meshed(IVec3),
+    /// Chunk was removed from the world
+    Unloaded(IVec3),
+}
+
+impl ChunkEvent {
+    pub fn position(&self) -> IVec3 {
+        match *self {
+            ChunkEvent::Loaded(position)
+            | ChunkEvent::Modified(position)
+            | ChunkEvent::Meshed(position)
+            | ChunkEvent::Unloaded(position) => position,
+        }
+    }
+}
+
+/// Handle returned by `ChunkEventBus::subscribe`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u32);
+
+/// Events kept per subscriber. A subscriber that stops polling loses the oldest ones
+/// instead of growing its queue forever.
+pub const MAX_QUEUED_CHUNK_EVENTS: usize = 16384;
+
+#[derive(Default)]
+struct Subscriber {
+    events: VecDeque<ChunkEvent>,
+    /// Events dropped since the last poll
+    dropped: usize,
+}
+
+/// Fans chunk events out to independent subscriber queues, so gameplay, physics and
+/// saving systems can each poll at their own pace.
+#[derive(Default)]
+pub struct ChunkEventBus {
+    subscribers: HashMap<SubscriptionId, Subscriber>,
+    next_id: u32,
+}
+
+impl ChunkEventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a new subscriber. It receives every event published from now on.
+    pub fn subscribe(&mut self) -> SubscriptionId {
+        let id = SubscriptionId(self.next_id);
+        self.next_id += 1;
+        self.subscribers.insert(id, Subscriber::default());
+        id
+    }
+
+    pub fn unsubscribe(&mut self, id: SubscriptionId) {
+        self.subscribers.remove(&id);
+    }
+
+    /// Queues the event for every subscriber, dropping the oldest one of a full queue.
+    pub fn publish(&mut self, event: ChunkEvent) {
+        for (id, subscriber) in self.subscribers.iter_mut() {
+            if subscriber.events.len() >= MAX_QUEUED_CHUNK_EVENTS {
+                subscriber.events.pop_front();
+                if subscriber.dropped == 0 {
+                    println!(
+                        "Chunk event subscriber {:?} has {} unpolled events, dropping the oldest",
+                        id, MAX_QUEUED_CHUNK_EVENTS
+                    );
+                }
+                subscriber.dropped += 1;
+            }
+            subscriber.events.push_back(event);
+        }
+    }
+
+    /// Takes all events queued for the subscriber, oldest first.
+    pub fn poll(&mut self, id: SubscriptionId) -> Vec<ChunkEvent> {
+        let Some(subscriber) = self.subscribers.get_mut(&id) else {
+            return Vec::new();
+        };
+        if subscriber.dropped > 0 {
+            println!("Chunk event subscriber {:?} missed {} events", id, subscriber.dropped);
+            subscriber.dropped = 0;
+        }
+        subscriber.events.drain(..).collect()
+    }
+}
@@ -0,0 +1,31 @@
+use glam::IVec3;
+
+/// Inclusive box of edited samples inside a chunk's padded `ScalarData`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirtyRegion {
+    pub min: IVec3,
+    pub max: IVec3,
+}
+
+impl DirtyRegion {
+    /// Region containing a single sample.
+    pub fn new(sample: IVec3) -> Self {
+        DirtyRegion { min: sample, max: sample }
+    }
+
+    /// Grows the region to include `sample`.
+    pub fn include(&mut self, sample: IVec3) {
+        self.min = self.min.min(sample);
+        self.max = self.max.max(sample);
+    }
+
+    /// Grows the region to include all of `other`.
+    pub fn merge(&mut self, other: &DirtyRegion) {
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    pub fn size(&self) -> IVec3 {
+        self.max - self.min + IVec3::ONE
+    }
+}
@@ -1,8 +1,9 @@
 use crate::terrain::marching_cubes::marching_cubes_data_tables::MarchingCubesDataTables;
 use crate::terrain::scalar::scalar_data::ScalarData;
-use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
-use rayon::ThreadPoolBuilder;
-use std::{collections::HashMap, time::Instant};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::collections::HashMap;
+use std::sync::OnceLock;
 
 #[derive(Hash, Eq, PartialEq, Debug, Clone, Copy)]
 struct VertexKey {
@@ -13,6 +14,13 @@ struct VertexKey {
     index: u8,
 }
 
+/// Floats per vertex: position, normal and material id.
+pub const VERTEX_STRIDE: usize = 7;
+
+/// Mesh data for one x slice of a chunk: slice x, interleaved [position, normal, material]
+/// vertex data and indices local to the slice.
+pub type MeshSlice = (usize, Vec<f32>, Vec<u32>);
+
 pub struct MarchingCubesGenerator;
 
 impl MarchingCubesGenerator {
@@ -22,52 +30,84 @@ impl MarchingCubesGenerator {
         isolevel: f32,
         lod: usize,
     ) -> (Vec<f32>, Vec<u32>) {
-        // Configure Rayon to use exactly 4 threads
-        let pool = ThreadPoolBuilder::new()
-            .num_threads(4)
-            .build()
-            .expect("Failed to create thread pool");
+        let slices = Self::generate_slices(&data_tables, &scalar_data, isolevel, lod, None);
+        Self::merge_slices(slices.iter())
+    }
 
+    /// Meshes the chunk slice by slice. With `x_range` set, only the slices whose cubes
+    /// or normals read samples in that inclusive x range are rebuilt.
+    pub fn generate_slices(
+        data_tables: &MarchingCubesDataTables,
+        scalar_data: &ScalarData,
+        isolevel: f32,
+        lod: usize,
+        x_range: Option<(usize, usize)>,
+    ) -> Vec<MeshSlice> {
         // LOD must be at least 1 (no skipping)
         let lod = lod.max(1);
         let grid_size = scalar_data.dimensions.x as usize;
         let values = &scalar_data.values;
 
         // Early exit if all values are below threshold
-        if values.iter().all(|&value| value < isolevel) {
-            return (Vec::new(), Vec::new());
+        if x_range.is_none() && values.iter().all(|&value| value < isolevel) {
+            return Vec::new();
         }
 
-        let mut vertex_data: Vec<f32> = Vec::new(); // Interleaved [position, normal]
-        let mut indices: Vec<u32> = Vec::new();
+        let slice_range = x_range.map(|(min_x, max_x)| Self::slices_affected_by(min_x, max_x, lod));
 
         // Process slices in parallel using our 4-thread pool
-        let slices: Vec<(usize, Vec<f32>, Vec<u32>)> = pool.install(|| {
-            (1..grid_size - 2)
+        Self::thread_pool().install(|| {
+            Self::slice_positions(grid_size, lod)
+                .filter(|x| slice_range.is_none_or(|(first, last)| (first..=last).contains(x)))
+                .collect::<Vec<_>>()
                 .into_par_iter()
-                .step_by(lod) // Skip slices based on LOD
-                .filter(|&x| x + lod < grid_size - 1) // Ensure overlap between slices
-                .map(|x| Self::process_slice(x, &data_tables, &scalar_data, isolevel, lod))
+                .map(|x| Self::process_slice(x, data_tables, scalar_data, isolevel, lod))
                 .collect()
-        });
-
-        // Merge results in order
-        let mut vertex_offset = 0;
-        for (_x, mut slice_vertex_data, mut slice_indices) in slices {
-            // Adjust indices to global vertex list
-            for index in &mut slice_indices {
-                *index += vertex_offset;
-            }
+        })
+    }
 
-            indices.append(&mut slice_indices);
-            vertex_data.append(&mut slice_vertex_data);
+    /// Concatenates slices in order, offsetting their indices into one vertex list.
+    pub fn merge_slices<'a>(slices: impl Iterator<Item = &'a MeshSlice>) -> (Vec<f32>, Vec<u32>) {
+        let mut vertex_data: Vec<f32> = Vec::new(); // Interleaved [position, normal, material]
+        let mut indices: Vec<u32> = Vec::new();
 
-            vertex_offset = (vertex_data.len() / 6) as u32; // 6 floats per vertex (position + normal)
+        for (_x, slice_vertex_data, slice_indices) in slices {
+            // Adjust indices to global vertex list
+            let vertex_offset = (vertex_data.len() / VERTEX_STRIDE) as u32;
+            indices.extend(slice_indices.iter().map(|index| index + vertex_offset));
+            vertex_data.extend_from_slice(slice_vertex_data);
         }
 
         (vertex_data, indices)
     }
 
+    /// X coordinates of every slice meshed for a grid of the given size.
+    pub fn slice_positions(grid_size: usize, lod: usize) -> impl Iterator<Item = usize> {
+        (1..grid_size - 2)
+            .step_by(lod) // Skip slices based on LOD
+            .filter(move |&x| x + lod < grid_size - 1) // Ensure overlap between slices
+    }
+
+    /// Inclusive range of slice x coordinates that read samples in `min_x..=max_x`.
+    ///
+    /// A slice at `x` spans corners `x..=x + lod` and its normals look one sample
+    /// further on both sides.
+    pub fn slices_affected_by(min_x: usize, max_x: usize, lod: usize) -> (usize, usize) {
+        (min_x.saturating_sub(lod + 1), max_x + 1)
+    }
+
+    fn thread_pool() -> &'static ThreadPool {
+        static POOL: OnceLock<ThreadPool> = OnceLock::new();
+
+        // Configure Rayon to use exactly 4 threads, built once and shared by all meshing
+        POOL.get_or_init(|| {
+            ThreadPoolBuilder::new()
+                .num_threads(4)
+                .build()
+                .expect("Failed to create thread pool")
+        })
+    }
+
     fn process_slice(
         x: usize,
         data_tables: &MarchingCubesDataTables,
@@ -77,12 +117,13 @@ impl MarchingCubesGenerator {
     ) -> (usize, Vec<f32>, Vec<u32>) {
         let grid_size = scalar_data.dimensions.x as usize;
         let values = &scalar_data.values;
-        let grid = &scalar_data.grid;
+        let materials = &scalar_data.materials;
 
         let mut slice_vertex_data = Vec::new();
         let mut slice_indices = Vec::new();
         let mut vertex_cache: HashMap<VertexKey, u32> = HashMap::new();
         let mut corner_values = [0.0; 8];
+        let mut corner_materials = [0u8; 8];
 
         for y in (1..grid_size - 2).step_by(lod) {
             for z in (1..grid_size - 2).step_by(lod) {
@@ -96,9 +137,11 @@ impl MarchingCubesGenerator {
                     
                     // Handle out-of-bounds cases safely
                     corner_values[i] = if corner_x >= grid_size || corner_y >= grid_size || corner_z >= grid_size {
+                        corner_materials[i] = 0;
                         isolevel - 1.0 // Assign a value below isolevel
                     } else {
                         let index = corner_x * grid_size * grid_size + corner_y * grid_size + corner_z;
+                        corner_materials[i] = materials.get(index).copied().unwrap_or(0);
                         // Handle NaN/infinite values
                         if values[index].is_nan() || values[index].is_infinite() {
                             isolevel - 1.0
@@ -136,6 +179,13 @@ impl MarchingCubesGenerator {
                             lod,
                         );
 
+                        // The vertex takes the material of the solid end of its edge
+                        let material = if corner_values[v1 as usize] >= corner_values[v2 as usize] {
+                            corner_materials[v1]
+                        } else {
+                            corner_materials[v2]
+                        };
+
                         // Create key for vertex cache
                         let key = VertexKey {
                             x,
@@ -149,9 +199,10 @@ impl MarchingCubesGenerator {
                         cube_vertices[i] = match vertex_cache.get(&key) {
                             Some(&index) => index,
                             None => {
-                                let index = (slice_vertex_data.len() / 6) as u32;
+                                let index = (slice_vertex_data.len() / VERTEX_STRIDE) as u32;
                                 slice_vertex_data.extend_from_slice(&pos);
                                 slice_vertex_data.extend_from_slice(&normal);
+                                slice_vertex_data.push(material as f32);
                                 vertex_cache.insert(key, index);
                                 index
                             }
@@ -274,21 +325,9 @@ impl MarchingCubesGenerator {
             position1[2] + t * (position2[2] - position1[2]),
         ];
 
-        let normal1 = Self::calculate_normal(position1, scalar_data);
-        let normal2 = Self::calculate_normal(position2, scalar_data);
-
-        // Normalize the interpolated normal
-        let normal = {
-            let nx = normal1[0] + t * (normal2[0] - normal1[0]);
-            let ny = normal1[1] + t * (normal2[1] - normal1[1]);
-            let nz = normal1[2] + t * (normal2[2] - normal1[2]);
-            let length = (nx * nx + ny * ny + nz * nz).sqrt();
-            if length > f32::EPSILON {
-                [nx / length, ny / length, nz / length]
-            } else {
-                [0.0, 0.0, 1.0] // Default normal if calculation fails
-            }
-        };
+        // Sample the field gradient at the exact vertex position rather than blending
+        // the two corner normals, so both chunks sharing a border agree on it
+        let normal = Self::calculate_normal(pos, scalar_data);
 
         (pos, normal)
     }
@@ -301,40 +340,53 @@ impl MarchingCubesGenerator {
         ]
     }
 
+    /// Computes the surface normal at a (possibly fractional) grid position.
+    ///
+    /// Central-difference gradients are taken at the eight grid points surrounding
+    /// `pos` and trilinearly interpolated. Cube corners live in `1..=chunk_size + 1`,
+    /// so every difference reads from the chunk's own padding and the result only
+    /// depends on world-space density, not on how the world is split into chunks.
     fn calculate_normal(pos: [f32; 3], scalar_data: &ScalarData) -> [f32; 3] {
         let grid_size = scalar_data.dimensions.x as usize;
-        let values = &scalar_data.values;
-        
-        let x = pos[0].floor() as usize;
-        let y = pos[1].floor() as usize;
-        let z = pos[2].floor() as usize;
-
-        // Helper function to get value at grid point with bounds checking
-        let get_value = |x: isize, y: isize, z: isize| -> f32 {
-            if x >= 0 && x < grid_size as isize && 
-               y >= 0 && y < grid_size as isize && 
-               z >= 0 && z < grid_size as isize 
-            {
-                let val = values[x as usize * grid_size * grid_size + y as usize * grid_size + z as usize];
-                if val.is_nan() || val.is_infinite() {
-                    0.0
-                } else {
-                    val
-                }
-            } else {
-                0.0
+        let max_cell = grid_size.saturating_sub(2) as f32;
+
+        // Lower corner of the cell containing `pos`, clamped so that the upper
+        // corner stays inside the grid even when `pos` lies on the last plane
+        let cell = [
+            pos[0].floor().clamp(0.0, max_cell),
+            pos[1].floor().clamp(0.0, max_cell),
+            pos[2].floor().clamp(0.0, max_cell),
+        ];
+        let frac = [
+            (pos[0] - cell[0]).clamp(0.0, 1.0),
+            (pos[1] - cell[1]).clamp(0.0, 1.0),
+            (pos[2] - cell[2]).clamp(0.0, 1.0),
+        ];
+
+        let mut gradient = [0.0f32; 3];
+        for corner in 0..8 {
+            let offset = [corner & 1, (corner >> 1) & 1, (corner >> 2) & 1];
+            let weight = (0..3)
+                .map(|axis| if offset[axis] == 1 { frac[axis] } else { 1.0 - frac[axis] })
+                .product::<f32>();
+
+            if weight <= 0.0 {
+                continue;
             }
-        };
 
-        // Calculate derivatives using central differences
-        let dx = (get_value(x as isize + 1, y as isize, z as isize) - 
-                  get_value(x as isize - 1, y as isize, z as isize));
-        let dy = (get_value(x as isize, y as isize + 1, z as isize) - 
-                  get_value(x as isize, y as isize - 1, z as isize));
-        let dz = (get_value(x as isize, y as isize, z as isize + 1) - 
-                  get_value(x as isize, y as isize, z as isize - 1));
+            let corner_gradient = Self::grid_gradient(
+                cell[0] as usize + offset[0],
+                cell[1] as usize + offset[1],
+                cell[2] as usize + offset[2],
+                scalar_data,
+            );
+            for axis in 0..3 {
+                gradient[axis] += corner_gradient[axis] * weight;
+            }
+        }
 
         // Normalize
+        let [dx, dy, dz] = gradient;
         let length = (dx * dx + dy * dy + dz * dz).sqrt();
         if length > f32::EPSILON {
             [dx / length, dy / length, dz / length]
@@ -342,4 +394,36 @@ impl MarchingCubesGenerator {
             [0.0, 0.0, 1.0] // Default normal if calculation fails
         }
     }
+
+    /// Density gradient at an integer grid point. Uses central differences and
+    /// falls back to one-sided differences on the outermost padding layer.
+    fn grid_gradient(x: usize, y: usize, z: usize, scalar_data: &ScalarData) -> [f32; 3] {
+        let grid_size = scalar_data.dimensions.x as usize;
+        let values = &scalar_data.values;
+
+        // Helper function to get value at grid point with non-finite values sanitized
+        let get_value = |x: usize, y: usize, z: usize| -> f32 {
+            let val = values[x * grid_size * grid_size + y * grid_size + z];
+            if val.is_nan() || val.is_infinite() {
+                0.0
+            } else {
+                val
+            }
+        };
+
+        let difference = |low: [usize; 3], high: [usize; 3], steps: usize| -> f32 {
+            (get_value(high[0], high[1], high[2]) - get_value(low[0], low[1], low[2])) / steps as f32
+        };
+
+        let mut gradient = [0.0; 3];
+        for axis in 0..3 {
+            let mut low = [x, y, z];
+            let mut high = [x, y, z];
+            low[axis] = low[axis].saturating_sub(1);
+            high[axis] = (high[axis] + 1).min(grid_size - 1);
+            gradient[axis] = difference(low, high, (high[axis] - low[axis]).max(1));
+        }
+
+        gradient
+    }
 }
\ No newline at end of file
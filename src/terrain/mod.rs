@@ -1,4 +1,7 @@
 pub mod terrain_manager;
 pub mod terrain_chunk;
+pub mod terrain_generator_config;
+pub mod chunk_lifecycle;
+pub mod dirty_region;
 pub mod scalar;
 pub mod marching_cubes;
\ No newline at end of file
@@ -4,33 +4,57 @@ use glam::IVec3;
 pub struct ScalarData {
     pub grid: Vec<[f32; 3]>,       // 3D positions of the grid points
     pub values: Vec<f32>,          // Scalar values at each grid point
+    pub materials: Vec<u8>,        // Material id at each grid point
     pub dimensions: IVec3, // Dimensions of the scalar field (x, y, z)
 }
 
 impl ScalarData {
+    /// Flat index of a grid coordinate, matching the generator's x-major, z-minor layout.
+    /// Returns None if coordinates are out of bounds
+    pub fn index_of(&self, coordinate: IVec3) -> Option<usize> {
+        if coordinate.cmplt(IVec3::ZERO).any() || coordinate.cmpge(self.dimensions).any() {
+            return None;
+        }
+
+        Some((coordinate.x * self.dimensions.y * self.dimensions.z
+            + coordinate.y * self.dimensions.z
+            + coordinate.z) as usize)
+    }
+
     /// Gets the value at specified grid coordinates
     /// Returns None if coordinates are out of bounds
     pub fn get_value(&self, coordinate: IVec3) -> Option<f32> {
-        if coordinate.x >= self.dimensions.x || coordinate.y >= self.dimensions.y || coordinate.z >= self.dimensions.z {
-            return None;
+        self.index_of(coordinate)
+            .and_then(|index| self.values.get(index).copied())
+    }
+
+    /// Gets the material id at specified grid coordinates
+    /// Returns None if coordinates are out of bounds
+    pub fn get_material(&self, coordinate: IVec3) -> Option<u8> {
+        self.index_of(coordinate)
+            .and_then(|index| self.materials.get(index).copied())
+    }
+
+    /// Sets the material id at specified grid coordinates
+    /// Returns Err if coordinates are out of bounds, Ok otherwise
+    pub fn set_material(&mut self, coordinate: IVec3, material: u8) -> Result<(), String> {
+        let index = self.index_of(coordinate)
+            .ok_or_else(|| format!("Coordinate {:?} is outside of the scalar field {:?}", coordinate, self.dimensions))?;
+
+        if let Some(m) = self.materials.get_mut(index) {
+            *m = material;
+            Ok(())
+        } else {
+            Err("Index calculation error".to_string())
         }
-        
-        let index = (coordinate.z * self.dimensions.x * self.dimensions.y 
-                  + coordinate.y * self.dimensions.x 
-                  + coordinate.x) as usize;
-        
-        self.values.get(index).copied()
     }
 
-    /// Sets the value at specified grid coordinates
+    /// Adds `value` to the density at specified grid coordinates
     /// Returns Err if coordinates are out of bounds, Ok otherwise
     pub fn set_value(&mut self, coordinate: IVec3, value: f32) -> Result<(), String> {
+        let index = self.index_of(coordinate)
+            .ok_or_else(|| format!("Coordinate {:?} is outside of the scalar field {:?}", coordinate, self.dimensions))?;
 
-        
-        let index = (coordinate.z * self.dimensions.x * self.dimensions.y 
-                  + coordinate.y * self.dimensions.x 
-                  + coordinate.x) as usize;
-        
         if let Some(v) = self.values.get_mut(index) {
             *v += value;
             Ok(())
@@ -38,4 +62,4 @@ impl ScalarData {
             Err("Index calculation error".to_string())
         }
     }
-}
\ No newline at end of file
+}
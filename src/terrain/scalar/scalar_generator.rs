@@ -3,25 +3,31 @@ use noise::{core::value, NoiseFn, Perlin};
 use rayon::prelude::*;
 use std::time::Instant;
 use super::scalar_data::ScalarData;
+use crate::terrain::terrain_generator_config::MaterialStrata;
 
 pub struct ScalarGenerator;
 
 impl ScalarGenerator {
-    pub fn generate(position: IVec3, seed: u32, chunk_size: u16) -> ScalarData {        
+    /// Extra samples stored along each axis on top of `chunk_size`. They overlap the
+    /// neighbouring chunks so the mesher can close the seam and take central
+    /// differences on both borders.
+    pub const PADDING: i32 = 3;
+
+    pub fn generate(position: IVec3, seed: u32, chunk_size: u16, material_strata: &MaterialStrata) -> ScalarData {        
         // Precompute Perlin noise instances
         let perlin = Perlin::new(seed);
         let perlin2 = Perlin::new(seed.wrapping_add(1)); // Different seeds for variety
         let perlin3 = Perlin::new(seed.wrapping_add(2));
         let perlin4 = Perlin::new(seed.wrapping_add(3));
 
-        let dimensions = IVec3::new(chunk_size as i32 +3, chunk_size as i32 +3, chunk_size as i32 +3);
+        let dimensions = IVec3::splat(chunk_size as i32 + Self::PADDING);
 
         // Precompute position offsets
         let base_x = position.x as f64 * (chunk_size as f64);
         let base_y = position.y as f64 * (chunk_size as f64);
         let base_z = position.z as f64 * (chunk_size as f64);
 
-        let (grid, values): (Vec<_>, Vec<_>) = (0..dimensions.x) // Swap x and z loops
+        let ((grid, values), materials): ((Vec<_>, Vec<_>), Vec<_>) = (0..dimensions.x) // Swap x and z loops
             .into_par_iter()
             .flat_map_iter(|x| { // Process x first
                 let world_x = base_x + x as f64;
@@ -43,7 +49,9 @@ impl ScalarGenerator {
                         let value =  noise1 + noise2*6.0 + noise3*12.0 + (noise4*1.5)*noise5 - ((world_y) * 0.025) as f32;
                         
 
-                        (grid_point, value)
+                        let material = material_strata.material_at(world_y, value);
+
+                        ((grid_point, value), material)
                     })
                 })
             })
@@ -52,6 +60,7 @@ impl ScalarGenerator {
         ScalarData {
             grid,
             values,
+            materials,
             dimensions,
         }
     }
@@ -1,19 +1,27 @@
-use std::panic::AssertUnwindSafe;
+use std::collections::BTreeMap;
 
 use ferrousgl::Mesh;
 use glam::{IVec3, Vec3};
 
+use super::chunk_lifecycle::ChunkState;
+use super::dirty_region::DirtyRegion;
 use super::marching_cubes::marching_cubes_data_tables::MarchingCubesDataTables;
-use super::marching_cubes::marching_cubes_generator::MarchingCubesGenerator;
+use super::marching_cubes::marching_cubes_generator::{MarchingCubesGenerator, MeshSlice, VERTEX_STRIDE};
 use super::scalar::scalar_data::ScalarData;
 use super::scalar::scalar_generator::ScalarGenerator;
-use crate::utils::ray::Ray;
+use super::terrain_generator_config::MaterialStrata;
+use crate::utils::aabb::Aabb;
 
 pub struct TerrainChunk {
     pub position: IVec3,
     pub mesh: Mesh,
     pub is_empty: bool,
+    pub modified: bool, // Edited since it was generated, regenerating would lose the edits
+    pub state: ChunkState,
+    pub bounds: Option<Aabb>, // Tight mesh-space bounds of the current mesh, None if empty
     pub scalar_data: ScalarData,
+    pub lod: usize,
+    mesh_slices: BTreeMap<usize, MeshSlice>, // Cached mesher output per x slice
 }
 
 impl TerrainChunk {
@@ -21,38 +29,43 @@ impl TerrainChunk {
         position: IVec3,
         chunk_size: u16,
         seed: u32,
+        material_strata: &MaterialStrata,
         data_tables: &MarchingCubesDataTables,
         isolevel: f32,
         lod: usize,
     ) -> Self {
         // Generate scalar data
-        let scalar_data = ScalarGenerator::generate(position, seed, chunk_size);
+        let scalar_data = ScalarGenerator::generate(position, seed, chunk_size, material_strata);
 
         // Generate mesh data using marching cubes
-        let (vertices, indices) =
-            MarchingCubesGenerator::generate(data_tables.clone(), scalar_data.clone(), isolevel, lod);
-
-        let mut is_empty = false;
-        if vertices.is_empty() {
-            is_empty = true;
-        }
+        let mut chunk = Self::new(position, scalar_data, lod);
+        chunk.remesh_chunk(data_tables, isolevel, lod);
+        chunk.state = ChunkState::Meshed;
+        chunk
+    }
 
+    /// Wraps generated scalar data in a chunk that has no mesh yet. It starts out
+    /// `Generating`, the first `remesh_chunk` builds its mesh.
+    pub fn new(position: IVec3, scalar_data: ScalarData, lod: usize) -> Self {
         // Create the mesh
         let mut mesh = Mesh::new();
 
         mesh.add_vertex_attributes(&[
             (0, 3, gl::FLOAT, false), // position
             (1, 3, gl::FLOAT, false), // normal
+            (2, 1, gl::FLOAT, false), // material id
         ]);
 
-        mesh.update_vertices(&vertices);
-        mesh.update_indices(&indices);
-
         TerrainChunk {
             position,
             mesh,
-            is_empty,
+            is_empty: true,
+            modified: false,
+            state: ChunkState::Generating,
+            bounds: None,
             scalar_data,
+            lod,
+            mesh_slices: BTreeMap::new(),
         }
     }
 
@@ -60,29 +73,68 @@ impl TerrainChunk {
         &self.mesh
     }
 
-    /// Modify the scalar data at a specific position and remesh
+    /// Mesh bounds moved to the chunk's world-space origin.
+    pub fn world_bounds(&self, origin: Vec3) -> Option<Aabb> {
+        self.bounds.map(|bounds| bounds.translated(origin))
+    }
+
+    /// Modify the scalar data at a specific position
     pub fn modify_terrain(
         &mut self,
         local_position: IVec3,
         delta: f32,
     ) {
-        self.scalar_data.set_value(local_position, delta);
+        if let Err(e) = self.scalar_data.set_value(local_position, delta) {
+            println!("Failed to modify chunk {:?}: {}", self.position, e);
+        }
     }
 
+    /// Rebuilds every slice of the mesh.
     pub fn remesh_chunk(
         &mut self,
         data_tables: &MarchingCubesDataTables,
         isolevel: f32,
         lod: usize
     ) {
-        // Regenerate the mesh
-        let (vertices, indices) =
-            MarchingCubesGenerator::generate(data_tables.clone(), self.scalar_data.clone(), isolevel, lod);
+        let slices = MarchingCubesGenerator::generate_slices(data_tables, &self.scalar_data, isolevel, lod, None);
 
-        self.is_empty = vertices.is_empty();
+        self.lod = lod;
+        self.mesh_slices = slices.into_iter().map(|slice| (slice.0, slice)).collect();
+        self.upload_mesh();
+    }
 
-        self.mesh.update_vertices(&vertices);
-        self.mesh.update_indices(&indices);
+    /// Rebuilds only the slices that read samples inside `region`, reusing the cached
+    /// output of all other slices.
+    pub fn remesh_region(
+        &mut self,
+        data_tables: &MarchingCubesDataTables,
+        isolevel: f32,
+        region: &DirtyRegion,
+    ) {
+        let min_x = region.min.x.max(0) as usize;
+        let max_x = region.max.x.max(0) as usize;
+        let slices = MarchingCubesGenerator::generate_slices(data_tables, &self.scalar_data, isolevel, self.lod, Some((min_x, max_x)));
+
+        for slice in slices {
+            self.mesh_slices.insert(slice.0, slice);
+        }
+        self.upload_mesh();
+    }
+
+    /// Mesh-space vertices of the current mesh, `VERTEX_STRIDE` floats each.
+    pub fn mesh_vertices(&self) -> impl Iterator<Item = &[f32]> {
+        self.mesh_slices
+            .values()
+            .flat_map(|(_, vertices, _)| vertices.chunks_exact(VERTEX_STRIDE))
+    }
+
+    fn upload_mesh(&mut self) {
+        let (vertices, indices) = MarchingCubesGenerator::merge_slices(self.mesh_slices.values());
+
+        self.is_empty = vertices.is_empty();
+        self.bounds = Aabb::from_points(
+            vertices.chunks_exact(VERTEX_STRIDE).map(|vertex| Vec3::new(vertex[0], vertex[1], vertex[2])),
+        );
 
         self.mesh.update_vertices(&vertices);
         self.mesh.update_indices(&indices);
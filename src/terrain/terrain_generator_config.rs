@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// Chunk edge lengths the generator, mesher and shaders are tuned for.
+pub const SUPPORTED_CHUNK_SIZES: [u16; 3] = [32, 64, 128];
+
+/// World generation settings loaded from `terrain_generator_config.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TerrainGeneratorConfig {
+    pub chunk_size: u16,
+    pub seed: u32,
+    pub isolevel: f32,
+    pub material_strata: MaterialStrata,
+}
+
+/// Rules for assigning material ids (indices into `terrain_materials.json`) to voxels.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MaterialStrata {
+    /// Material for solid voxels close to the surface
+    pub surface_material: u8,
+    /// Voxels with a density below this count as surface
+    pub surface_max_density: f32,
+    /// Materials of the horizontal layers below the surface, repeating with depth
+    pub strata_materials: Vec<u8>,
+    /// Height of each layer in world units
+    pub strata_thickness: f32,
+}
+
+impl Default for MaterialStrata {
+    fn default() -> Self {
+        MaterialStrata {
+            surface_material: 0,
+            surface_max_density: 0.65,
+            strata_materials: vec![1],
+            strata_thickness: 24.0,
+        }
+    }
+}
+
+impl MaterialStrata {
+    pub fn material_at(&self, world_y: f64, density: f32) -> u8 {
+        if density < self.surface_max_density || self.strata_materials.is_empty() {
+            return self.surface_material;
+        }
+
+        let layer = (world_y / self.strata_thickness.max(f32::EPSILON) as f64).floor() as i64;
+        self.strata_materials[layer.rem_euclid(self.strata_materials.len() as i64) as usize]
+    }
+}
+
+impl Default for TerrainGeneratorConfig {
+    fn default() -> Self {
+        TerrainGeneratorConfig {
+            chunk_size: 64,
+            seed: 0,
+            isolevel: 0.5,
+            material_strata: MaterialStrata::default(),
+        }
+    }
+}
+
+impl TerrainGeneratorConfig {
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        let config: Self = serde_json::from_reader(reader)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Loads the config, falling back to defaults if it is missing or invalid.
+    pub fn load_or_default<P: AsRef<Path>>(path: P) -> Self {
+        match Self::load_from_file(&path) {
+            Ok(config) => config,
+            Err(e) => {
+                println!("Failed to load terrain generator config {:?}, using defaults: {}", path.as_ref(), e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !SUPPORTED_CHUNK_SIZES.contains(&self.chunk_size) {
+            return Err(format!("Unsupported chunk size {} (expected one of {:?})", self.chunk_size, SUPPORTED_CHUNK_SIZES));
+        }
+        Ok(())
+    }
+}
@@ -1,67 +1,150 @@
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
-use std::collections::HashSet;
 use std::collections::VecDeque;
-use std::path::Path;
-use std::time::Instant;
-use ferrousgl::{texture, GlWindow, MipmapType, Shader, Texture};
-use glam::{IVec3, Mat4, Vec3, Vec4};
-use serde_json::de;
+use std::time::{Duration, Instant};
+use glam::{IVec3, Vec3};
+use crate::physics::density_field::DensityField;
+use crate::utils::aabb::Aabb;
 use crate::utils::ray::Ray; // Ensure Ray is imported
 
+use super::chunk_lifecycle::{ChunkEvent, ChunkEventBus, ChunkState, SubscriptionId};
+use super::dirty_region::DirtyRegion;
+use super::scalar::scalar_generator::ScalarGenerator;
 use super::terrain_chunk::TerrainChunk;
+use super::terrain_generator_config::{MaterialStrata, TerrainGeneratorConfig};
 use super::marching_cubes::marching_cubes_data_tables::MarchingCubesDataTables;
 
+/// Time spent on terrain work since the last `take_timings`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TerrainTimings {
+    /// Scalar field generation of new chunks
+    pub generation: Duration,
+    /// Marching cubes for new chunks and remeshing of edited ones
+    pub meshing: Duration,
+    pub chunks_generated: usize,
+    pub chunks_meshed: usize,
+}
+
 pub struct TerrainManager {
     pub chunk_size: u16,
     pub chunks: HashMap<IVec3, TerrainChunk>,
     data_tables: MarchingCubesDataTables,
-    pub terrain_shader: Shader,
-    pub textures: Vec<Texture>, // List of textures
     chunk_generation_queue: VecDeque<IVec3>, // Queue for chunk positions to generate
+    unmeshed_chunks: VecDeque<IVec3>, // Loaded chunks still `Generating`, waiting for their first mesh
+    replacements: HashMap<IVec3, TerrainChunk>, // Regenerated chunks waiting for a mesh, the old chunk is drawn meanwhile
     seed: u32,
     isolevel: f32,
-    last_chunk_position: IVec3,
-    last_local_position: IVec3,
-    chunks_to_remesh: HashSet<IVec3>,
+    material_strata: MaterialStrata,
+    dirty_regions: HashMap<IVec3, DirtyRegion>, // Edited samples per chunk awaiting a remesh
+    pub remesh_budget: Duration, // Time per tick spent remeshing edited chunks
+    pub generation_budget: Duration, // Time per tick spent generating queued chunks
+    chunk_events: ChunkEventBus,
     chunk_generation_start_time: Option<Instant>,
+    timings: TerrainTimings,
 }
 
 impl TerrainManager {
-    pub fn new() -> Self {
+    pub fn new(config: &TerrainGeneratorConfig) -> Self {
         let data_tables = MarchingCubesDataTables::load_from_files("./assets/data/marching_cubes_tables/").unwrap();
 
-        let terrain_shader = Shader::new_from_file(
-            Path::new("./assets/shaders/terrain/vertex.glsl"),
-            Path::new("./assets/shaders/terrain/fragment.glsl"),
-        ).unwrap();
-
-        let textures = vec![
-            Texture::new_from_file(Path::new("./assets/media/textures/grass.png")).unwrap(),
-            Texture::new_from_file(Path::new("./assets/media/textures/grass_normal.png")).unwrap(),
-            Texture::new_from_file(Path::new("./assets/media/textures/rock.png")).unwrap(),
-            Texture::new_from_file(Path::new("./assets/media/textures/rock_normal.png")).unwrap(),
-        ];
-        for texture in &textures {
-            texture.bind(0);
-            texture.set_mipmap_type(MipmapType::Nearest);
-        }
-
         TerrainManager {
-            chunk_size: 64,
+            chunk_size: config.chunk_size,
             chunks: HashMap::new(),
             data_tables,
-            terrain_shader,
-            textures,
             chunk_generation_queue: VecDeque::new(),
-            seed: 0,
-            isolevel: 0.5,
-            last_chunk_position: IVec3::new(0, 0, 0),
-            last_local_position: IVec3::new(0, 0, 0),
-            chunks_to_remesh: HashSet::new(),
+            unmeshed_chunks: VecDeque::new(),
+            replacements: HashMap::new(),
+            seed: config.seed,
+            isolevel: config.isolevel,
+            material_strata: config.material_strata.clone(),
+            dirty_regions: HashMap::new(),
+            remesh_budget: Duration::from_millis(4),
+            generation_budget: Duration::from_millis(4),
+            chunk_events: ChunkEventBus::new(),
             chunk_generation_start_time: None,
+            timings: TerrainTimings::default(),
+        }
+    }
+
+    /// World-space position of a chunk's first sample.
+    pub fn chunk_origin(&self, chunk_position: IVec3) -> Vec3 {
+        (chunk_position * self.chunk_size as i32).as_vec3()
+    }
+
+    /// World-space bounds of a chunk's mesh, None if the chunk has no geometry.
+    pub fn chunk_bounds(&self, chunk: &TerrainChunk) -> Option<Aabb> {
+        chunk.world_bounds(self.chunk_origin(chunk.position))
+    }
+
+    /// Chunk that owns the voxel containing the world position.
+    pub fn world_to_chunk_position(&self, position: Vec3) -> IVec3 {
+        (position / self.chunk_size as f32).floor().as_ivec3()
+    }
+
+    pub fn isolevel(&self) -> f32 {
+        self.isolevel
+    }
+
+    /// Trilinearly interpolated density at a world position, None if it isn't loaded.
+    pub fn density_at(&self, position: Vec3) -> Option<f32> {
+        let base = position.floor();
+        let t = position - base;
+        let base = base.as_ivec3();
+
+        // The padding means all eight corners of a cell are stored in the chunk owning
+        // its lowest corner
+        let size = IVec3::splat(self.chunk_size as i32);
+        let chunk = self.chunks.get(&base.div_euclid(size))?;
+        let local = base.rem_euclid(size);
+        let corner = |x: i32, y: i32, z: i32| chunk.scalar_data.get_value(local + IVec3::new(x, y, z));
+
+        let x00 = lerp(corner(0, 0, 0)?, corner(1, 0, 0)?, t.x);
+        let x10 = lerp(corner(0, 1, 0)?, corner(1, 1, 0)?, t.x);
+        let x01 = lerp(corner(0, 0, 1)?, corner(1, 0, 1)?, t.x);
+        let x11 = lerp(corner(0, 1, 1)?, corner(1, 1, 1)?, t.x);
+        Some(lerp(lerp(x00, x10, t.y), lerp(x01, x11, t.y), t.z))
+    }
+
+    /// Registers a listener for chunk lifecycle events, see `poll_chunk_events`.
+    pub fn subscribe_chunk_events(&mut self) -> SubscriptionId {
+        self.chunk_events.subscribe()
+    }
+
+    pub fn unsubscribe_chunk_events(&mut self, id: SubscriptionId) {
+        self.chunk_events.unsubscribe(id);
+    }
+
+    /// Takes the chunk events published since the subscriber last polled.
+    pub fn poll_chunk_events(&mut self, id: SubscriptionId) -> Vec<ChunkEvent> {
+        self.chunk_events.poll(id)
+    }
+
+    /// Lifecycle state of the chunk, or None if it is neither loaded nor queued.
+    pub fn chunk_state(&self, position: IVec3) -> Option<ChunkState> {
+        if let Some(chunk) = self.chunks.get(&position) {
+            Some(chunk.state)
+        } else if self.chunk_generation_queue.contains(&position) {
+            Some(ChunkState::Queued)
+        } else {
+            None
         }
     }
 
+    /// Returns the terrain timings gathered since the last call and starts over.
+    pub fn take_timings(&mut self) -> TerrainTimings {
+        std::mem::take(&mut self.timings)
+    }
+
+    /// Chunk positions waiting for generation, next one first.
+    pub fn queued_chunks(&self) -> impl Iterator<Item = &IVec3> {
+        self.chunk_generation_queue.iter()
+    }
+
+    /// True while chunks are queued or still waiting for their first mesh.
+    pub fn is_generating(&self) -> bool {
+        !self.chunk_generation_queue.is_empty() || !self.unmeshed_chunks.is_empty()
+    }
+
     pub fn get_active_chunks_count(&self) -> usize {
         self.chunks.len()
     }
@@ -70,7 +153,16 @@ impl TerrainManager {
         self.chunks.values().filter(|chunk| chunk.is_empty).count()
     }
 
+    /// Generates the chunk and builds its mesh right away.
     pub fn generate_chunk(&mut self, position: IVec3) {
+        self.load_chunk(position);
+        self.mesh_loaded_chunk(position);
+    }
+
+    /// Generates the chunk's density field and inserts it as `Generating`. The mesh is
+    /// built later by `mesh_loaded_chunk`. A chunk that is already loaded keeps being drawn
+    /// until its replacement has a mesh.
+    fn load_chunk(&mut self, position: IVec3) {
         // Calculate distance from origin (0,0,0)
         let distance = position.as_vec3().length();
         
@@ -87,23 +179,123 @@ impl TerrainManager {
         };
         lod_level = 1;
         
-        let chunk = TerrainChunk::generate(
-            position,
-            self.chunk_size,
-            self.seed,
-            &self.data_tables,
-            self.isolevel,
-            lod_level
-        );
+        let generation_start = Instant::now();
+        let scalar_data = ScalarGenerator::generate(position, self.seed, self.chunk_size, &self.material_strata);
+        self.timings.generation += generation_start.elapsed();
+        self.timings.chunks_generated += 1;
     
-        self.chunks.insert(position, chunk);
+        let chunk = TerrainChunk::new(position, scalar_data, lod_level);
+        match self.chunks.entry(position) {
+            Entry::Occupied(_) => {
+                self.replacements.insert(position, chunk);
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(chunk);
+                self.dirty_regions.remove(&position);
+            }
+        }
+        if !self.unmeshed_chunks.contains(&position) {
+            self.unmeshed_chunks.push_back(position);
+        }
+        self.chunk_events.publish(ChunkEvent::Loaded(position));
+    }
+
+    /// Builds the first mesh of a `Generating` chunk. Edits made since it was loaded are
+    /// part of the density field already, so their dirty region is dropped. A replacement
+    /// is swapped in once meshed, unless the chunk it replaces was edited in the meantime.
+    fn mesh_loaded_chunk(&mut self, position: IVec3) {
+        self.unmeshed_chunks.retain(|unmeshed| *unmeshed != position);
+        if let Some(replacement) = self.replacements.remove(&position) {
+            if self.chunks.get(&position).is_some_and(|chunk| chunk.modified) {
+                return;
+            }
+            self.chunks.insert(position, replacement);
+        }
+        let Some(chunk) = self.chunks.get_mut(&position) else {
+            return;
+        };
+
+        let meshing_start = Instant::now();
+        chunk.remesh_chunk(&self.data_tables, self.isolevel, chunk.lod);
+        self.timings.meshing += meshing_start.elapsed();
+        self.timings.chunks_meshed += 1;
+
+        chunk.state = ChunkState::Meshed;
+        self.dirty_regions.remove(&position);
+        self.chunk_events.publish(ChunkEvent::Meshed(position));
+    }
+
+    /// Removes a chunk from the world and discards its pending edits.
+    pub fn unload_chunk(&mut self, position: IVec3) {
+        if self.chunks.remove(&position).is_some() {
+            self.unmeshed_chunks.retain(|unmeshed| *unmeshed != position);
+            self.replacements.remove(&position);
+            self.dirty_regions.remove(&position);
+            self.chunk_events.publish(ChunkEvent::Unloaded(position));
+        }
     }
 
     pub fn clear_chunks(&mut self) {
-        self.chunks.clear();
+        let positions: Vec<IVec3> = self.chunks.keys().copied().collect();
+        for position in positions {
+            self.unload_chunk(position);
+        }
         self.chunk_generation_queue.clear();
     }
 
+    /// Switches to new generator settings. Loaded chunks that were never edited are queued
+    /// for regeneration, nearest to `center` first; edited chunks keep their terrain.
+    ///
+    /// A chunk size change invalidates every chunk. The world is cleared and the area that
+    /// was loaded or queued around `center` is queued again in chunks of the new size.
+    /// Returns how many chunks were queued.
+    pub fn apply_generator_config(&mut self, config: &TerrainGeneratorConfig, center: Vec3) -> usize {
+        self.seed = config.seed;
+        self.isolevel = config.isolevel;
+        self.material_strata = config.material_strata.clone();
+
+        if config.chunk_size != self.chunk_size {
+            println!("Chunk size changed from {} to {}, regenerating every chunk", self.chunk_size, config.chunk_size);
+
+            // Furthest loaded or queued chunk from the center, in old chunks
+            let old_size = self.chunk_size as u32;
+            let old_center = self.world_to_chunk_position(center);
+            let loaded_radius = self.chunks
+                .keys()
+                .chain(self.chunk_generation_queue.iter())
+                .map(|position| (*position - old_center).abs().max_element())
+                .max();
+
+            self.clear_chunks();
+            self.chunk_size = config.chunk_size;
+
+            let Some(loaded_radius) = loaded_radius else {
+                return 0;
+            };
+            // The center lies inside its chunk, so this covers everything that was loaded
+            let world_radius = (loaded_radius as u32 + 1) * old_size;
+            let radius = world_radius.div_ceil(config.chunk_size as u32) as i32;
+            self.enqueue_chunks_in_radius(self.world_to_chunk_position(center), radius);
+            return self.chunk_generation_queue.len();
+        }
+
+        let center = self.world_to_chunk_position(center);
+        let mut positions: Vec<IVec3> = self.chunks
+            .values()
+            .filter(|chunk| !chunk.modified)
+            .map(|chunk| chunk.position)
+            .collect();
+
+        positions.sort_by_key(|position| (*position - center).length_squared());
+        self.chunk_generation_queue.retain(|queued| !positions.contains(queued));
+        for position in positions.iter().rev() {
+            self.chunk_generation_queue.push_front(*position);
+        }
+        self.chunk_generation_start_time = Some(Instant::now());
+
+        positions.len()
+    }
+
     pub fn force_generate_chunk(&mut self, position: IVec3) {
         if !self.chunks.contains_key(&position) {
             self.generate_chunk(position);
@@ -139,13 +331,28 @@ impl TerrainManager {
         self.chunk_generation_start_time = Some(Instant::now());
     }
 
+    /// Generates queued chunks until `generation_budget` is spent, at least one step per
+    /// call. Loading a chunk and building its first mesh are separate steps, a chunk whose
+    /// mesh didn't fit in the budget stays `Generating` until the next call.
     pub fn process_chunk_generation(&mut self) {
-        if let Some(position) = self.chunk_generation_queue.pop_front() {
-            self.generate_chunk(position);
+        let start_time = Instant::now();
+        loop {
+            // Finish chunks that are already loaded before starting new ones
+            if let Some(&position) = self.unmeshed_chunks.front() {
+                self.mesh_loaded_chunk(position);
+            } else if let Some(position) = self.chunk_generation_queue.pop_front() {
+                self.load_chunk(position);
+            } else {
+                break;
+            }
+
+            if start_time.elapsed() >= self.generation_budget {
+                break;
+            }
         }
 
         // Check if the queue is empty and stop the timer
-        if self.chunk_generation_queue.is_empty() {
+        if !self.is_generating() {
             if let Some(start_time) = self.chunk_generation_start_time.take() {
                 let duration = start_time.elapsed();
                 println!("Total time elapsed generating all chunks: {:?}", duration);
@@ -153,48 +360,9 @@ impl TerrainManager {
         }
     }
 
-    pub fn render(&mut self, window: &mut GlWindow, vp: Mat4) {
-        self.textures[0].bind(0); // Bind the first texture to texture unit 0
-        self.textures[1].bind(1);
-        self.textures[2].bind(2);
-        self.textures[3].bind(3);
-        self.textures[4].bind(4);
-        self.textures[5].bind(5);
-
-        self.terrain_shader.bind_program();
-        self.terrain_shader.set_uniform_3f("uLightDir", -0.8, 1.0, 0.5);
-        self.terrain_shader.set_uniform_texture("uGrassTex", 0);
-        self.terrain_shader.set_uniform_texture("uGrassNormal", 1);
-        self.terrain_shader.set_uniform_texture("uRockTex", 2);
-        self.terrain_shader.set_uniform_texture("uRockNormal",3);
-
-        for (_, chunk) in &self.chunks {
-            if chunk.is_empty {
-                continue;
-            }
-
-            let model = Mat4::from_translation(
-                Vec3::new(
-                    chunk.position.x as f32 * self.chunk_size as f32,
-                    chunk.position.y as f32 * self.chunk_size as f32,
-                    chunk.position.z as f32 * self.chunk_size as f32
-                ));
-            let mvp = vp * model;
-            self.terrain_shader.set_uniform_matrix_4fv("uMVP", &mvp.to_cols_array());
-
-            window.render_mesh(chunk.get_mesh());
-        }
-
-        self.terrain_shader.unbind_program();
-    }
-
     pub fn get_chunk_for_voxel(&mut self, pos: Vec3) -> Option<&mut TerrainChunk> {
         // Calculate the chunk position
-        let chunk_position = IVec3::new(
-            (pos.x / self.chunk_size as f32).floor() as i32,
-            (pos.y / self.chunk_size as f32).floor() as i32,
-            (pos.z / self.chunk_size as f32).floor() as i32,
-        );
+        let chunk_position = self.world_to_chunk_position(pos);
     
         // Ensure the chunk exists, generate it if necessary
         if !self.chunks.contains_key(&chunk_position) {
@@ -234,139 +402,151 @@ impl TerrainManager {
                 }
             }
         }
-        
-        self. remesh_all_chunks();
     }
 
-    pub fn place_voxel(&mut self, position: Vec3, delta: f32) {
-        // Track which chunks need remeshing
-        let mut chunks_to_remesh = HashSet::new();
-    
-        let chunk_size = self.chunk_size as usize;
-    
-        // Extract the values we need before any mutable borrows
-        let mut last_chunk_position = self.last_chunk_position.clone();
-        let mut last_local_position = self.last_local_position.clone();
-    
-        let target_position = position; // Use a mutable copy
-    
-        let mut local_position = IVec3::new(
-            (position.z.rem_euclid(chunk_size as f32)) as i32,
-            (position.y.rem_euclid(chunk_size as f32)) as i32,
-            (position.x.rem_euclid(chunk_size as f32)) as i32,
-        );
-
-        let mut chunk_position = IVec3::new(
-            (position.x / self.chunk_size as f32).floor() as i32,
-            (position.y / self.chunk_size as f32).floor() as i32,
-            (position.z / self.chunk_size as f32).floor() as i32,
-        );
-
-        if let Some(chunk) = self.chunks.get_mut(&chunk_position) {
-            let is_different = chunk.position != last_chunk_position || last_local_position != local_position;
-            if is_different {
+    /// Lists every chunk whose padded `ScalarData` contains the world voxel, paired with
+    /// the voxel's sample coordinate inside that chunk.
+    ///
+    /// Each chunk stores `chunk_size + ScalarGenerator::PADDING` samples per axis starting at
+    /// its origin, so a voxel near a chunk's lower border is also part of the padding of
+    /// the chunks below it. Up to eight chunks can share one voxel.
+    pub fn chunks_containing_voxel(voxel: IVec3, chunk_size: u16) -> Vec<(IVec3, IVec3)> {
+        let size = chunk_size as i32;
+        let owner = voxel.div_euclid(IVec3::splat(size));
+        let local = voxel.rem_euclid(IVec3::splat(size));
+
+        // Candidate (chunk, local) pairs along a single axis
+        let axis_candidates = |axis: usize| -> Vec<(i32, i32)> {
+            let mut candidates = vec![(owner[axis], local[axis])];
+            let mut chunk = owner[axis] - 1;
+            let mut sample = local[axis] + size;
+            while sample < size + ScalarGenerator::PADDING {
+                candidates.push((chunk, sample));
+                chunk -= 1;
+                sample += size;
+            }
+            candidates
+        };
 
+        let (xs, ys, zs) = (axis_candidates(0), axis_candidates(1), axis_candidates(2));
+        let mut result = Vec::with_capacity(xs.len() * ys.len() * zs.len());
+        for &(cx, lx) in &xs {
+            for &(cy, ly) in &ys {
+                for &(cz, lz) in &zs {
+                    result.push((IVec3::new(cx, cy, cz), IVec3::new(lx, ly, lz)));
+                }
             }
-    
-            last_chunk_position = chunk.position;
-            last_local_position = local_position;
-    
-            chunk.modify_terrain(local_position, delta); // Full density value
-            chunks_to_remesh.insert(chunk.position);
-        }
-    
-        // Check if any coordinate is zero
-       // println!("Local position:     {:?}", local_position);
-        if local_position.z == 0 {
-            chunk_position.x -= 1;
-            local_position.z = 64 as i32;
-        }
-        if local_position.y == 0 {
-            chunk_position.y -= 1;
-            local_position.y = 64 as i32;
-        }
-        if local_position.x == 0 {
-            chunk_position.z -= 1;
-            local_position.x = 64 as i32;
         }
+        result
+    }
 
-        if local_position.z == 1 {
-            chunk_position.x -= 1;
-            local_position.z = 65 as i32;
-        }
-        if local_position.y == 1 {
-            chunk_position.y -= 1;
-            local_position.y = 65 as i32;
-        }
-        if local_position.x == 1 {
-            chunk_position.z -= 1;
-            local_position.x = 65 as i32;
-        }
+    /// Adds `delta` to the density of the world voxel containing `position`.
+    ///
+    /// The edit is written into every loaded chunk that stores the voxel, including the
+    /// padding of neighbouring chunks, and the voxel is added to each chunk's dirty region.
+    /// Nothing is remeshed until `process_remeshing` or `remesh_all_chunks` runs.
+    pub fn place_voxel(&mut self, position: Vec3, delta: f32) {
+        let voxel = position.floor().as_ivec3();
 
-        if local_position.z == 2 {
-            chunk_position.x -= 1;
-            local_position.z = 66 as i32;
+        for (chunk_position, local_position) in Self::chunks_containing_voxel(voxel, self.chunk_size) {
+            if let Some(chunk) = self.chunks.get_mut(&chunk_position) {
+                chunk.modify_terrain(local_position, delta);
+                chunk.modified = true;
+                if chunk.state != ChunkState::Dirty {
+                    chunk.state = ChunkState::Dirty;
+                    self.chunk_events.publish(ChunkEvent::Modified(chunk_position));
+                }
+                self.dirty_regions
+                    .entry(chunk_position)
+                    .and_modify(|region| region.include(local_position))
+                    .or_insert_with(|| DirtyRegion::new(local_position));
+            }
         }
-        if local_position.y == 2 {
-            chunk_position.y -= 1;
-            local_position.y = 66 as i32;
+    }
+
+    /// Remeshes every chunk with pending edits, ignoring the frame budget.
+    pub fn remesh_all_chunks(&mut self) {
+        let dirty_regions: Vec<(IVec3, DirtyRegion)> = self.dirty_regions.drain().collect();
+
+        for (chunk_pos, region) in dirty_regions {
+            self.remesh_dirty_chunk(chunk_pos, &region);
         }
-        if local_position.x == 2 {
-            chunk_position.z -= 1;
-            local_position.x = 66 as i32;
+    }
+
+    /// Remeshes dirty chunks nearest to the camera first until `remesh_budget` is spent.
+    /// Edits made to a chunk before it is processed are coalesced into one remesh.
+    /// Returns how many chunks were remeshed.
+    pub fn process_remeshing(&mut self, camera_position: Vec3) -> usize {
+        if self.dirty_regions.is_empty() {
+            return 0;
         }
-        //println!("Local position new: {:?}", local_position);
-        //println!("Target position:    {:?}", target_position);
-    
-        if let Some(chunk) = self.chunks.get_mut(&chunk_position) {
-            let is_different = chunk.position != last_chunk_position || last_local_position != local_position;
-            if is_different {
 
+        let start_time = Instant::now();
+        let half_chunk = Vec3::splat(self.chunk_size as f32 * 0.5);
+
+        let mut queue: Vec<(f32, IVec3)> = self.dirty_regions
+            .keys()
+            .map(|&chunk_pos| {
+                let center = self.chunk_origin(chunk_pos) + half_chunk;
+                (center.distance_squared(camera_position), chunk_pos)
+            })
+            .collect();
+        queue.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut remeshed = 0;
+        for (_, chunk_pos) in queue {
+            // Always make progress on at least one chunk per tick
+            if remeshed > 0 && start_time.elapsed() >= self.remesh_budget {
+                break;
             }
-    
-            last_chunk_position = chunk.position;
-            last_local_position = local_position;
-    
-            chunk.modify_terrain(local_position, delta); // Full density value
-            chunks_to_remesh.insert(chunk.position);
-        }
-        self.last_chunk_position = last_chunk_position;
-        self.last_local_position = last_local_position;
-    
-        // Remesh affected chunks
-        for chunk_pos in chunks_to_remesh {
-            self.chunks_to_remesh.insert(chunk_pos);
-        }
-    }
 
-    pub fn remesh_all_chunks(&mut self) {
-        let chunks_to_remesh = self.chunks_to_remesh.clone();
-        self.chunks_to_remesh.clear();
-    
-        for chunk_pos in chunks_to_remesh {
-            if let Some(chunk) = self.chunks.get_mut(&chunk_pos) {
-                chunk.remesh_chunk(&self.data_tables, self.isolevel, 1);
+            if let Some(region) = self.dirty_regions.remove(&chunk_pos) {
+                if self.remesh_dirty_chunk(chunk_pos, &region) {
+                    remeshed += 1;
+                }
             }
         }
+
+        remeshed
     }
 
-    pub fn e (&mut self) {
-        self.get_active_chunks_count();
+    fn remesh_dirty_chunk(&mut self, chunk_pos: IVec3, region: &DirtyRegion) -> bool {
+        // The first mesh of a chunk that is still generating picks up the edits anyway
+        if self.unmeshed_chunks.contains(&chunk_pos) && !self.replacements.contains_key(&chunk_pos) {
+            return false;
+        }
+        let Some(chunk) = self.chunks.get_mut(&chunk_pos) else {
+            return false;
+        };
+
+        let start = Instant::now();
+        chunk.remesh_region(&self.data_tables, self.isolevel, region);
+        self.timings.meshing += start.elapsed();
+        self.timings.chunks_meshed += 1;
+        chunk.state = ChunkState::Meshed;
+        self.chunk_events.publish(ChunkEvent::Meshed(chunk_pos));
+        true
+    }
 
-        self.last_chunk_position = IVec3::new(0, 0, 0);
+    /// Number of chunks with edits that have not been remeshed yet.
+    pub fn get_dirty_chunks_count(&self) -> usize {
+        self.dirty_regions.len()
     }
 
     pub fn new_modify_terrain(&mut self, position: IVec3, delta: f32) {
-        if let Some(chunk) = self.get_chunk_for_voxel(position.as_vec3()) {
-            chunk.modify_terrain(position, delta);
-            //chunk.remesh_chunk(&self.data_tables, self.isolevel, 1);
+        // Make sure the owning chunk exists before editing
+        if self.get_chunk_for_voxel(position.as_vec3()).is_some() {
+            self.place_voxel(position.as_vec3(), delta);
         }
     }
 
+    /// Edits a voxel addressed by chunk and sample coordinate, then remeshes every
+    /// chunk that shares it.
     pub fn place_voxel_in_chunk(&mut self, chunk_position: IVec3, local_position: IVec3, density_delta: f32) {
-        if let Some(chunk) = self.chunks.get_mut(&chunk_position) {
-            chunk.modify_terrain(local_position, density_delta);
-            chunk.remesh_chunk(&self.data_tables, self.isolevel, 1);
+        if self.chunks.contains_key(&chunk_position) {
+            let voxel = chunk_position * self.chunk_size as i32 + local_position;
+            self.place_voxel(voxel.as_vec3(), density_delta);
+            self.remesh_all_chunks();
         } else {
             println!("Chunk at position {:?} does not exist.", chunk_position);
         }
@@ -380,11 +560,7 @@ impl TerrainManager {
             let current_position = ray.at(current_distance);
             
             // Get the chunk for the current voxel position
-            let chunk_position = IVec3::new(
-                (current_position.x / self.chunk_size as f32).floor() as i32,
-                (current_position.y / self.chunk_size as f32).floor() as i32,
-                (current_position.z / self.chunk_size as f32).floor() as i32,
-            );
+            let chunk_position = self.world_to_chunk_position(current_position);
 
             if let Some(chunk) = self.chunks.get_mut(&chunk_position) {
                 // Convert world position to local position within the chunk
@@ -402,8 +578,8 @@ impl TerrainManager {
                     // Get the density value at the local position
                     let density = chunk.scalar_data.get_value(local_position);
 
-                    // Check if the density is below the threshold
-                    if density > Some(0.5) {
+                    // Solid once the density passes the isolevel
+                    if density.is_some_and(|density| density > self.isolevel) {
                         return Some(current_position);
                     }
                 }
@@ -419,3 +595,17 @@ impl TerrainManager {
     }
 }
 
+
+impl DensityField for TerrainManager {
+    fn density(&self, position: Vec3) -> Option<f32> {
+        self.density_at(position)
+    }
+
+    fn isolevel(&self) -> f32 {
+        self.isolevel
+    }
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
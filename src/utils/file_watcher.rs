@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Polls modification times of a set of files. Cheap enough to call every frame, the
+/// file system is only touched once per `poll_interval`.
+pub struct FileWatcher {
+    files: HashMap<PathBuf, Option<SystemTime>>,
+    pub poll_interval: Duration,
+    last_poll: Option<Instant>,
+}
+
+impl FileWatcher {
+    pub fn new(poll_interval: Duration) -> Self {
+        FileWatcher {
+            files: HashMap::new(),
+            poll_interval,
+            last_poll: None,
+        }
+    }
+
+    /// Starts watching a file. Files that don't exist yet are reported once they appear.
+    pub fn watch_file<P: AsRef<Path>>(&mut self, path: P) {
+        let path = path.as_ref().to_path_buf();
+        let modified = Self::modified_time(&path);
+        self.files.entry(path).or_insert(modified);
+    }
+
+    pub fn unwatch_file<P: AsRef<Path>>(&mut self, path: P) {
+        self.files.remove(path.as_ref());
+    }
+
+    pub fn is_watching<P: AsRef<Path>>(&self, path: P) -> bool {
+        self.files.contains_key(path.as_ref())
+    }
+
+    /// Files written since the last check, or nothing if the poll interval hasn't passed.
+    pub fn poll(&mut self) -> Vec<PathBuf> {
+        if let Some(last_poll) = self.last_poll
+            && last_poll.elapsed() < self.poll_interval
+        {
+            return Vec::new();
+        }
+        self.last_poll = Some(Instant::now());
+        self.check()
+    }
+
+    /// Files written since the last check, ignoring the poll interval. Deleted files are
+    /// not reported, they show up again when they are recreated.
+    pub fn check(&mut self) -> Vec<PathBuf> {
+        let mut changed = Vec::new();
+        for (path, last_modified) in self.files.iter_mut() {
+            let modified = Self::modified_time(path);
+            if modified != *last_modified {
+                *last_modified = modified;
+                if modified.is_some() {
+                    changed.push(path.clone());
+                }
+            }
+        }
+        changed.sort();
+        changed
+    }
+
+    fn modified_time(path: &Path) -> Option<SystemTime> {
+        std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+    }
+}
@@ -0,0 +1,43 @@
+/// Turns variable frame times into a whole number of fixed simulation ticks. Leftover
+/// time carries over to the next frame, `alpha` says how far between the last two ticks
+/// the rendered frame is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FixedTimestep {
+    /// Seconds per simulation tick
+    pub tick: f32,
+    /// Ticks run at most per frame, a long stall drops time instead of spiralling
+    pub max_ticks_per_frame: u32,
+    accumulator: f32,
+}
+
+impl FixedTimestep {
+    pub fn new(ticks_per_second: f32) -> Self {
+        FixedTimestep {
+            tick: 1.0 / ticks_per_second,
+            max_ticks_per_frame: 8,
+            accumulator: 0.0,
+        }
+    }
+
+    /// Adds a frame's time and returns how many ticks to simulate.
+    pub fn advance(&mut self, frame_seconds: f32) -> u32 {
+        self.accumulator += frame_seconds.max(0.0);
+
+        let mut ticks = 0;
+        while self.accumulator >= self.tick && ticks < self.max_ticks_per_frame {
+            self.accumulator -= self.tick;
+            ticks += 1;
+        }
+
+        // Dropped time is gone for good, the simulation slows down instead of catching up
+        if ticks == self.max_ticks_per_frame {
+            self.accumulator = self.accumulator.min(self.tick);
+        }
+        ticks
+    }
+
+    /// Fraction of a tick left over, for interpolating between the last two ticks.
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.tick).clamp(0.0, 1.0)
+    }
+}
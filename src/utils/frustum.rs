@@ -0,0 +1,52 @@
+use glam::{Mat4, Vec3, Vec4};
+
+use super::aabb::Aabb;
+
+/// View frustum as six inward-facing planes (`xyz` normal, `w` distance).
+#[derive(Debug, Clone, Copy)]
+pub struct Frustum {
+    pub planes: [Vec4; 6],
+}
+
+impl Frustum {
+    /// Extracts the planes of a combined projection * view matrix (Gribb-Hartmann).
+    ///
+    /// Assumes OpenGL clip space (`-w <= z <= w`). For projections with a `0..w` depth
+    /// range the near plane ends up slightly looser, which only makes culling conservative.
+    pub fn from_matrix(view_projection: Mat4) -> Self {
+        let row0 = view_projection.row(0);
+        let row1 = view_projection.row(1);
+        let row2 = view_projection.row(2);
+        let row3 = view_projection.row(3);
+
+        let planes = [
+            row3 + row0, // left
+            row3 - row0, // right
+            row3 + row1, // bottom
+            row3 - row1, // top
+            row3 + row2, // near
+            row3 - row2, // far
+        ]
+        .map(|plane| plane / plane.truncate().length());
+
+        Self { planes }
+    }
+
+    /// Signed distance from a plane to a point, positive on the inside
+    fn distance(plane: Vec4, point: Vec3) -> f32 {
+        plane.truncate().dot(point) + plane.w
+    }
+
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        self.planes.iter().all(|&plane| Self::distance(plane, point) >= 0.0)
+    }
+
+    /// Returns false only if the box is entirely outside one of the planes
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|&plane| {
+            // Corner of the box furthest along the plane normal
+            let positive = Vec3::select(plane.truncate().cmpge(Vec3::ZERO), aabb.max, aabb.min);
+            Self::distance(plane, positive) >= 0.0
+        })
+    }
+}
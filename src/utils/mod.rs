@@ -1 +1,6 @@
-pub mod ray;
\ No newline at end of file
+pub mod aabb;
+pub mod file_watcher;
+pub mod fixed_timestep;
+pub mod frustum;
+pub mod ray;
+pub mod timing_stats;
\ No newline at end of file
@@ -0,0 +1,60 @@
+use std::path::Path;
+
+use fallendust::assets::asset_manager::{AssetError, AssetManager, ImageKind};
+use fallendust::assets::placeholder;
+use fallendust::language::Language;
+
+fn asset_manager() -> AssetManager {
+    AssetManager::new(Language::load_from_file("./assets/data/language.json").unwrap())
+}
+
+#[test]
+fn missing_image_is_reported_and_replaced() {
+    let mut assets = asset_manager();
+    let handle = assets.load_image("./assets/media/textures/does_not_exist.png", ImageKind::Normal);
+
+    assert!(assets.is_placeholder(handle));
+    assert_eq!(assets.image(handle).get_pixel(0, 0), placeholder::flat_normal(1).get_pixel(0, 0));
+    assert_eq!(
+        assets.errors()[0].message(assets.language()),
+        "Asset 'does_not_exist.png' not found at expected location: ./assets/media/textures/does_not_exist.png"
+    );
+}
+
+#[test]
+fn corrupt_image_is_reported_and_replaced() {
+    let path = std::env::temp_dir().join("fallendust_corrupt_texture.png");
+    std::fs::write(&path, b"definitely not a png").unwrap();
+
+    let mut assets = asset_manager();
+    let handle = assets.load_image(&path, ImageKind::Color);
+
+    assert!(assets.is_placeholder(handle));
+    match &assets.errors()[0] {
+        AssetError::Corrupted { asset, .. } => assert_eq!(asset, "fallendust_corrupt_texture.png"),
+        error => panic!("expected a corrupted asset error, got {:?}", error),
+    }
+    assert!(assets.errors()[0]
+        .message(assets.language())
+        .starts_with("Asset 'fallendust_corrupt_texture.png' is corrupted and cannot be loaded. Error: "));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn existing_image_loads_once() {
+    let mut assets = asset_manager();
+    let path = Path::new("./assets/media/textures/test.png");
+    let first = assets.load_image(path, ImageKind::Color);
+    let second = assets.load_image(path, ImageKind::Color);
+
+    assert_eq!(first, second);
+    assert!(!assets.is_placeholder(first));
+    assert!(assets.errors().is_empty());
+}
+
+#[test]
+fn missing_language_keys_keep_their_arguments() {
+    let language = Language::default();
+    assert_eq!(language.format("assets.errors.not_found", &[("asset", "a.png")]), "assets.errors.not_found (asset=a.png)");
+}
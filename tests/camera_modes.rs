@@ -0,0 +1,150 @@
+use fallendust::camera_controller::{CameraController, CameraInput, CameraMode};
+use fallendust::physics::density_field::DensityField;
+use fallendust::renderer::shadow_cascades::CascadedShadowMap;
+use glam::Vec3;
+
+const TICK: f32 = 1.0 / 60.0;
+
+/// Orbit and top-down modes don't collide, so no terrain is needed.
+struct NoTerrain;
+
+impl DensityField for NoTerrain {
+    fn density(&self, _position: Vec3) -> Option<f32> {
+        None
+    }
+
+    fn isolevel(&self) -> f32 {
+        0.5
+    }
+}
+
+fn run(camera: &mut CameraController, input: CameraInput, seconds: f32) {
+    for _ in 0..(seconds / TICK).ceil() as usize {
+        camera.advance(&input, &NoTerrain, TICK);
+    }
+}
+
+fn movement(movement: Vec3) -> CameraInput {
+    CameraInput { movement, ..Default::default() }
+}
+
+#[test]
+fn orbit_circles_the_pivot() {
+    let mut camera = CameraController::new(16.0 / 9.0);
+    let pivot = Vec3::new(10.0, 5.0, -20.0);
+    camera.orbit_around(pivot);
+    run(&mut camera, CameraInput::default(), 1.0);
+
+    assert_eq!(camera.mode, CameraMode::Orbit);
+    assert_eq!(camera.target, pivot);
+    assert!((camera.position.distance(pivot) - camera.orbit.distance).abs() < 1e-3);
+
+    // Rotating keeps the distance
+    camera.look(200.0, -50.0);
+    assert!((camera.position.distance(pivot) - camera.orbit.distance).abs() < 1e-3);
+    assert_eq!(camera.target, pivot);
+
+    // Forward zooms in, panning moves the pivot sideways
+    let distance = camera.orbit.distance;
+    run(&mut camera, movement(Vec3::Z), 0.5);
+    assert!(camera.orbit.distance < distance * 0.5);
+    run(&mut camera, movement(Vec3::X), 0.5);
+    assert!(camera.orbit.pivot.distance(pivot) > 1.0);
+    assert!((camera.position.distance(camera.orbit.pivot) - camera.orbit.distance).abs() < 1e-3);
+}
+
+#[test]
+fn top_down_is_orthographic_and_looks_straight_down() {
+    let mut camera = CameraController::new(1.0);
+    camera.set_mode(CameraMode::TopDown);
+    run(&mut camera, CameraInput::default(), 1.0);
+
+    let direction = (camera.target - camera.position).normalize();
+    assert!(direction.abs_diff_eq(Vec3::NEG_Y, 1e-5));
+
+    // Points above each other land on the same pixel
+    let view_projection = camera.get_vp();
+    let center = camera.top_down.center;
+    let low = view_projection.project_point3(center + Vec3::new(5.0, -20.0, 3.0));
+    let high = view_projection.project_point3(center + Vec3::new(5.0, 20.0, 3.0));
+    assert!((low.x - high.x).abs() < 1e-5 && (low.y - high.y).abs() < 1e-5);
+
+    // The extent is half the view height, with north at the top
+    let north = view_projection.project_point3(center + Vec3::new(0.0, 0.0, -camera.top_down.extent));
+    assert!((north.y - 1.0).abs() < 1e-4, "north edge at {}", north);
+
+    // Up zooms out
+    let extent = camera.top_down.extent;
+    run(&mut camera, movement(Vec3::Y), 0.5);
+    assert!(camera.top_down.extent > extent);
+}
+
+#[test]
+fn modes_keep_their_own_state() {
+    let mut camera = CameraController::new(1.0);
+    let start = camera.position;
+
+    camera.orbit_around(Vec3::new(0.0, 0.0, -30.0));
+    run(&mut camera, movement(Vec3::Z), 0.3);
+    let orbit = camera.orbit;
+
+    camera.set_mode(CameraMode::TopDown);
+    run(&mut camera, movement(Vec3::X), 0.3);
+    let top_down = camera.top_down;
+
+    camera.set_mode(CameraMode::FirstPerson);
+    run(&mut camera, CameraInput::default(), 1.0);
+    assert!(camera.position.distance(start) < 1e-4, "first person moved to {}", camera.position);
+
+    camera.set_mode(CameraMode::Orbit);
+    assert_eq!(camera.orbit, orbit);
+    camera.set_mode(CameraMode::TopDown);
+    assert_eq!(camera.top_down, top_down);
+}
+
+#[test]
+fn mode_switches_are_smooth() {
+    let mut camera = CameraController::new(1.0);
+    run(&mut camera, CameraInput::default(), 0.1);
+    let before = camera.interpolated(1.0);
+
+    camera.set_mode(CameraMode::TopDown);
+
+    // The first rendered frame still shows the old view
+    let first = camera.interpolated(0.0);
+    assert!(first.position.distance(before.position) < 1e-4);
+    assert!(first.get_projection().abs_diff_eq(before.get_projection(), 1e-5));
+
+    // No jumps along the way
+    let mut previous = first.position;
+    let total = before.position.distance(camera.position);
+    for _ in 0..(camera.transition_duration / TICK) as usize + 2 {
+        run(&mut camera, CameraInput::default(), TICK);
+        for alpha in [0.5, 1.0] {
+            let rendered = camera.interpolated(alpha);
+            assert!(rendered.position.distance(previous) < total * 0.1, "jumped to {}", rendered.position);
+            previous = rendered.position;
+        }
+    }
+
+    // Ends exactly on the new mode's view
+    assert!(camera.transition.is_none());
+    let after = camera.interpolated(1.0);
+    assert_eq!(after.position, camera.position);
+    assert_eq!(after.get_projection(), camera.get_projection());
+    assert_eq!(after.get_projection().w_axis.w, 1.0, "top-down should be orthographic");
+}
+
+#[test]
+fn shadow_slices_follow_the_orthographic_view() {
+    let mut camera = CameraController::new(1.0);
+    camera.set_mode(CameraMode::TopDown);
+    run(&mut camera, CameraInput::default(), 1.0);
+
+    // An orthographic slice is a box, its near and far faces are the same size
+    let corners = CascadedShadowMap::frustum_slice_corners(&camera, 10.0, 100.0);
+    let near_width = corners[0].distance(corners[1]);
+    let far_width = corners[4].distance(corners[5]);
+    assert!((near_width - far_width).abs() < 1e-2);
+    assert!((near_width - 2.0 * camera.top_down.extent).abs() < 1e-2);
+}
@@ -0,0 +1,179 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use fallendust::benchmark::{BenchmarkReport, FrameTiming};
+use fallendust::camera_controller::{CameraController, CameraInput, CameraMode};
+use fallendust::camera_path::{default_camera_path, CameraPath, CameraPathSample};
+use fallendust::headless;
+use fallendust::terrain::terrain_generator_config::TerrainGeneratorConfig;
+use fallendust::terrain::terrain_manager::{TerrainManager, TerrainTimings};
+use fallendust::utils::timing_stats::{percentile, TimingSummary};
+use glam::{IVec3, Vec3};
+
+const TICK: f32 = 1.0 / 60.0;
+
+fn args(text: &str) -> Vec<String> {
+    std::iter::once("fallendust").chain(text.split_whitespace()).map(String::from).collect()
+}
+
+#[test]
+fn samples_replay_the_recorded_view() {
+    let mut camera = CameraController::new(16.0 / 9.0);
+    let mut path = CameraPath::new(60.0, TerrainGeneratorConfig::default());
+    let input = CameraInput { movement: Vec3::new(0.3, 0.0, 1.0), ..Default::default() };
+    let mut views = Vec::new();
+    for tick in 0..30 {
+        camera.look(4.0, 1.0);
+        camera.simulate(&input, TICK);
+        path.record(&camera);
+        views.push(camera.get_vp());
+        if tick == 20 {
+            camera.set_mode(CameraMode::TopDown);
+        }
+    }
+    assert_eq!(path.samples.len(), 30);
+    assert_eq!(path.duration_seconds(), 0.5);
+
+    // A perspective sample reproduces the view exactly
+    let mut replay = CameraController::new(16.0 / 9.0);
+    path.samples[10].apply(&mut replay);
+    assert!(replay.get_vp().abs_diff_eq(views[10], 1e-5));
+
+    // Top-down samples bring back the orthographic projection
+    let last = path.samples.last().unwrap();
+    assert_eq!(last.orthographic_extent, Some(camera.top_down.extent));
+    last.apply(&mut replay);
+    assert_eq!(replay.mode, CameraMode::TopDown);
+    assert!(replay.transition.is_none());
+    assert_eq!(replay.get_projection().w_axis.w, 1.0);
+}
+
+#[test]
+fn camera_paths_round_trip_through_json() {
+    let mut path = CameraPath::new(60.0, TerrainGeneratorConfig::default());
+    let camera = CameraController::new(1.0);
+    path.record(&camera);
+    path.samples.push(CameraPathSample { orthographic_extent: Some(32.0), ..path.samples[0] });
+
+    let directory = std::env::temp_dir().join(format!("fallendust_camera_path_{}", std::process::id()));
+    let file = directory.join("nested").join("path.json");
+    path.save_to_file(&file).unwrap();
+    assert_eq!(CameraPath::load_from_file(&file).unwrap(), path);
+
+    std::fs::write(&file, "{\"ticks_per_second\": 0.0, \"samples\": []}").unwrap();
+    assert!(CameraPath::load_from_file(&file).is_err());
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn default_camera_path_is_timestamped() {
+    let time = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    assert_eq!(
+        default_camera_path(time),
+        std::path::Path::new("camera_paths").join("camera_path_1700000000.json")
+    );
+}
+
+#[test]
+fn percentiles_use_the_nearest_rank() {
+    let samples: Vec<f64> = (1..=100).map(f64::from).collect();
+    assert_eq!(percentile(&samples, 95.0), 95.0);
+    assert_eq!(percentile(&samples, 99.0), 99.0);
+    assert_eq!(percentile(&samples, 100.0), 100.0);
+    assert_eq!(percentile(&[4.0], 99.0), 4.0);
+    assert_eq!(percentile(&[], 50.0), 0.0);
+
+    // Order doesn't matter, one slow frame shows up in the tail but not the average
+    let mut frames = vec![10.0; 99];
+    frames.insert(40, 100.0);
+    let summary = TimingSummary::from_samples(&frames);
+    assert_eq!(summary.count, 100);
+    assert!((summary.average - 10.9).abs() < 1e-9);
+    assert_eq!(summary.p95, 10.0);
+    assert_eq!(summary.p99, 10.0);
+    assert_eq!(summary.max, 100.0);
+    assert_eq!(TimingSummary::from_samples(&[]), TimingSummary::default());
+}
+
+#[test]
+fn reports_summarize_every_frame() {
+    let terrain = TerrainTimings {
+        generation: Duration::from_millis(3),
+        meshing: Duration::from_micros(1500),
+        chunks_generated: 2,
+        chunks_meshed: 2,
+    };
+    let mut frames = vec![FrameTiming::new(0.010, &TerrainTimings::default()); 9];
+    frames.push(FrameTiming::new(0.020, &terrain));
+
+    let report = BenchmarkReport::new(frames);
+    assert_eq!(report.chunks_generated, 2);
+    assert!((report.frame.average - 11.0).abs() < 1e-9);
+    assert!((report.frame.p99 - 20.0).abs() < 1e-9);
+    assert!((report.generation.max - 3.0).abs() < 1e-9);
+    assert!((report.meshing.average - 0.15).abs() < 1e-9);
+
+    let text = report.to_string();
+    assert!(text.contains("10 frames"), "{}", text);
+    assert!(text.contains("p95") && text.contains("p99"), "{}", text);
+}
+
+#[test]
+fn playback_arguments_are_parsed() {
+    let options = headless::parse_args(&args("--offscreen out.png --play flight.json --report report.json"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(options.camera_path, Some("flight.json".into()));
+    assert_eq!(options.report, Some("report.json".into()));
+
+    let still = headless::parse_args(&args("--offscreen out.png")).unwrap().unwrap();
+    assert_eq!(still.camera_path, None);
+
+    assert!(headless::parse_args(&args("--offscreen out.png --report report.json")).is_err());
+}
+
+#[test]
+fn terrain_work_is_timed() {
+    let config = TerrainGeneratorConfig { chunk_size: 32, ..Default::default() };
+    let mut terrain_manager = TerrainManager::new(&config);
+    terrain_manager.generate_chunk(IVec3::ZERO);
+    terrain_manager.place_voxel(Vec3::new(16.0, 16.0, 16.0), -0.5);
+    terrain_manager.remesh_all_chunks();
+
+    let timings = terrain_manager.take_timings();
+    assert_eq!(timings.chunks_generated, 1);
+    assert_eq!(timings.chunks_meshed, 2);
+    assert!(timings.generation > Duration::ZERO);
+    assert!(timings.meshing > Duration::ZERO);
+    assert_eq!(terrain_manager.take_timings(), TerrainTimings::default());
+}
+
+#[test]
+fn playback_generates_a_fixed_number_of_chunks_per_tick() {
+    let config = TerrainGeneratorConfig { chunk_size: 16, ..Default::default() };
+    let mut terrain_manager = TerrainManager::new(&config);
+    terrain_manager.generation_budget = Duration::from_secs(60);
+    let mut loaded_around = None;
+
+    // Loading and meshing a chunk are one step each
+    let chunks_per_tick = headless::PLAYBACK_GENERATION_STEPS_PER_TICK / 2;
+    for tick in 1..=3 {
+        headless::step_playback_terrain(&mut terrain_manager, &mut loaded_around, Vec3::splat(8.0), 1);
+        assert_eq!(terrain_manager.chunks.len(), tick * chunks_per_tick);
+    }
+    assert_eq!(loaded_around, Some(IVec3::ZERO));
+
+    while terrain_manager.is_generating() {
+        headless::step_playback_terrain(&mut terrain_manager, &mut loaded_around, Vec3::splat(8.0), 1);
+    }
+    assert_eq!(terrain_manager.chunks.len(), 27);
+}
+
+#[test]
+fn camera_paths_without_a_generator_config_still_load() {
+    let file = std::env::temp_dir().join(format!("fallendust_old_camera_path_{}.json", std::process::id()));
+    std::fs::write(&file, "{\"ticks_per_second\": 60.0, \"samples\": []}").unwrap();
+
+    let path = CameraPath::load_from_file(&file).unwrap();
+    assert_eq!(path.generator_config, None);
+    std::fs::remove_file(&file).unwrap();
+}
@@ -0,0 +1,117 @@
+use std::time::Duration;
+
+use fallendust::terrain::chunk_lifecycle::{ChunkEvent, ChunkEventBus, ChunkState, MAX_QUEUED_CHUNK_EVENTS};
+use fallendust::terrain::terrain_generator_config::TerrainGeneratorConfig;
+use fallendust::TerrainManager;
+use glam::{IVec3, Vec3};
+
+/// Terrain manager that does one generation step per `process_chunk_generation` call.
+fn stepped_terrain_manager() -> TerrainManager {
+    let config = TerrainGeneratorConfig { chunk_size: 32, ..Default::default() };
+    let mut terrain_manager = TerrainManager::new(&config);
+    terrain_manager.generation_budget = Duration::ZERO;
+    terrain_manager
+}
+
+#[test]
+fn chunks_move_through_every_state() {
+    let mut terrain_manager = stepped_terrain_manager();
+    let subscription = terrain_manager.subscribe_chunk_events();
+    let position = IVec3::new(0, -1, 0);
+
+    terrain_manager.enqueue_chunks_in_radius(position, 0);
+    assert_eq!(terrain_manager.chunk_state(position), Some(ChunkState::Queued));
+    assert!(terrain_manager.is_generating());
+
+    // Loading and meshing take one step each
+    terrain_manager.process_chunk_generation();
+    assert_eq!(terrain_manager.chunk_state(position), Some(ChunkState::Generating));
+    assert_eq!(terrain_manager.poll_chunk_events(subscription), vec![ChunkEvent::Loaded(position)]);
+
+    terrain_manager.process_chunk_generation();
+    assert_eq!(terrain_manager.chunk_state(position), Some(ChunkState::Meshed));
+    assert_eq!(terrain_manager.poll_chunk_events(subscription), vec![ChunkEvent::Meshed(position)]);
+    assert!(!terrain_manager.is_generating());
+
+    // Edits before the next remesh are reported once
+    terrain_manager.place_voxel(Vec3::new(16.0, -16.0, 16.0), -0.5);
+    terrain_manager.place_voxel(Vec3::new(17.0, -16.0, 16.0), -0.5);
+    assert_eq!(terrain_manager.chunk_state(position), Some(ChunkState::Dirty));
+    assert_eq!(terrain_manager.poll_chunk_events(subscription), vec![ChunkEvent::Modified(position)]);
+
+    assert_eq!(terrain_manager.process_remeshing(Vec3::ZERO), 1);
+    assert_eq!(terrain_manager.chunk_state(position), Some(ChunkState::Meshed));
+    assert_eq!(terrain_manager.poll_chunk_events(subscription), vec![ChunkEvent::Meshed(position)]);
+
+    terrain_manager.unload_chunk(position);
+    assert_eq!(terrain_manager.chunk_state(position), None);
+    assert_eq!(terrain_manager.poll_chunk_events(subscription), vec![ChunkEvent::Unloaded(position)]);
+}
+
+#[test]
+fn edits_while_generating_are_meshed_with_the_first_mesh() {
+    let mut terrain_manager = stepped_terrain_manager();
+    let subscription = terrain_manager.subscribe_chunk_events();
+    let position = IVec3::new(0, -1, 0);
+
+    terrain_manager.enqueue_chunks_in_radius(position, 0);
+    terrain_manager.process_chunk_generation();
+    terrain_manager.place_voxel(Vec3::new(16.0, -16.0, 16.0), -0.5);
+    assert_eq!(terrain_manager.chunk_state(position), Some(ChunkState::Dirty));
+
+    // Remeshing leaves the chunk to generation, which builds the whole mesh
+    assert_eq!(terrain_manager.process_remeshing(Vec3::ZERO), 0);
+    terrain_manager.process_chunk_generation();
+    assert_eq!(terrain_manager.chunk_state(position), Some(ChunkState::Meshed));
+    assert_eq!(terrain_manager.get_dirty_chunks_count(), 0);
+
+    terrain_manager.unload_chunk(position);
+    assert_eq!(
+        terrain_manager.poll_chunk_events(subscription),
+        vec![
+            ChunkEvent::Loaded(position),
+            ChunkEvent::Modified(position),
+            ChunkEvent::Meshed(position),
+            ChunkEvent::Unloaded(position),
+        ]
+    );
+}
+
+#[test]
+fn unloading_a_generating_chunk_cancels_its_mesh() {
+    let mut terrain_manager = stepped_terrain_manager();
+    let subscription = terrain_manager.subscribe_chunk_events();
+    let position = IVec3::new(0, -1, 0);
+
+    terrain_manager.enqueue_chunks_in_radius(position, 0);
+    terrain_manager.process_chunk_generation();
+    terrain_manager.unload_chunk(position);
+    assert!(!terrain_manager.is_generating());
+
+    terrain_manager.process_chunk_generation();
+    assert_eq!(terrain_manager.chunk_state(position), None);
+    assert_eq!(
+        terrain_manager.poll_chunk_events(subscription),
+        vec![ChunkEvent::Loaded(position), ChunkEvent::Unloaded(position)]
+    );
+}
+
+#[test]
+fn full_queues_drop_the_oldest_events() {
+    let mut bus = ChunkEventBus::new();
+    let idle = bus.subscribe();
+    let polling = bus.subscribe();
+
+    for x in 0..MAX_QUEUED_CHUNK_EVENTS as i32 + 10 {
+        bus.publish(ChunkEvent::Loaded(IVec3::new(x, 0, 0)));
+        if x == 0 {
+            assert_eq!(bus.poll(polling), vec![ChunkEvent::Loaded(IVec3::ZERO)]);
+        }
+    }
+
+    let events = bus.poll(idle);
+    assert_eq!(events.len(), MAX_QUEUED_CHUNK_EVENTS);
+    assert_eq!(events[0], ChunkEvent::Loaded(IVec3::new(10, 0, 0)));
+    assert_eq!(bus.poll(polling).len(), MAX_QUEUED_CHUNK_EVENTS);
+    assert!(bus.poll(idle).is_empty());
+}
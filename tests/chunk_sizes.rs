@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use fallendust::terrain::marching_cubes::marching_cubes_generator::VERTEX_STRIDE;
+use fallendust::terrain::terrain_generator_config::{MaterialStrata, SUPPORTED_CHUNK_SIZES};
+use fallendust::{MarchingCubesDataTables, MarchingCubesGenerator, ScalarData, ScalarGenerator, TerrainManager};
+use glam::IVec3;
+
+const ISOLEVEL: f32 = 0.5;
+
+fn data_tables() -> MarchingCubesDataTables {
+    MarchingCubesDataTables::load_from_files("./assets/data/marching_cubes_tables/").unwrap()
+}
+
+/// Zero-filled scalar field with the same layout the generator produces.
+fn empty_scalar_data(chunk_size: u16) -> ScalarData {
+    let dimensions = IVec3::splat(chunk_size as i32 + ScalarGenerator::PADDING);
+    let count = (dimensions.x * dimensions.y * dimensions.z) as usize;
+    ScalarData {
+        grid: vec![[0.0; 3]; count],
+        values: vec![0.0; count],
+        materials: vec![0; count],
+        dimensions,
+    }
+}
+
+#[test]
+fn generation_covers_chunk_at_every_size() {
+    let data_tables = data_tables();
+
+    for chunk_size in SUPPORTED_CHUNK_SIZES {
+        // The surface crosses y = 0, so the chunk just below it is never empty
+        let position = IVec3::new(0, -1, 0);
+        let scalar_data = ScalarGenerator::generate(position, 0, chunk_size, &MaterialStrata::default());
+        assert_eq!(scalar_data.dimensions, IVec3::splat(chunk_size as i32 + ScalarGenerator::PADDING));
+
+        // Sample coordinates map one-to-one onto world voxels
+        let origin = position * chunk_size as i32;
+        let corner = IVec3::new(1, 2, 3);
+        let grid_index = scalar_data.index_of(corner).unwrap();
+        assert_eq!(scalar_data.grid[grid_index], (origin + corner).as_vec3().to_array());
+
+        let (vertices, indices) = MarchingCubesGenerator::generate(data_tables.clone(), scalar_data, ISOLEVEL, 1);
+        assert!(!vertices.is_empty(), "chunk size {} produced no vertices", chunk_size);
+        assert_eq!(indices.len() % 3, 0);
+
+        // Every vertex stays inside the cubes the chunk owns
+        for vertex in vertices.chunks(VERTEX_STRIDE) {
+            for &coordinate in &vertex[..3] {
+                assert!((1.0..=chunk_size as f32 + 1.0).contains(&coordinate));
+            }
+        }
+    }
+}
+
+#[test]
+fn edits_stay_consistent_across_chunk_padding_at_every_size() {
+    for chunk_size in SUPPORTED_CHUNK_SIZES {
+        let size = chunk_size as i32;
+        let mut fields: HashMap<IVec3, ScalarData> = HashMap::new();
+        for x in -1..=0 {
+            for y in -1..=0 {
+                for z in -1..=0 {
+                    fields.insert(IVec3::new(x, y, z), empty_scalar_data(chunk_size));
+                }
+            }
+        }
+
+        // Voxels on and around the shared corner of all eight chunks
+        let edits = [
+            (IVec3::new(0, 0, 0), 1.0),
+            (IVec3::new(1, 2, 0), 0.5),
+            (IVec3::new(2, -1, 1), -0.25),
+            (IVec3::new(-size + 1, 0, 2), 2.0),
+        ];
+
+        for &(voxel, delta) in &edits {
+            for (chunk_position, local_position) in TerrainManager::chunks_containing_voxel(voxel, chunk_size) {
+                assert_eq!(chunk_position * size + local_position, voxel);
+                if let Some(field) = fields.get_mut(&chunk_position) {
+                    field.set_value(local_position, delta).unwrap();
+                }
+            }
+        }
+
+        // Every copy of an edited voxel holds the same value
+        for &(voxel, delta) in &edits {
+            let owners = TerrainManager::chunks_containing_voxel(voxel, chunk_size);
+            for (chunk_position, local_position) in owners {
+                if let Some(field) = fields.get(&chunk_position) {
+                    assert_eq!(field.get_value(local_position), Some(delta), "size {} voxel {:?} chunk {:?}", chunk_size, voxel, chunk_position);
+                }
+            }
+        }
+
+        // The corner voxel lives in all eight chunks
+        assert_eq!(TerrainManager::chunks_containing_voxel(IVec3::ZERO, chunk_size).len(), 8);
+        // An interior voxel lives in exactly one
+        assert_eq!(TerrainManager::chunks_containing_voxel(IVec3::splat(size / 2), chunk_size).len(), 1);
+    }
+}
@@ -0,0 +1,75 @@
+use glam::{Mat4, Vec3};
+
+use fallendust::renderer::debug_lines::{heatmap_color, DebugDrawSettings, DebugLines, LINE_VERTEX_STRIDE};
+use fallendust::utils::aabb::Aabb;
+
+#[test]
+fn line_stores_position_and_color_per_endpoint() {
+    let mut lines = DebugLines::new();
+    lines.line(Vec3::ZERO, Vec3::ONE, Vec3::X);
+
+    assert_eq!(lines.line_count(), 1);
+    assert_eq!(lines.vertices.len(), 2 * LINE_VERTEX_STRIDE);
+    assert_eq!(&lines.vertices[6..], &[1.0, 1.0, 1.0, 1.0, 0.0, 0.0]);
+}
+
+#[test]
+fn aabb_draws_twelve_axis_aligned_edges() {
+    let mut lines = DebugLines::new();
+    let aabb = Aabb::new(Vec3::ZERO, Vec3::new(1.0, 2.0, 3.0));
+    lines.aabb(&aabb, Vec3::ONE);
+
+    assert_eq!(lines.line_count(), 12);
+    let mut total_length = 0.0;
+    for line in lines.vertices.chunks_exact(LINE_VERTEX_STRIDE * 2) {
+        let from = Vec3::new(line[0], line[1], line[2]);
+        let to = Vec3::new(line[6], line[7], line[8]);
+        let delta = (to - from).abs();
+        // Exactly one axis changes along an edge
+        assert_eq!(delta.cmpgt(Vec3::ZERO).bitmask().count_ones(), 1);
+        total_length += delta.element_sum();
+    }
+    assert!((total_length - 4.0 * (1.0 + 2.0 + 3.0)).abs() < 1e-5);
+}
+
+#[test]
+fn frustum_of_orthographic_matrix_matches_its_box() {
+    let projection = Mat4::orthographic_rh_gl(-1.0, 3.0, -2.0, 2.0, 0.5, 10.0);
+    let mut frustum = DebugLines::new();
+    frustum.frustum(projection, Vec3::ONE);
+
+    // Orthographic volume is the box from (-1, -2, -10) to (3, 2, -0.5) in view space
+    let mut expected = DebugLines::new();
+    expected.aabb(&Aabb::new(Vec3::new(-1.0, -2.0, -10.0), Vec3::new(3.0, 2.0, -0.5)), Vec3::ONE);
+
+    assert_eq!(frustum.line_count(), 12);
+    let mut frustum_points: Vec<Vec3> = frustum.vertices.chunks_exact(LINE_VERTEX_STRIDE).map(|v| Vec3::new(v[0], v[1], v[2])).collect();
+    let mut expected_points: Vec<Vec3> = expected.vertices.chunks_exact(LINE_VERTEX_STRIDE).map(|v| Vec3::new(v[0], v[1], v[2])).collect();
+    let order = |a: &Vec3, b: &Vec3| a.to_array().partial_cmp(&b.to_array()).unwrap();
+    frustum_points.sort_by(order);
+    expected_points.sort_by(order);
+    for (a, b) in frustum_points.iter().zip(&expected_points) {
+        assert!(a.abs_diff_eq(*b, 1e-4), "{:?} != {:?}", a, b);
+    }
+}
+
+#[test]
+fn heatmap_goes_from_red_to_blue() {
+    assert_eq!(heatmap_color(0, 5), Vec3::new(1.0, 0.0, 0.0));
+    assert_eq!(heatmap_color(4, 5), Vec3::new(0.0, 0.0, 1.0));
+    assert_eq!(heatmap_color(0, 1), Vec3::new(1.0, 0.0, 0.0));
+}
+
+#[test]
+fn settings_are_set_by_name() {
+    let mut settings = DebugDrawSettings::default();
+    settings.set("normals", "on").unwrap();
+    settings.set("normals_radius", "2").unwrap();
+    settings.set("chunk_bounds", "off").unwrap();
+
+    assert!(settings.normals);
+    assert_eq!(settings.normals_radius, 2);
+    assert!(!settings.chunk_bounds);
+    assert!(settings.set("normals", "maybe").is_err());
+    assert!(settings.set("wireframe", "on").is_err());
+}
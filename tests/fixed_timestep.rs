@@ -0,0 +1,96 @@
+use fallendust::camera_controller::{CameraController, CameraInput};
+use fallendust::utils::fixed_timestep::FixedTimestep;
+use glam::Vec3;
+
+#[test]
+fn frames_are_split_into_whole_ticks() {
+    let mut timestep = FixedTimestep::new(60.0);
+
+    // A 144 Hz frame is shorter than a tick, time piles up until one fits
+    assert_eq!(timestep.advance(1.0 / 144.0), 0);
+    assert_eq!(timestep.advance(1.0 / 144.0), 0);
+    assert_eq!(timestep.advance(1.0 / 144.0), 1);
+    assert!(timestep.alpha() > 0.0 && timestep.alpha() < 1.0);
+
+    // A 30 Hz frame runs two ticks
+    let mut timestep = FixedTimestep::new(60.0);
+    assert_eq!(timestep.advance(1.0 / 30.0 + 1e-4), 2);
+}
+
+#[test]
+fn total_ticks_match_elapsed_time_at_any_frame_rate() {
+    for frame_rate in [30.0, 60.0, 144.0, 1444.0] {
+        let mut timestep = FixedTimestep::new(60.0);
+        let frames = (frame_rate * 2.0) as usize;
+        let ticks: u32 = (0..frames).map(|_| timestep.advance(1.0 / frame_rate)).sum();
+        assert!((119..=120).contains(&ticks), "{} ticks in 2 s at {} FPS", ticks, frame_rate);
+    }
+}
+
+#[test]
+fn long_stalls_are_capped() {
+    let mut timestep = FixedTimestep::new(60.0);
+    assert_eq!(timestep.advance(5.0), timestep.max_ticks_per_frame);
+    assert!(timestep.advance(0.0) <= 1, "dropped time must not be caught up later");
+}
+
+fn forward_input(sprint: bool) -> CameraInput {
+    CameraInput {
+        movement: Vec3::Z,
+        sprint,
+        ..Default::default()
+    }
+}
+
+fn travel(ticks_per_second: f32, seconds: f32, input: CameraInput) -> CameraController {
+    let mut camera = CameraController::new(1.0);
+    let tick = 1.0 / ticks_per_second;
+    for _ in 0..(seconds * ticks_per_second).round() as usize {
+        camera.simulate(&input, tick);
+    }
+    camera
+}
+
+#[test]
+fn camera_distance_does_not_depend_on_tick_rate() {
+    let slow = travel(30.0, 2.0, forward_input(false));
+    let fast = travel(240.0, 2.0, forward_input(false));
+
+    let start = CameraController::new(1.0).position;
+    let slow_distance = slow.position.distance(start);
+    let fast_distance = fast.position.distance(start);
+    assert!((slow_distance - fast_distance).abs() / fast_distance < 0.02, "{} vs {}", slow_distance, fast_distance);
+}
+
+#[test]
+fn camera_accelerates_to_top_speed_and_sprints_faster() {
+    let walking = travel(60.0, 1.0, forward_input(false));
+    assert!((walking.velocity.length() - walking.speed).abs() < 1e-3);
+
+    let sprinting = travel(60.0, 1.0, forward_input(true));
+    assert!((sprinting.velocity.length() - sprinting.speed * sprinting.sprint_multiplier).abs() < 1e-3);
+
+    // Moves along the view direction, -Z with the default yaw
+    assert!(walking.velocity.normalize().abs_diff_eq(walking.forward(), 1e-4));
+}
+
+#[test]
+fn camera_glides_to_a_stop_without_input() {
+    let mut camera = travel(60.0, 1.0, forward_input(false));
+    for _ in 0..60 {
+        camera.simulate(&CameraInput::default(), 1.0 / 60.0);
+    }
+    assert_eq!(camera.velocity, Vec3::ZERO);
+}
+
+#[test]
+fn interpolation_blends_the_last_two_ticks() {
+    let mut camera = CameraController::new(1.0);
+    camera.simulate(&forward_input(false), 1.0 / 60.0);
+    camera.simulate(&forward_input(false), 1.0 / 60.0);
+
+    let halfway = camera.interpolated(0.5);
+    let expected = camera.previous_position.lerp(camera.position, 0.5);
+    assert!(halfway.position.abs_diff_eq(expected, 1e-6));
+    assert!(camera.interpolated(1.0).position.abs_diff_eq(camera.position, 1e-6));
+}
@@ -0,0 +1,44 @@
+use fallendust::renderer::fog::{Fog, FogSettings};
+use fallendust::renderer::lighting::Lighting;
+use glam::Vec3;
+
+fn fog() -> Fog {
+    let settings = FogSettings { view_distance: 4, ..Default::default() };
+    Fog::new(&settings, 64, &Lighting::default())
+}
+
+#[test]
+fn view_distance_edge_is_fully_fogged() {
+    let fog = fog();
+    let eye = Vec3::new(0.0, 20.0, 0.0);
+
+    assert!(fog.transmittance(eye, eye + Vec3::X * 10.0).min_element() > 0.9);
+    assert_eq!(fog.transmittance(eye, eye + Vec3::X * 256.0), Vec3::ZERO);
+}
+
+#[test]
+fn fog_thins_out_with_height() {
+    let fog = fog();
+    let low = fog.optical_depth(Vec3::new(0.0, 0.0, 0.0), Vec3::new(100.0, 0.0, 0.0));
+    let high = fog.optical_depth(Vec3::new(0.0, 100.0, 0.0), Vec3::new(100.0, 100.0, 0.0));
+    assert!(high < low * 0.5);
+
+    // Looking down into the fog sees more of it than looking up out of it
+    let down = fog.optical_depth(Vec3::new(0.0, 50.0, 0.0), Vec3::new(100.0, 0.0, 0.0));
+    let up = fog.optical_depth(Vec3::new(0.0, 50.0, 0.0), Vec3::new(100.0, 100.0, 0.0));
+    assert!(down > up);
+}
+
+#[test]
+fn distant_terrain_turns_blue_first() {
+    let fog = fog();
+    let transmittance = fog.transmittance(Vec3::ZERO, Vec3::X * 120.0);
+    assert!(transmittance.z < transmittance.x);
+}
+
+#[test]
+fn disabled_fog_keeps_everything_visible() {
+    let settings = FogSettings { enabled: false, ..Default::default() };
+    let fog = Fog::new(&settings, 64, &Lighting::default());
+    assert_eq!(fog.transmittance(Vec3::ZERO, Vec3::X * 5000.0), Vec3::ONE);
+}
@@ -0,0 +1,39 @@
+use fallendust::{Aabb, CameraController, Frustum};
+use glam::{Mat4, Vec3};
+
+#[test]
+fn camera_frustum_culls_boxes_behind_and_beside_the_view() {
+    let mut camera = CameraController::new(16.0 / 9.0);
+    camera.position = Vec3::ZERO;
+    camera.target = Vec3::NEG_Z;
+    let frustum = camera.get_frustum();
+
+    let in_front = Aabb::new(Vec3::new(-1.0, -1.0, -12.0), Vec3::new(1.0, 1.0, -10.0));
+    let behind = Aabb::new(Vec3::new(-1.0, -1.0, 10.0), Vec3::new(1.0, 1.0, 12.0));
+    let far_left = Aabb::new(Vec3::new(-500.0, -1.0, -12.0), Vec3::new(-400.0, 1.0, -10.0));
+    let beyond_far = Aabb::new(Vec3::new(-1.0, -1.0, -2000.0), Vec3::new(1.0, 1.0, -1500.0));
+    let straddling_near = Aabb::new(Vec3::splat(-1.0), Vec3::splat(1.0));
+
+    assert!(frustum.intersects_aabb(&in_front));
+    assert!(!frustum.intersects_aabb(&behind));
+    assert!(!frustum.intersects_aabb(&far_left));
+    assert!(!frustum.intersects_aabb(&beyond_far));
+    assert!(frustum.intersects_aabb(&straddling_near));
+}
+
+#[test]
+fn orthographic_frustum_matches_its_box() {
+    let projection = Mat4::orthographic_rh_gl(-10.0, 10.0, -10.0, 10.0, 0.0, 100.0);
+    let view = Mat4::look_at_rh(Vec3::new(0.0, 50.0, 0.0), Vec3::ZERO, Vec3::Z);
+    let frustum = Frustum::from_matrix(projection * view);
+
+    assert!(frustum.contains_point(Vec3::ZERO));
+    assert!(frustum.contains_point(Vec3::new(9.0, -40.0, 9.0)));
+    assert!(!frustum.contains_point(Vec3::new(11.0, 0.0, 0.0)));
+    assert!(!frustum.contains_point(Vec3::new(0.0, 60.0, 0.0)));
+    assert!(!frustum.contains_point(Vec3::new(0.0, -60.0, 0.0)));
+
+    let bounds = Aabb::from_points([Vec3::new(9.0, 0.0, 0.0), Vec3::new(20.0, 5.0, 3.0)]).unwrap();
+    assert!(frustum.intersects_aabb(&bounds));
+    assert!(!frustum.intersects_aabb(&bounds.translated(Vec3::new(5.0, 0.0, 0.0))));
+}
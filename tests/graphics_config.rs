@@ -0,0 +1,59 @@
+use fallendust::renderer::graphics_config::GraphicsConfig;
+
+#[test]
+fn bundled_config_is_valid() {
+    let mut config = GraphicsConfig::load_from_file("./assets/data/graphics_config.json").unwrap();
+    assert!(config.sanitize().is_empty());
+}
+
+#[test]
+fn invalid_values_fall_back_to_defaults() {
+    let mut config = GraphicsConfig {
+        msaa_samples: 3,
+        shadow_resolution: 1000,
+        shadow_cascades: 9,
+        pcf_radius: 40,
+        ..Default::default()
+    };
+
+    let warnings = config.sanitize();
+    assert_eq!(warnings.len(), 4);
+    assert_eq!(config, GraphicsConfig::default());
+}
+
+#[test]
+fn set_rejects_invalid_values_and_keeps_the_old_one() {
+    let mut config = GraphicsConfig::default();
+
+    assert!(config.set("shadow_resolution", "4096").is_ok());
+    assert_eq!(config.shadow_resolution, 4096);
+
+    assert!(config.set("shadow_resolution", "3000").is_err());
+    assert!(config.set("shadow_resolution", "big").is_err());
+    assert!(config.set("brightness", "1").is_err());
+    assert_eq!(config.shadow_resolution, 4096);
+}
+
+#[test]
+fn missing_file_is_created_with_defaults() {
+    let path = std::env::temp_dir().join("fallendust_missing_graphics_config.json");
+    let _ = std::fs::remove_file(&path);
+
+    let config = GraphicsConfig::load_or_default(&path);
+    assert_eq!(config, GraphicsConfig::default());
+    assert_eq!(GraphicsConfig::load_from_file(&path).unwrap(), config);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn unparsable_file_is_left_alone() {
+    let path = std::env::temp_dir().join("fallendust_broken_graphics_config.json");
+    std::fs::write(&path, "{ \"msaa_samples\": 4,").unwrap();
+
+    let config = GraphicsConfig::load_or_default(&path);
+    assert_eq!(config, GraphicsConfig::default());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "{ \"msaa_samples\": 4,");
+
+    std::fs::remove_file(&path).unwrap();
+}
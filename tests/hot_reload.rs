@@ -0,0 +1,122 @@
+use std::fs::File;
+use std::time::{Duration, SystemTime};
+
+use fallendust::terrain::chunk_lifecycle::{ChunkEvent, ChunkState};
+use fallendust::terrain::terrain_generator_config::TerrainGeneratorConfig;
+use fallendust::utils::file_watcher::FileWatcher;
+use fallendust::TerrainManager;
+use glam::{IVec3, Vec3};
+
+#[test]
+fn watcher_reports_written_and_created_files() {
+    let directory = std::env::temp_dir().join("fallendust_file_watcher");
+    std::fs::create_dir_all(&directory).unwrap();
+    let existing = directory.join("existing.glsl");
+    let created = directory.join("created.glsl");
+    std::fs::write(&existing, "a").unwrap();
+    let _ = std::fs::remove_file(&created);
+
+    let mut watcher = FileWatcher::new(Duration::ZERO);
+    watcher.watch_file(&existing);
+    watcher.watch_file(&created);
+    assert!(watcher.check().is_empty());
+
+    // Move the timestamp explicitly, file systems can have coarse mtime resolution
+    let file = File::options().write(true).open(&existing).unwrap();
+    file.set_modified(SystemTime::now() + Duration::from_secs(10)).unwrap();
+    std::fs::write(&created, "b").unwrap();
+
+    assert_eq!(watcher.check(), vec![created.clone(), existing.clone()]);
+    assert!(watcher.check().is_empty());
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn generator_change_regenerates_only_unmodified_chunks() {
+    let config = TerrainGeneratorConfig { chunk_size: 32, ..Default::default() };
+    let mut terrain_manager = TerrainManager::new(&config);
+    let positions = [IVec3::new(0, 0, 0), IVec3::new(1, 0, 0), IVec3::new(0, -1, 0)];
+    for position in positions {
+        terrain_manager.generate_chunk(position);
+    }
+
+    // Edit a voxel in the middle of the first chunk only
+    terrain_manager.place_voxel(Vec3::new(16.0, 16.0, 16.0), -0.5);
+    terrain_manager.remesh_all_chunks();
+
+    let subscription = terrain_manager.subscribe_chunk_events();
+    let queued = terrain_manager.apply_generator_config(&TerrainGeneratorConfig { seed: 7, ..config }, Vec3::ZERO);
+    assert_eq!(queued, 2);
+
+    // Regenerated chunks keep their old mesh until the new one is built
+    terrain_manager.generation_budget = Duration::ZERO;
+    terrain_manager.process_chunk_generation();
+    assert!(positions.iter().all(|position| terrain_manager.chunk_state(*position) == Some(ChunkState::Meshed)));
+
+    while terrain_manager.is_generating() {
+        terrain_manager.process_chunk_generation();
+    }
+    let regenerated: Vec<IVec3> = terrain_manager
+        .poll_chunk_events(subscription)
+        .into_iter()
+        .filter_map(|event| match event {
+            ChunkEvent::Loaded(position) => Some(position),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(regenerated.len(), 2);
+    assert!(!regenerated.contains(&IVec3::new(0, 0, 0)));
+    assert!(terrain_manager.chunks[&IVec3::new(0, 0, 0)].modified);
+}
+
+/// World-space box covered by the chunks queued for generation.
+fn queued_area(terrain_manager: &TerrainManager) -> (IVec3, IVec3) {
+    let size = terrain_manager.chunk_size as i32;
+    let queued: Vec<IVec3> = terrain_manager.queued_chunks().copied().collect();
+    let min = queued.iter().fold(IVec3::MAX, |min, position| min.min(*position));
+    let max = queued.iter().fold(IVec3::MIN, |max, position| max.max(*position));
+    (min * size, (max + IVec3::ONE) * size)
+}
+
+#[test]
+fn chunk_size_change_requeues_the_loaded_area() {
+    let config = TerrainGeneratorConfig { chunk_size: 64, ..Default::default() };
+    let mut terrain_manager = TerrainManager::new(&config);
+    terrain_manager.enqueue_chunks_in_radius(IVec3::ZERO, 2);
+    let (loaded_min, loaded_max) = queued_area(&terrain_manager);
+    assert_eq!((loaded_min, loaded_max), (IVec3::splat(-128), IVec3::splat(192)));
+
+    // Smaller chunks: the same area needs more of them, all in the new grid
+    let center = Vec3::new(40.0, 10.0, 20.0);
+    let queued = terrain_manager.apply_generator_config(&TerrainGeneratorConfig { chunk_size: 32, ..config.clone() }, center);
+    assert_eq!(terrain_manager.chunk_size, 32);
+    assert_eq!(queued, terrain_manager.queued_chunks().count());
+    let (min, max) = queued_area(&terrain_manager);
+    assert!(min.cmple(loaded_min).all() && max.cmpge(loaded_max).all(), "{} {} doesn't cover the loaded area", min, max);
+    // Centered on the camera's chunk in the new grid
+    assert_eq!(terrain_manager.queued_chunks().next(), Some(&IVec3::new(1, 0, 0)));
+
+    // Larger chunks: far fewer chunks, but they still reach as far as before
+    let (loaded_min, loaded_max, loaded_count) = (min, max, queued);
+    let queued = terrain_manager.apply_generator_config(&TerrainGeneratorConfig { chunk_size: 128, ..config }, center);
+    assert!(queued < loaded_count / 8);
+    let (min, max) = queued_area(&terrain_manager);
+    assert!(min.cmple(loaded_min).all() && max.cmpge(loaded_max).all(), "{} {} doesn't cover the loaded area", min, max);
+    // At most one extra chunk of margin on each side
+    assert!((max - min).max_element() <= (loaded_max - loaded_min).max_element() + 2 * 128);
+}
+
+#[test]
+fn chunk_size_change_replaces_loaded_chunks() {
+    let config = TerrainGeneratorConfig { chunk_size: 32, ..Default::default() };
+    let mut terrain_manager = TerrainManager::new(&config);
+    terrain_manager.generate_chunk(IVec3::new(1, 0, 0));
+
+    let queued = terrain_manager.apply_generator_config(&TerrainGeneratorConfig { chunk_size: 64, ..config }, Vec3::new(40.0, 0.0, 0.0));
+    assert!(terrain_manager.chunks.is_empty());
+    // The old chunk spanned x 32..64, which the new grid's chunk 0 covers
+    assert_eq!(terrain_manager.queued_chunks().next(), Some(&IVec3::ZERO));
+    assert_eq!(queued, 27);
+}
@@ -0,0 +1,52 @@
+use std::collections::BTreeMap;
+
+use fallendust::terrain::dirty_region::DirtyRegion;
+use fallendust::terrain::terrain_generator_config::MaterialStrata;
+use fallendust::{MarchingCubesDataTables, MarchingCubesGenerator, ScalarGenerator};
+use glam::IVec3;
+
+const ISOLEVEL: f32 = 0.5;
+
+#[test]
+fn remeshing_dirty_slices_matches_full_remesh() {
+    let data_tables = MarchingCubesDataTables::load_from_files("./assets/data/marching_cubes_tables/").unwrap();
+    let mut scalar_data = ScalarGenerator::generate(IVec3::new(0, -1, 0), 0, 32, &MaterialStrata::default());
+
+    let mut cached: BTreeMap<usize, _> = MarchingCubesGenerator::generate_slices(&data_tables, &scalar_data, ISOLEVEL, 1, None)
+        .into_iter()
+        .map(|slice| (slice.0, slice))
+        .collect();
+
+    // Dig a small box through the surface
+    let mut region: Option<DirtyRegion> = None;
+    for x in 10..14 {
+        for y in 0..scalar_data.dimensions.y {
+            for z in 12..15 {
+                let sample = IVec3::new(x, y, z);
+                scalar_data.set_value(sample, -0.75).unwrap();
+                match region.as_mut() {
+                    Some(region) => region.include(sample),
+                    None => region = Some(DirtyRegion::new(sample)),
+                }
+            }
+        }
+    }
+    let region = region.unwrap();
+    assert_eq!(region.size(), IVec3::new(4, scalar_data.dimensions.y, 3));
+
+    let rebuilt = MarchingCubesGenerator::generate_slices(
+        &data_tables,
+        &scalar_data,
+        ISOLEVEL,
+        1,
+        Some((region.min.x as usize, region.max.x as usize)),
+    );
+    assert!(rebuilt.len() < cached.len(), "only the affected slices should be rebuilt");
+    for slice in rebuilt {
+        cached.insert(slice.0, slice);
+    }
+
+    let incremental = MarchingCubesGenerator::merge_slices(cached.values());
+    let full = MarchingCubesGenerator::generate(data_tables, scalar_data, ISOLEVEL, 1);
+    assert_eq!(incremental, full);
+}
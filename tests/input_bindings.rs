@@ -0,0 +1,140 @@
+use fallendust::camera_controller::CameraController;
+use fallendust::input::action::Action;
+use fallendust::input::bindings::{Binding, InputBindings};
+use fallendust::input::input_source::{InputButton, ScriptedInput};
+use ferrousgl::WindowKey;
+use glam::Vec3;
+
+fn key(key: WindowKey) -> InputButton {
+    InputButton::Key(key)
+}
+
+#[test]
+fn default_bindings_cover_every_action_without_conflicts() {
+    let bindings = InputBindings::default();
+    for action in Action::ALL {
+        assert!(!bindings.bindings[&action].is_empty(), "{:?} is unbound", action);
+    }
+    assert_eq!(bindings.conflicts(), Vec::new());
+}
+
+#[test]
+fn bindings_parse_and_print_chords() {
+    let binding: Binding = "LeftControl+LeftShift+S".parse().unwrap();
+    assert_eq!(binding.button, key(WindowKey::S));
+    assert_eq!(binding.modifiers, vec![key(WindowKey::LeftControl), key(WindowKey::LeftShift)]);
+    assert_eq!(binding.to_string(), "LeftControl+LeftShift+S");
+
+    let mouse: Binding = "g + mouseleft".parse().unwrap();
+    assert_eq!(mouse.to_string(), "G+MouseLeft");
+
+    assert!("LeftControl+Nope".parse::<Binding>().is_err());
+    assert!("".parse::<Binding>().is_err());
+}
+
+#[test]
+fn bindings_round_trip_through_json() {
+    let mut bindings = InputBindings::default();
+    bindings.bind(
+        Action::Screenshot,
+        vec![Binding::new(key(WindowKey::P)).with_modifier(key(WindowKey::LeftControl))],
+    );
+
+    let json = serde_json::to_string(&bindings).unwrap();
+    assert!(json.contains("\"Screenshot\":[\"LeftControl+P\"]"), "{}", json);
+    let loaded: InputBindings = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded, bindings);
+
+    assert!(serde_json::from_str::<InputBindings>("{\"Jump\": [\"NotAKey\"]}").is_err());
+}
+
+#[test]
+fn the_shipped_config_matches_the_defaults() {
+    let loaded = InputBindings::load_from_file("./assets/data/input_bindings.json").unwrap();
+    assert_eq!(loaded, InputBindings::default());
+}
+
+#[test]
+fn missing_actions_fall_back_to_defaults() {
+    let path = std::env::temp_dir().join(format!("fallendust_bindings_{}.json", std::process::id()));
+    std::fs::write(&path, "{\"Jump\": [\"J\"]}").unwrap();
+
+    let bindings = InputBindings::load_or_default(&path);
+    assert_eq!(bindings.bindings[&Action::Jump], vec![Binding::new(key(WindowKey::J))]);
+    assert_eq!(bindings.bindings[&Action::MoveForward], InputBindings::default().bindings[&Action::MoveForward]);
+
+    // The completed bindings were written back
+    let saved = InputBindings::load_from_file(&path).unwrap();
+    assert_eq!(saved, bindings);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn unparsable_bindings_are_left_alone() {
+    let path = std::env::temp_dir().join(format!("fallendust_broken_bindings_{}.json", std::process::id()));
+    std::fs::write(&path, "{\"Jump\": [\"J\"").unwrap();
+
+    let bindings = InputBindings::load_or_default(&path);
+    assert_eq!(bindings, InputBindings::default());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "{\"Jump\": [\"J\"");
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn conflicting_bindings_are_detected() {
+    let mut bindings = InputBindings::default();
+    bindings.bind(Action::Jump, vec!["W".parse().unwrap()]);
+    bindings.bind(Action::Screenshot, vec!["LeftShift+LeftControl+P".parse().unwrap()]);
+    bindings.bind(Action::ToggleWalk, vec!["LeftControl+LeftShift+P".parse().unwrap()]);
+
+    let conflicts = bindings.conflicts();
+    assert_eq!(conflicts.len(), 2, "{:?}", conflicts);
+    assert!(conflicts.iter().any(|c| c.first == Action::MoveForward && c.second == Action::Jump));
+    assert!(conflicts.iter().any(|c| c.first == Action::Screenshot && c.second == Action::ToggleWalk));
+}
+
+#[test]
+fn scripted_input_drives_actions() {
+    let bindings = InputBindings::default();
+    let mut input = ScriptedInput::new();
+
+    input.press(key(WindowKey::W));
+    input.press(key(WindowKey::LeftShift));
+    assert!(bindings.is_active(Action::MoveForward, &input));
+    assert!(bindings.was_triggered(Action::MoveForward, &input));
+
+    let camera_input = CameraController::read_input(&input, &bindings);
+    assert_eq!(camera_input.movement, Vec3::Z);
+    assert!(camera_input.sprint);
+
+    // Still held on the next frame, but no longer just pressed
+    input.end_frame();
+    assert!(bindings.is_active(Action::MoveForward, &input));
+    assert!(!bindings.was_triggered(Action::MoveForward, &input));
+
+    input.release(key(WindowKey::W));
+    assert!(!bindings.is_active(Action::MoveForward, &input));
+
+    input.tap(InputButton::Mouse(glfw::MouseButton::Left));
+    assert_eq!(bindings.triggered_actions(&input), vec![Action::Sculpt]);
+}
+
+#[test]
+fn chords_need_their_modifiers_and_win_over_plain_bindings() {
+    let mut bindings = InputBindings::default();
+    bindings.bind(Action::Screenshot, vec!["LeftControl+S".parse().unwrap()]);
+    let mut input = ScriptedInput::new();
+
+    // Plain S walks backwards
+    input.press(key(WindowKey::S));
+    assert!(bindings.is_active(Action::MoveBackward, &input));
+    assert!(!bindings.was_triggered(Action::Screenshot, &input));
+    input.release_all();
+    input.end_frame();
+
+    // Ctrl+S takes a screenshot and doesn't also walk backwards
+    input.press(key(WindowKey::LeftControl));
+    input.press(key(WindowKey::S));
+    assert!(bindings.was_triggered(Action::Screenshot, &input));
+    assert!(!bindings.is_active(Action::MoveBackward, &input));
+}
@@ -0,0 +1,79 @@
+use fallendust::camera_controller::{CameraController, CameraMode};
+use glam::{Vec2, Vec3};
+
+fn camera() -> CameraController {
+    let mut camera = CameraController::new(16.0 / 9.0);
+    camera.viewport_size = Vec2::new(1600.0, 900.0);
+    camera.position = Vec3::new(3.0, 20.0, -4.0);
+    camera.yaw = 30.0;
+    camera.pitch = -25.0;
+    camera.target = camera.position + camera.forward();
+    camera
+}
+
+/// Window pixel a world point is drawn at.
+fn to_screen(camera: &CameraController, point: Vec3) -> (f32, f32) {
+    let ndc = camera.get_vp().project_point3(point);
+    (
+        (ndc.x + 1.0) * 0.5 * camera.viewport_size.x,
+        (1.0 - ndc.y) * 0.5 * camera.viewport_size.y,
+    )
+}
+
+fn distance_to_ray(point: Vec3, origin: Vec3, direction: Vec3) -> f32 {
+    let offset = point - origin;
+    (offset - direction * offset.dot(direction)).length()
+}
+
+#[test]
+fn centre_of_the_screen_matches_the_view_ray() {
+    let camera = camera();
+    let ray = camera.screen_point_to_ray(800.0, 450.0);
+    assert!(ray.direction.abs_diff_eq(camera.get_ray().direction, 1e-4));
+    assert!(distance_to_ray(camera.position, ray.origin, ray.direction) < 1e-3);
+}
+
+#[test]
+fn rays_pass_through_what_is_drawn_under_the_cursor() {
+    let camera = camera();
+    let forward = camera.forward();
+    let right = forward.cross(Vec3::Y).normalize();
+    let up = right.cross(forward);
+    let points = [
+        camera.position + forward * 10.0 + right * 3.0 + up * 2.0,
+        camera.position + forward * 40.0 - right * 12.0 + up * 5.0,
+        camera.position + forward * 150.0 + right * 60.0 - up * 30.0,
+    ];
+    for point in points {
+        let (x, y) = to_screen(&camera, point);
+        assert!((0.0..1600.0).contains(&x) && (0.0..900.0).contains(&y), "{} is off screen", point);
+
+        let ray = camera.screen_point_to_ray(x, y);
+        let distance = distance_to_ray(point, ray.origin, ray.direction);
+        assert!(distance < 1e-2, "missed {} by {}", point, distance);
+        assert!((point - ray.origin).dot(ray.direction) > 0.0, "{} is behind the ray", point);
+    }
+
+    // Top left is up and to the left of the view direction
+    let corner = camera.screen_point_to_ray(0.0, 0.0);
+    assert!(corner.direction.dot(right) < 0.0);
+    assert!(corner.direction.dot(up) > 0.0);
+}
+
+#[test]
+fn orthographic_rays_are_parallel() {
+    let mut camera = camera();
+    camera.set_mode(CameraMode::TopDown);
+    camera.transition = None;
+
+    let center = camera.screen_point_to_ray(800.0, 450.0);
+    let corner = camera.screen_point_to_ray(100.0, 100.0);
+    assert!(center.direction.abs_diff_eq(Vec3::NEG_Y, 1e-4));
+    assert!(corner.direction.abs_diff_eq(Vec3::NEG_Y, 1e-4));
+
+    // The corner ray starts up and to the left, which is west and north
+    let offset = corner.origin - center.origin;
+    assert!(offset.x < 0.0 && offset.z < 0.0);
+    let expected = camera.top_down.extent * (350.0 / 450.0);
+    assert!((offset.z + expected).abs() < 1e-2, "{}", offset);
+}
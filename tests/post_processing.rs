@@ -0,0 +1,67 @@
+use fallendust::renderer::graphics_config::GraphicsConfig;
+use fallendust::renderer::post_processing::{bloom_mip_sizes, ssao_kernel, PostSettings, SSAO_KERNEL_SIZE};
+
+#[test]
+fn ssao_kernel_lies_in_the_upper_unit_hemisphere() {
+    let kernel = ssao_kernel(SSAO_KERNEL_SIZE);
+
+    assert_eq!(kernel.len(), SSAO_KERNEL_SIZE);
+    for sample in &kernel {
+        assert!(sample.z >= 0.0, "{:?} points below the surface", sample);
+        assert!(sample.length() <= 1.0 + 1e-5, "{:?} is outside the unit sphere", sample);
+        assert!(sample.length() > 0.0);
+    }
+}
+
+#[test]
+fn ssao_kernel_is_deterministic_and_grows_outwards() {
+    let kernel = ssao_kernel(SSAO_KERNEL_SIZE);
+    assert_eq!(kernel, ssao_kernel(SSAO_KERNEL_SIZE));
+
+    let half = SSAO_KERNEL_SIZE / 2;
+    let inner: f32 = kernel[..half].iter().map(|sample| sample.length()).sum();
+    let outer: f32 = kernel[half..].iter().map(|sample| sample.length()).sum();
+    assert!(inner < outer, "early samples should stay closer to the centre");
+}
+
+#[test]
+fn bloom_mips_halve_from_half_resolution() {
+    assert_eq!(
+        bloom_mip_sizes(1280, 720, 5),
+        vec![(640, 360), (320, 180), (160, 90), (80, 45), (40, 22)]
+    );
+}
+
+#[test]
+fn bloom_mips_stop_before_becoming_degenerate() {
+    assert_eq!(bloom_mip_sizes(16, 8, 10), vec![(8, 4), (4, 2)]);
+    assert!(bloom_mip_sizes(2, 2, 3).is_empty());
+}
+
+#[test]
+fn post_toggles_come_from_the_graphics_config() {
+    let mut config = GraphicsConfig::default();
+    config.set("ssao", "false").unwrap();
+    config.set("fxaa", "false").unwrap();
+    config.set("exposure", "2.5").unwrap();
+    config.set("msaa_samples", "8").unwrap();
+
+    let settings = config.post_settings();
+    assert!(!settings.ssao);
+    assert!(!settings.fxaa);
+    assert!(settings.bloom);
+    assert!(settings.tonemapping);
+    assert_eq!(settings.exposure, 2.5);
+    // The scene target is multisampled, MSAA isn't lost to the post chain
+    assert_eq!(settings.msaa_samples, 8);
+    assert_eq!(settings.bloom_levels, PostSettings::default().bloom_levels);
+}
+
+#[test]
+fn exposure_must_be_positive() {
+    let mut config = GraphicsConfig::default();
+    assert!(config.set("exposure", "0").is_err());
+    assert!(config.set("exposure", "-1").is_err());
+    assert!(config.set("exposure", "NaN").is_err());
+    assert_eq!(config.exposure, 1.0);
+}
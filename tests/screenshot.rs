@@ -0,0 +1,92 @@
+use std::path::PathBuf;
+use std::time::{Duration, UNIX_EPOCH};
+
+use fallendust::headless::{parse_args, GoldenScene};
+use fallendust::renderer::screenshot::{compare_images, default_screenshot_path, save_png};
+use image::{Rgba, RgbaImage};
+
+fn args(text: &str) -> Vec<String> {
+    text.split_whitespace().map(String::from).collect()
+}
+
+#[test]
+fn identical_images_have_no_difference() {
+    let image = RgbaImage::from_pixel(4, 4, Rgba([10, 20, 30, 255]));
+    let difference = compare_images(&image, &image, 0).unwrap();
+
+    assert_eq!(difference.differing_pixels, 0);
+    assert_eq!(difference.max_channel_difference, 0);
+    assert!(difference.within(0.0));
+}
+
+#[test]
+fn small_channel_differences_are_tolerated() {
+    let reference = RgbaImage::from_pixel(10, 10, Rgba([100, 100, 100, 255]));
+    let mut image = reference.clone();
+    image.put_pixel(0, 0, Rgba([104, 100, 100, 255]));
+    image.put_pixel(1, 0, Rgba([100, 140, 100, 255]));
+
+    let difference = compare_images(&image, &reference, 8).unwrap();
+    assert_eq!(difference.differing_pixels, 1);
+    assert_eq!(difference.max_channel_difference, 40);
+    assert!((difference.differing_fraction() - 0.01).abs() < 1e-6);
+    assert!(difference.within(0.01));
+    assert!(!difference.within(0.005));
+}
+
+#[test]
+fn images_of_different_sizes_do_not_compare() {
+    let a = RgbaImage::new(4, 4);
+    let b = RgbaImage::new(4, 5);
+    assert!(compare_images(&a, &b, 255).is_err());
+}
+
+#[test]
+fn saved_png_round_trips() {
+    let path = std::env::temp_dir().join("fallendust_screenshot_test").join("nested").join("shot.png");
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    let mut image = RgbaImage::from_pixel(3, 2, Rgba([0, 0, 0, 255]));
+    image.put_pixel(2, 1, Rgba([255, 128, 0, 255]));
+
+    save_png(&image, &path).unwrap();
+    let loaded = image::open(&path).unwrap().to_rgba8();
+    assert_eq!(loaded, image);
+}
+
+#[test]
+fn default_screenshot_path_is_timestamped() {
+    let path = default_screenshot_path(UNIX_EPOCH + Duration::from_millis(1234));
+    assert_eq!(path, PathBuf::from("./screenshots/screenshot_1234.png"));
+}
+
+#[test]
+fn offscreen_arguments_are_parsed() {
+    assert_eq!(parse_args(&args("fallendust")).unwrap(), None);
+
+    let options = parse_args(&args(
+        "fallendust --offscreen out.png --golden golden.png --tolerance 4 --max-differing 0.01 --seed 7 --size 320x200",
+    ))
+    .unwrap()
+    .unwrap();
+    assert_eq!(options.output, PathBuf::from("out.png"));
+    assert_eq!(options.golden, Some(PathBuf::from("golden.png")));
+    assert_eq!(options.channel_tolerance, 4);
+    assert_eq!(options.max_differing_fraction, 0.01);
+    assert_eq!(options.scene.seed, 7);
+    assert_eq!((options.scene.width, options.scene.height), (320, 200));
+
+    assert!(parse_args(&args("fallendust --offscreen out.png --size 320")).is_err());
+    assert!(parse_args(&args("fallendust --offscreen out.png --golden")).is_err());
+    assert!(parse_args(&args("fallendust --offscreen out.png --fast")).is_err());
+}
+
+#[test]
+fn golden_scene_camera_looks_along_yaw_and_pitch() {
+    let scene = GoldenScene::default();
+    let camera = scene.camera();
+    let direction = (camera.target - camera.position).normalize();
+
+    assert!((direction - camera.forward()).length() < 1e-5);
+    assert!(direction.y < 0.0, "golden camera should look down at the terrain");
+    assert_eq!(camera.aspect_ratio, scene.width as f32 / scene.height as f32);
+}
@@ -0,0 +1,56 @@
+use fallendust::renderer::shadow_cascades::{CascadedShadowMap, ShadowSettings};
+use fallendust::CameraController;
+use glam::Vec3;
+
+fn camera() -> CameraController {
+    let mut camera = CameraController::new(16.0 / 9.0);
+    camera.position = Vec3::new(12.3, 40.0, -7.9);
+    camera.target = camera.position + Vec3::new(0.6, -0.2, -0.8);
+    camera
+}
+
+#[test]
+fn splits_increase_up_to_the_shadow_distance() {
+    let splits = CascadedShadowMap::compute_splits(0.1, 400.0, 4, 0.75);
+
+    assert_eq!(splits.len(), 4);
+    assert!(splits.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!((splits[3] - 400.0).abs() < 1e-3);
+}
+
+#[test]
+fn every_cascade_contains_its_frustum_slice() {
+    let camera = camera();
+    let settings = ShadowSettings::default();
+    let sun_direction = Vec3::new(-0.4, -1.0, -0.3);
+    let cascades = CascadedShadowMap::fit_cascades(&camera, sun_direction, &settings);
+
+    assert_eq!(cascades.len(), settings.cascade_count);
+    for cascade in &cascades {
+        let frustum = cascade.frustum();
+        let corners = CascadedShadowMap::frustum_slice_corners(&camera, cascade.split_near, cascade.split_far);
+        let center = corners.iter().copied().sum::<Vec3>() / 8.0;
+        for corner in corners {
+            // Allow for the texel snap shifting the projection by under a texel
+            let nudged = corner.lerp(center, 0.01);
+            assert!(frustum.contains_point(nudged), "corner {:?} outside cascade ending at {}", corner, cascade.split_far);
+        }
+    }
+}
+
+#[test]
+fn cascade_origins_snap_to_whole_texels() {
+    let settings = ShadowSettings::default();
+    let sun_direction = Vec3::new(-0.4, -1.0, -0.3);
+    let half_resolution = settings.resolution as f32 * 0.5;
+
+    let mut camera = camera();
+    for step in 0..4 {
+        camera.position.x += 0.013 * step as f32;
+        for cascade in CascadedShadowMap::fit_cascades(&camera, sun_direction, &settings) {
+            let origin = cascade.light_space_matrix.project_point3(Vec3::ZERO) * half_resolution;
+            assert!((origin.x - origin.x.round()).abs() < 0.05);
+            assert!((origin.y - origin.y.round()).abs() < 0.05);
+        }
+    }
+}
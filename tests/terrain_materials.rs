@@ -0,0 +1,69 @@
+use fallendust::renderer::materials::{MaterialLibrary, MAX_LAYER_SIZE};
+use fallendust::terrain::terrain_generator_config::MaterialStrata;
+use fallendust::ScalarGenerator;
+
+#[test]
+fn bundled_material_library_is_valid() {
+    let library = MaterialLibrary::load_from_file("./assets/data/terrain_materials.json").unwrap();
+
+    let grass = library.id_of("grass").unwrap();
+    let rock = library.id_of("rock").unwrap();
+    assert_eq!(library.steep_ids()[grass as usize], rock);
+    assert_eq!(library.steep_ids()[rock as usize], rock);
+}
+
+#[test]
+fn unknown_steep_material_is_rejected() {
+    let mut library = MaterialLibrary::load_from_file("./assets/data/terrain_materials.json").unwrap();
+    library.materials[0].steep_material = Some("lava".to_string());
+    assert!(library.validate().is_err());
+}
+
+#[test]
+fn generated_materials_come_from_the_strata() {
+    let strata = MaterialStrata::default();
+    let scalar = ScalarGenerator::generate(glam::IVec3::new(0, -1, 0), 0, 32, &strata);
+
+    let mut allowed = strata.strata_materials.clone();
+    allowed.push(strata.surface_material);
+    assert_eq!(scalar.materials.len(), scalar.values.len());
+    assert!(scalar.materials.iter().all(|material| allowed.contains(material)));
+}
+
+#[test]
+fn non_positive_tiling_and_sharpness_are_rejected() {
+    let mut library = MaterialLibrary::load_from_file("./assets/data/terrain_materials.json").unwrap();
+    library.materials[0].tiling = 0.0;
+    assert!(library.validate().is_err());
+
+    let mut library = MaterialLibrary::load_from_file("./assets/data/terrain_materials.json").unwrap();
+    library.materials[0].triplanar_sharpness = -1.0;
+    assert!(library.validate().is_err());
+}
+
+#[test]
+fn layer_size_must_be_in_range() {
+    let mut library = MaterialLibrary::load_from_file("./assets/data/terrain_materials.json").unwrap();
+    library.layer_size = 0;
+    assert!(library.validate().is_err());
+    library.layer_size = MAX_LAYER_SIZE + 1;
+    assert!(library.validate().is_err());
+    library.layer_size = MAX_LAYER_SIZE;
+    assert!(library.validate().is_ok());
+}
+
+#[test]
+fn strata_ids_must_exist_in_the_library() {
+    let library = MaterialLibrary::load_from_file("./assets/data/terrain_materials.json").unwrap();
+    let mut strata = MaterialStrata::default();
+    assert!(library.validate_strata(&strata).is_ok());
+
+    strata.strata_materials.push(library.materials.len() as u8);
+    assert!(library.validate_strata(&strata).is_err());
+
+    let strata = MaterialStrata {
+        surface_material: library.materials.len() as u8,
+        ..Default::default()
+    };
+    assert!(library.validate_strata(&strata).is_err());
+}
@@ -0,0 +1,56 @@
+use fallendust::terrain::marching_cubes::marching_cubes_generator::VERTEX_STRIDE;
+use fallendust::terrain::terrain_generator_config::MaterialStrata;
+use fallendust::{MarchingCubesDataTables, MarchingCubesGenerator, ScalarGenerator};
+use glam::{IVec3, Vec3};
+
+const ISOLEVEL: f32 = 0.5;
+const CHUNK_SIZE: u16 = 32;
+
+/// World-space positions and normals of a generated chunk's vertices.
+fn mesh_vertices(data_tables: &MarchingCubesDataTables, position: IVec3) -> Vec<(Vec3, Vec3)> {
+    let scalar_data = ScalarGenerator::generate(position, 0, CHUNK_SIZE, &MaterialStrata::default());
+    let (vertices, _) = MarchingCubesGenerator::generate(data_tables.clone(), scalar_data, ISOLEVEL, 1);
+    let origin = (position * CHUNK_SIZE as i32).as_vec3();
+
+    vertices
+        .chunks(VERTEX_STRIDE)
+        .map(|vertex| (origin + Vec3::from_slice(&vertex[..3]), Vec3::from_slice(&vertex[3..6])))
+        .collect()
+}
+
+#[test]
+fn normals_agree_across_chunk_borders() {
+    let data_tables = MarchingCubesDataTables::load_from_files("./assets/data/marching_cubes_tables/").unwrap();
+
+    // The surface leaves this chunk through its top, so it shares geometry with the
+    // neighbours along every axis
+    let position = IVec3::new(-4, -1, -1);
+    let vertices = mesh_vertices(&data_tables, position);
+
+    for axis in [IVec3::X, IVec3::Y, IVec3::Z] {
+        let neighbour_vertices = mesh_vertices(&data_tables, position + axis);
+
+        // Both chunks emit the vertices on the face between them
+        let face = ((position + axis) * CHUNK_SIZE as i32).as_vec3().dot(axis.as_vec3()) + 1.0;
+        let on_face = |(vertex, _): &&(Vec3, Vec3)| (vertex.dot(axis.as_vec3()) - face).abs() < 1e-4;
+
+        let mut matched = 0;
+        for (vertex, normal) in vertices.iter().filter(on_face) {
+            let (_, neighbour_normal) = neighbour_vertices
+                .iter()
+                .filter(on_face)
+                .find(|(neighbour, _)| neighbour.distance(*vertex) < 1e-4)
+                .unwrap_or_else(|| panic!("no vertex at {} in the neighbour along {}", vertex, axis));
+
+            assert!(
+                normal.distance(*neighbour_normal) < 1e-3,
+                "normals differ at {}: {} vs {}",
+                vertex,
+                normal,
+                neighbour_normal
+            );
+            matched += 1;
+        }
+        assert!(matched > 0, "no shared vertices along {}", axis);
+    }
+}
@@ -0,0 +1,55 @@
+use fallendust::console::Console;
+use fallendust::renderer::time_of_day::TimeOfDay;
+
+fn at(hours: f32) -> TimeOfDay {
+    TimeOfDay { hours, ..Default::default() }
+}
+
+#[test]
+fn sun_follows_the_clock() {
+    assert!(at(6.0).sun_position().y.abs() < 1e-5);
+    assert!(at(12.0).sun_position().y > 0.8);
+    assert!(at(0.0).sun_position().y < -0.8);
+    assert!(at(9.0).sun_position().x > 0.0 && at(15.0).sun_position().x < 0.0);
+}
+
+#[test]
+fn moon_lights_the_night_and_shadows_follow_the_light() {
+    let noon = at(12.0).lighting();
+    assert!((noon.light_direction + noon.sun_position).length() < 1e-5);
+
+    let midnight = at(0.0).lighting();
+    assert!((midnight.light_direction + midnight.moon_position).length() < 1e-5);
+    assert!(midnight.light_color.length() < noon.light_color.length() * 0.1);
+}
+
+#[test]
+fn sky_is_blue_at_noon_and_red_at_sunset() {
+    let noon = at(12.0);
+    let zenith = noon.sky_radiance(glam::Vec3::Y);
+    assert!(zenith.z > zenith.y && zenith.y > zenith.x);
+
+    let sunset = at(17.8);
+    let sun = sunset.sun_position();
+    let towards_sun = glam::Vec3::new(sun.x, 0.05, sun.z).normalize();
+    let horizon = sunset.sky_radiance(towards_sun);
+    assert!(horizon.x > horizon.z);
+
+    // Light through more air is redder as well
+    let sunset_light = sunset.lighting().light_color;
+    assert!(sunset_light.x > sunset_light.z);
+}
+
+#[test]
+fn clock_wraps_and_parses() {
+    let mut time = at(23.5);
+    time.time_scale = 1.0;
+    time.update(1.0);
+    assert!((time.hours - 0.5).abs() < 1e-5);
+    assert_eq!(time.clock_string(), "00:30");
+
+    assert_eq!(Console::parse_clock("18:30").unwrap(), 18.5);
+    assert_eq!(Console::parse_clock("6").unwrap(), 6.0);
+    assert!(Console::parse_clock("25").is_err());
+    assert!(Console::parse_clock("10:75").is_err());
+}
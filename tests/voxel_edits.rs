@@ -0,0 +1,53 @@
+use fallendust::terrain::chunk_lifecycle::ChunkState;
+use fallendust::terrain::terrain_generator_config::TerrainGeneratorConfig;
+use fallendust::TerrainManager;
+use glam::IVec3;
+
+#[test]
+fn edits_reach_every_chunk_sharing_the_voxel() {
+    let config = TerrainGeneratorConfig { chunk_size: 32, ..Default::default() };
+    let mut terrain_manager = TerrainManager::new(&config);
+    for x in -1..=0 {
+        for y in -1..=0 {
+            for z in -1..=0 {
+                terrain_manager.generate_chunk(IVec3::new(x, y, z));
+            }
+        }
+    }
+
+    // Voxels in the padding on one, two and three axes of the chunks around the origin
+    let cases = [
+        ("face", IVec3::new(0, 10, 10), 2),
+        ("edge", IVec3::new(1, 2, 10), 4),
+        ("corner", IVec3::new(2, 0, 1), 8),
+    ];
+
+    for (name, voxel, expected_chunks) in cases {
+        let sharing = TerrainManager::chunks_containing_voxel(voxel, config.chunk_size);
+        assert_eq!(sharing.len(), expected_chunks, "{} voxel", name);
+
+        let before: Vec<f32> = sharing
+            .iter()
+            .map(|(chunk_position, local_position)| {
+                let scalar_data = &terrain_manager.chunks[chunk_position].scalar_data;
+                // The sample at the local index is the edited world voxel
+                let index = scalar_data.index_of(*local_position).unwrap();
+                assert_eq!(scalar_data.grid[index], voxel.as_vec3().to_array(), "{} voxel in {}", name, chunk_position);
+                scalar_data.values[index]
+            })
+            .collect();
+
+        terrain_manager.place_voxel(voxel.as_vec3(), -0.25);
+
+        assert_eq!(terrain_manager.get_dirty_chunks_count(), expected_chunks, "{} voxel", name);
+        for ((chunk_position, local_position), value) in sharing.iter().zip(before) {
+            let chunk = &terrain_manager.chunks[chunk_position];
+            assert_eq!(chunk.scalar_data.get_value(*local_position), Some(value - 0.25), "{} voxel in {}", name, chunk_position);
+            assert!(chunk.modified);
+            assert_eq!(terrain_manager.chunk_state(*chunk_position), Some(ChunkState::Dirty));
+        }
+
+        terrain_manager.remesh_all_chunks();
+        assert_eq!(terrain_manager.get_dirty_chunks_count(), 0);
+    }
+}
@@ -0,0 +1,179 @@
+use fallendust::physics::density_field::DensityField;
+use fallendust::physics::walker::{WalkSettings, Walker};
+use glam::Vec3;
+
+const ISOLEVEL: f32 = 0.5;
+const TICK: f32 = 1.0 / 60.0;
+
+/// Analytic terrain, solid where `solid_depth` is positive. Densities fall off like the
+/// generated terrain does, by a small factor per unit.
+struct Field<F: Fn(Vec3) -> f32>(F);
+
+impl<F: Fn(Vec3) -> f32> DensityField for Field<F> {
+    fn density(&self, position: Vec3) -> Option<f32> {
+        Some(ISOLEVEL + 0.05 * (self.0)(position))
+    }
+
+    fn isolevel(&self) -> f32 {
+        ISOLEVEL
+    }
+}
+
+/// Max with the corner rounded over about a voxel, like sampled terrain has.
+fn smooth_max(a: f32, b: f32) -> f32 {
+    let k = 0.5;
+    let h = (0.5 + 0.5 * (a - b) / k).clamp(0.0, 1.0);
+    b + (a - b) * h + k * h * (1.0 - h)
+}
+
+fn flat_ground() -> Field<impl Fn(Vec3) -> f32> {
+    Field(|p: Vec3| -p.y)
+}
+
+/// Flat ground with a raised block of `height` for x > 1.
+fn ledge(height: f32) -> Field<impl Fn(Vec3) -> f32> {
+    Field(move |p: Vec3| smooth_max(-p.y, (height - p.y).min(p.x - 1.0)))
+}
+
+/// Ground rising along x at `degrees`.
+fn slope(degrees: f32) -> Field<impl Fn(Vec3) -> f32> {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    Field(move |p: Vec3| p.x * sin - p.y * cos)
+}
+
+fn run(walker: &mut Walker, field: &dyn DensityField, direction: Vec3, jump: bool, seconds: f32) {
+    for _ in 0..(seconds / TICK) as usize {
+        walker.simulate(field, direction, jump, false, TICK);
+    }
+}
+
+#[test]
+fn falls_and_lands_on_the_ground() {
+    let field = flat_ground();
+    let mut walker = Walker::new(Vec3::new(0.0, 3.0, 0.0), WalkSettings::default());
+
+    run(&mut walker, &field, Vec3::ZERO, false, 2.0);
+    assert!(walker.on_ground);
+    assert!(walker.position.y.abs() < 0.05, "feet at {}", walker.position.y);
+    assert_eq!(walker.velocity.y, 0.0);
+}
+
+#[test]
+fn jumping_leaves_the_ground_and_lands_again() {
+    let field = flat_ground();
+    let mut walker = Walker::new(Vec3::ZERO, WalkSettings::default());
+    run(&mut walker, &field, Vec3::ZERO, false, 0.5);
+    assert!(walker.on_ground);
+
+    walker.simulate(&field, Vec3::ZERO, true, false, TICK);
+    assert!(!walker.on_ground);
+    let mut peak: f32 = 0.0;
+    for _ in 0..120 {
+        walker.simulate(&field, Vec3::ZERO, false, false, TICK);
+        peak = peak.max(walker.position.y);
+    }
+
+    // v^2 / 2g with the default settings
+    let settings = WalkSettings::default();
+    let expected_peak = settings.jump_speed * settings.jump_speed / (2.0 * settings.gravity);
+    assert!((peak - expected_peak).abs() < 0.2, "peak {} expected {}", peak, expected_peak);
+    assert!(walker.on_ground);
+    assert!(walker.position.y.abs() < 0.05);
+}
+
+#[test]
+fn walls_block_movement() {
+    let field = Field(|p: Vec3| smooth_max(-p.y, p.x - 2.0));
+    let mut walker = Walker::new(Vec3::ZERO, WalkSettings::default());
+
+    run(&mut walker, &field, Vec3::X, false, 3.0);
+    let radius = walker.settings.radius;
+    assert!(walker.position.x < 2.0 - radius + 0.1, "walked into the wall to x {}", walker.position.x);
+    assert!(walker.position.x > 2.0 - radius - 0.3, "stopped early at x {}", walker.position.x);
+    assert!(walker.position.y.abs() < 0.1);
+}
+
+#[test]
+fn low_ledges_are_stepped_onto() {
+    let field = ledge(0.3);
+    let mut walker = Walker::new(Vec3::ZERO, WalkSettings::default());
+
+    run(&mut walker, &field, Vec3::X, false, 2.0);
+    assert!(walker.position.x > 3.0, "stuck at x {}", walker.position.x);
+    assert!((walker.position.y - 0.3).abs() < 0.1, "feet at {}", walker.position.y);
+    assert!(walker.on_ground);
+}
+
+#[test]
+fn ledges_above_the_step_height_block_movement() {
+    let field = ledge(1.2);
+    let mut walker = Walker::new(Vec3::ZERO, WalkSettings::default());
+
+    run(&mut walker, &field, Vec3::X, false, 2.0);
+    assert!(walker.position.x < 1.0, "climbed to x {}", walker.position.x);
+    assert!(walker.position.y < 0.2);
+}
+
+#[test]
+fn gentle_slopes_can_be_stood_on() {
+    let field = slope(20.0);
+    let mut walker = Walker::new(Vec3::new(0.0, 1.0, 0.0), WalkSettings::default());
+
+    run(&mut walker, &field, Vec3::ZERO, false, 2.0);
+    let rest = walker.position;
+    run(&mut walker, &field, Vec3::ZERO, false, 2.0);
+    assert!(walker.on_ground);
+    assert!(walker.position.distance(rest) < 0.05, "slid from {} to {}", rest, walker.position);
+}
+
+#[test]
+fn steep_slopes_are_slid_down() {
+    let field = slope(65.0);
+    let mut walker = Walker::new(Vec3::new(0.0, 1.0, 0.0), WalkSettings::default());
+
+    run(&mut walker, &field, Vec3::ZERO, false, 0.5);
+    let start = walker.position;
+    run(&mut walker, &field, Vec3::ZERO, false, 0.5);
+    assert!(!walker.on_ground);
+    assert!(walker.position.y < start.y - 0.5, "stuck on the slope at {}", walker.position);
+
+    // Walking up it doesn't get anywhere either
+    let mut climber = Walker::new(Vec3::new(-1.0, 0.0, 0.0), WalkSettings::default());
+    run(&mut climber, &field, Vec3::X, false, 3.0);
+    assert!(climber.position.y < 1.0, "climbed to {}", climber.position);
+}
+
+#[test]
+fn walking_down_slopes_stays_on_the_ground() {
+    let field = slope(30.0);
+    let mut walker = Walker::new(Vec3::new(0.0, 1.0, 0.0), WalkSettings::default());
+    run(&mut walker, &field, Vec3::ZERO, false, 1.0);
+    assert!(walker.on_ground);
+
+    for _ in 0..120 {
+        walker.simulate(&field, Vec3::NEG_X, false, true, TICK);
+        assert!(walker.on_ground, "left the ground at {}", walker.position);
+    }
+}
+
+#[test]
+fn nothing_moves_over_unloaded_terrain() {
+    let unloaded = UnloadedField(flat_ground());
+    let start = Vec3::new(0.0, 5.0, 0.0);
+    let mut walker = Walker::new(start, WalkSettings::default());
+
+    run(&mut walker, &unloaded, Vec3::X, false, 1.0);
+    assert_eq!(walker.position, start);
+}
+
+struct UnloadedField<F: DensityField>(F);
+
+impl<F: DensityField> DensityField for UnloadedField<F> {
+    fn density(&self, position: Vec3) -> Option<f32> {
+        if position.y > 2.0 { None } else { self.0.density(position) }
+    }
+
+    fn isolevel(&self) -> f32 {
+        self.0.isolevel()
+    }
+}